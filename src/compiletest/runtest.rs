@@ -339,15 +339,18 @@ fn check_expected_errors(expected_errors: ~[errors::ExpectedError],
     //    filename:line1:col1: line2:col2: *warning:* msg
     // where line1:col1: is the starting point, line2:col2:
     // is the ending point, and * represents ANSI color codes.
+    // The labels of multi-span errors are matched as notes.
+    let mut src_line = None;
     for str::split_char(ProcRes.stderr, '\n').each |line| {
+        let line = label_as_note(copy *line, testfile, &mut src_line);
         let mut was_expected = false;
         for vec::eachi(expected_errors) |i, ee| {
             if !found_flags[i] {
                 debug!("prefix=%s ee.kind=%s ee.msg=%s line=%s",
-                       prefixes[i], ee.kind, ee.msg, *line);
-                if (str::starts_with(*line, prefixes[i]) &&
-                    str::contains(*line, ee.kind) &&
-                    str::contains(*line, ee.msg)) {
+                       prefixes[i], ee.kind, ee.msg, line);
+                if (str::starts_with(line, prefixes[i]) &&
+                    str::contains(line, ee.kind) &&
+                    str::contains(line, ee.msg)) {
                     found_flags[i] = true;
                     was_expected = true;
                     break;
@@ -356,13 +359,13 @@ fn check_expected_errors(expected_errors: ~[errors::ExpectedError],
         }
 
         // ignore this msg which gets printed at the end
-        if str::contains(*line, ~"aborting due to") {
+        if str::contains(line, ~"aborting due to") {
             was_expected = true;
         }
 
-        if !was_expected && is_compiler_error_or_warning(*line) {
+        if !was_expected && is_compiler_error_or_warning(line) {
             fatal_ProcRes(fmt!("unexpected compiler error or warning: '%s'",
-                               *line),
+                               line),
                           ProcRes);
         }
     }
@@ -376,6 +379,36 @@ fn check_expected_errors(expected_errors: ~[errors::ExpectedError],
     }
 }
 
+// Labels of multi-span errors are printed under the source line they
+// point at:
+//    filename:line source text
+//                  ---- label
+// This rewrites them into `filename:line: note: label`, and remembers the
+// line of each source line it sees in `src_line`.
+fn label_as_note(line: ~str, testfile: &Path,
+                 src_line: &mut Option<uint>) -> ~str {
+    let prefix = testfile.to_str() + ~":";
+    if str::starts_with(line, prefix) {
+        let mut i = prefix.len();
+        *src_line = if scan_integer(copy line, &mut i) &&
+                scan_char(copy line, ' ', &mut i) {
+            uint::from_str(str::slice(line, prefix.len(), i - 1u))
+        } else {
+            None
+        };
+        return line;
+    }
+    let underline = str::trim_left(line);
+    if src_line.is_some() && (str::starts_with(underline, "-") ||
+                              str::starts_with(underline, "^")) {
+        return fmt!("%s%u: note: %s", prefix, src_line.get(),
+                    str::trim(str::trim_left_chars(underline,
+                                                   ['-', '^', '~'])));
+    }
+    *src_line = None;
+    line
+}
+
 fn is_compiler_error_or_warning(line: ~str) -> bool {
     let mut i = 0u;
     return
@@ -498,8 +531,15 @@ fn compose_and_run_compiler(
 
     for vec::each(props.aux_builds) |rel_ab| {
         let abs_ab = config.aux_base.push_rel(&Path(*rel_ab));
+        // Auxiliary crates are built with their own compile-flags, and are
+        // dynamic libraries unless those ask for a static one
+        let aux_props = load_props(&abs_ab);
+        let crate_type = match aux_props.compile_flags {
+          Some(ref flags) if str::contains(*flags, "--staticlib") => ~[],
+          _ => ~[~"--lib"]
+        };
         let aux_args =
-            make_compile_args(config, props, ~[~"--lib"] + extra_link_args,
+            make_compile_args(config, aux_props, crate_type + extra_link_args,
                               |a,b| make_lib_name(a, b, testfile), &abs_ab);
         let auxres = compose_and_run(config, &abs_ab, aux_args, ~[],
                                      config.compile_lib_path, None);
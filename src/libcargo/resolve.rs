@@ -0,0 +1,226 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Picks one version of every package a crate (transitively) depends on,
+// such that every dependency's version constraint is satisfied.
+
+use core::prelude::*;
+
+use core::str;
+use core::vec;
+use semver::{Constraint, Version};
+use semver;
+use std::sort;
+
+/// One installable version of a package, as offered by some source
+pub struct Candidate {
+    name: ~str,
+    source: ~str,
+    // None for packages that don't list any versions; those can only
+    // satisfy unconstrained dependencies
+    version: Option<Version>,
+    // Dependency queries (`name` or `name@constraint`) of this version
+    deps: ~[~str]
+}
+
+pub struct Requirement {
+    name: ~str,
+    constraint: Constraint,
+    // Who asked for it, for conflict reports
+    required_by: ~str
+}
+
+/// A candidate chosen by the resolver, with the requirement that chose it
+pub struct Resolved {
+    candidate: Candidate,
+    required_by: ~str
+}
+
+pub fn candidate_to_str(c: &Candidate) -> ~str {
+    match c.version {
+        Some(ref v) => c.name + ~" " + v.to_str(),
+        None => copy c.name
+    }
+}
+
+/// Splits a dependency query `name@constraint` into its parts
+pub fn parse_requirement(query: &str, required_by: &str)
+    -> Result<Requirement, ~str> {
+    let (name, cstr) = match str::find_char(query, '@') {
+        Some(i) => (str::slice(query, 0u, i),
+                    str::slice(query, i + 1u, query.len())),
+        None => (query.to_owned(), ~"")
+    };
+    match semver::parse_constraint(cstr) {
+        Ok(c) => Ok(Requirement { name: name, constraint: c,
+                                  required_by: required_by.to_owned() }),
+        Err(e) => Err(fmt!("%s (required by %s)", e, required_by))
+    }
+}
+
+fn satisfies(c: &Candidate, req: &Requirement) -> bool {
+    match c.version {
+        Some(ref v) => req.constraint.matches(v),
+        None => req.constraint.is_any()
+    }
+}
+
+// Depth-first search with backtracking: satisfy the first pending
+// requirement, trying the newest matching version first, and recurse on
+// the rest plus that version's own dependencies.
+fn search(candidates: &[Candidate], pending: &[Requirement],
+          chosen: ~[Resolved], conflicts: &mut ~[~str])
+    -> Option<~[Resolved]> {
+    if pending.is_empty() {
+        return Some(chosen);
+    }
+    let req = &pending[0];
+    let rest = vec::slice(pending, 1u, pending.len());
+
+    match chosen.position(|r| r.candidate.name == req.name) {
+        Some(i) => {
+            if satisfies(&chosen[i].candidate, req) {
+                return search(candidates, rest, chosen, conflicts);
+            }
+            conflicts.push(fmt!("%s requires %s %s, but %s was selected \
+                                 for %s",
+                                req.required_by, req.name,
+                                req.constraint.to_str(),
+                                candidate_to_str(&chosen[i].candidate),
+                                chosen[i].required_by));
+            return None;
+        }
+        None => ()
+    }
+
+    let mut matching = ~[];
+    let mut any_named = false;
+    for candidates.each |c| {
+        if c.name == req.name {
+            any_named = true;
+            if satisfies(c, req) { matching.push(copy *c); }
+        }
+    }
+    if matching.is_empty() {
+        conflicts.push(if any_named {
+            fmt!("%s requires %s %s, but no source has a matching version",
+                 req.required_by, req.name, req.constraint.to_str())
+        } else {
+            fmt!("%s requires %s, which no source provides",
+                 req.required_by, req.name)
+        });
+        return None;
+    }
+
+    pure fn newer(a: &Candidate, b: &Candidate) -> bool {
+        match (&a.version, &b.version) {
+            (&Some(ref x), &Some(ref y)) => x >= y,
+            (&Some(_), &None) => true,
+            (&None, _) => false
+        }
+    }
+    for sort::merge_sort(matching, newer).each |c| {
+        let who = candidate_to_str(c);
+        let mut next = vec::from_slice(rest);
+        let mut bad = false;
+        for c.deps.each |q| {
+            match parse_requirement(*q, who) {
+                Ok(r) => next.push(r),
+                Err(e) => { conflicts.push(e); bad = true; }
+            }
+        }
+        if bad { loop; }
+        let mut now = copy chosen;
+        now.push(Resolved { candidate: copy *c,
+                            required_by: copy req.required_by });
+        match search(candidates, next, now, conflicts) {
+            Some(r) => return Some(r),
+            None => ()
+        }
+    }
+    None
+}
+
+/**
+ * Resolves `reqs` against `candidates`, keeping the packages in `fixed`
+ * at the versions already chosen for them. On success every package
+ * that is needed appears exactly once; on failure the error lists, one
+ * per line, every conflict hit while searching.
+ */
+pub fn resolve(candidates: &[Candidate], reqs: &[Requirement],
+               fixed: &[Resolved]) -> Result<~[Resolved], ~str> {
+    let mut conflicts = ~[];
+    match search(candidates, reqs, vec::from_slice(fixed), &mut conflicts) {
+        Some(r) => Ok(r),
+        None => {
+            let mut seen = ~[];
+            for conflicts.each |c| {
+                if !seen.contains(c) { seen.push(copy *c); }
+            }
+            Err(str::connect(seen, ~"\n"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semver;
+
+    fn cand(name: &str, vers: &str, deps: ~[~str]) -> Candidate {
+        Candidate { name: name.to_owned(), source: ~"central",
+                    version: semver::parse_version(vers), deps: deps }
+    }
+
+    fn req(q: &str) -> Requirement {
+        parse_requirement(q, "root").get()
+    }
+
+    fn chosen(r: &[Resolved], name: &str) -> ~str {
+        for r.each |x| {
+            if x.candidate.name == name.to_owned() {
+                return x.candidate.version.get().to_str();
+            }
+        }
+        fail!(~"not chosen: " + name.to_owned())
+    }
+
+    #[test]
+    fn test_resolve_backtracks() {
+        let cs = ~[cand("a", "1.0.0", ~[~"c@^1.0"]),
+                   cand("a", "2.0.0", ~[~"c@^2.0"]),
+                   cand("b", "1.0.0", ~[~"c@~1.1"]),
+                   cand("c", "1.1.4", ~[]),
+                   cand("c", "2.0.0", ~[])];
+        // The newest `a` wants c 2.x, which `b` can't live with
+        let r = resolve(cs, [req("a"), req("b")], []).get();
+        assert r.len() == 3u;
+        assert chosen(r, "a") == ~"1.0.0";
+        assert chosen(r, "c") == ~"1.1.4";
+    }
+
+    #[test]
+    fn test_resolve_conflict_report() {
+        let cs = ~[cand("a", "1.0.0", ~[~"c@^2.0"]),
+                   cand("c", "1.0.0", ~[])];
+        match resolve(cs, [req("a"), req("d")], []) {
+            Ok(_) => fail!(),
+            Err(e) => {
+                assert str::contains(e, "a 1.0.0 requires c ^2.0");
+            }
+        }
+        match resolve(cs, [req("c@1.0"), req("d")], []) {
+            Ok(_) => fail!(),
+            Err(e) => {
+                assert e == ~"root requires d, which no source provides";
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Version numbers and the constraints crates place on their dependencies'
+// versions, e.g. `extern mod foo(vers = "^1.2")`.
+
+use core::prelude::*;
+
+use core::cmp;
+use core::str;
+use core::uint;
+use core::vec;
+
+#[deriving_eq]
+pub struct Version {
+    major: uint,
+    minor: uint,
+    patch: uint
+}
+
+pub impl Version : cmp::Ord {
+    pure fn lt(&self, other: &Version) -> bool {
+        if self.major != other.major { return self.major < other.major; }
+        if self.minor != other.minor { return self.minor < other.minor; }
+        self.patch < other.patch
+    }
+    pure fn le(&self, other: &Version) -> bool { !(*other).lt(&(*self)) }
+    pure fn ge(&self, other: &Version) -> bool { !(*self).lt(other)     }
+    pure fn gt(&self, other: &Version) -> bool { (*other).lt(&(*self))  }
+}
+
+pub impl Version : ToStr {
+    pure fn to_str(&self) -> ~str {
+        fmt!("%u.%u.%u", self.major, self.minor, self.patch)
+    }
+}
+
+// Parses `major[.minor[.patch]]`, also returning how many of the three
+// components were actually written, which `~` and `=` care about
+fn parse_partial(s: &str) -> Option<(Version, uint)> {
+    let parts = str::split_char(str::trim(s), '.');
+    if parts.is_empty() || parts.len() > 3u {
+        return None;
+    }
+    let mut nums = ~[];
+    for parts.each |p| {
+        match uint::from_str(*p) {
+            Some(n) => nums.push(n),
+            None => return None
+        }
+    }
+    let given = nums.len();
+    while nums.len() < 3u { nums.push(0u); }
+    Some((Version { major: nums[0], minor: nums[1], patch: nums[2] },
+          given))
+}
+
+/// Parses a version such as `1.2.3`; missing components are zero
+pub fn parse_version(s: &str) -> Option<Version> {
+    match parse_partial(s) {
+        Some((v, _)) => Some(v),
+        None => None
+    }
+}
+
+#[deriving_eq]
+pub enum Op {
+    OpEq,
+    OpGt,
+    OpGe,
+    OpLt,
+    OpLe,
+    OpTilde,
+    OpCaret
+}
+
+pub struct Predicate {
+    op: Op,
+    version: Version,
+    // Number of version components written, e.g. 2 for `~1.2`
+    given: uint
+}
+
+impl Predicate {
+    pure fn matches(&self, v: &Version) -> bool {
+        let p = &self.version;
+        match self.op {
+            OpEq => match self.given {
+                1u => v.major == p.major,
+                2u => v.major == p.major && v.minor == p.minor,
+                _ => *v == *p
+            },
+            OpGt => *v > *p,
+            OpGe => *v >= *p,
+            OpLt => *v < *p,
+            OpLe => *v <= *p,
+            // `~1.2.3` allows patch updates, `~1` minor ones
+            OpTilde => *v >= *p && v.major == p.major &&
+                (self.given == 1u || v.minor == p.minor),
+            // `^1.2.3` allows anything that is not a breaking change:
+            // before 1.0 the minor version is the breaking one
+            OpCaret => *v >= *p && v.major == p.major &&
+                (p.major > 0u || self.given == 1u || v.minor == p.minor)
+        }
+    }
+}
+
+pub impl Predicate : ToStr {
+    pure fn to_str(&self) -> ~str {
+        let op = match self.op {
+            OpEq => ~"=", OpGt => ~">", OpGe => ~">=", OpLt => ~"<",
+            OpLe => ~"<=", OpTilde => ~"~", OpCaret => ~"^"
+        };
+        let parts = [self.version.major, self.version.minor,
+                     self.version.patch];
+        let written = vec::slice(parts, 0u, self.given).map(|n| n.to_str());
+        op + str::connect(written, ~".")
+    }
+}
+
+/**
+ * A set of predicates that must all hold, written as a comma-separated
+ * list such as `>= 1.2, < 2`. A bare version means exactly that version;
+ * an empty constraint (or `*`) matches any version.
+ */
+pub struct Constraint {
+    preds: ~[Predicate]
+}
+
+impl Constraint {
+    static fn any() -> Constraint { Constraint { preds: ~[] } }
+
+    pure fn is_any(&self) -> bool { self.preds.is_empty() }
+
+    pure fn matches(&self, v: &Version) -> bool {
+        self.preds.all(|p| p.matches(v))
+    }
+}
+
+pub impl Constraint : ToStr {
+    pure fn to_str(&self) -> ~str {
+        if self.is_any() {
+            ~"*"
+        } else {
+            str::connect(self.preds.map(|p| p.to_str()), ~", ")
+        }
+    }
+}
+
+/// Parses a constraint, returning a message describing what is wrong
+pub fn parse_constraint(s: &str) -> Result<Constraint, ~str> {
+    let s = str::trim(s);
+    if s.is_empty() || s == ~"*" {
+        return Ok(Constraint::any());
+    }
+    let mut preds = ~[];
+    for str::split_char(s, ',').each |part| {
+        let part = str::trim(*part);
+        let (op, rest) = if str::starts_with(part, ">=") {
+            (OpGe, 2u)
+        } else if str::starts_with(part, "<=") {
+            (OpLe, 2u)
+        } else if str::starts_with(part, ">") {
+            (OpGt, 1u)
+        } else if str::starts_with(part, "<") {
+            (OpLt, 1u)
+        } else if str::starts_with(part, "=") {
+            (OpEq, 1u)
+        } else if str::starts_with(part, "~") {
+            (OpTilde, 1u)
+        } else if str::starts_with(part, "^") {
+            (OpCaret, 1u)
+        } else {
+            (OpEq, 0u)
+        };
+        match parse_partial(str::slice(part, rest, part.len())) {
+            Some((v, given)) => {
+                preds.push(Predicate { op: op, version: v, given: given })
+            }
+            None => {
+                return Err(fmt!("invalid version constraint '%s' in '%s'",
+                                part, s));
+            }
+        }
+    }
+    Ok(Constraint { preds: preds })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(c: &str, v: &str) -> bool {
+        parse_constraint(c).get().matches(&parse_version(v).get())
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert parse_version("1.2.3") ==
+            Some(Version { major: 1u, minor: 2u, patch: 3u });
+        assert parse_version("2") ==
+            Some(Version { major: 2u, minor: 0u, patch: 0u });
+        assert parse_version("1.x").is_none();
+        assert parse_version("1.2.3.4").is_none();
+        assert parse_version("1.10.0").get() > parse_version("1.9.9").get();
+    }
+
+    #[test]
+    fn test_constraints() {
+        assert matches("", "0.1.0");
+        assert matches("*", "3.0.0");
+        assert matches("1.2.3", "1.2.3");
+        assert !matches("1.2.3", "1.2.4");
+        assert matches("=1.2", "1.2.9");
+        assert matches(">= 1.2, < 2", "1.9.0");
+        assert !matches(">= 1.2, < 2", "2.0.0");
+        assert matches("~1.2.3", "1.2.9");
+        assert !matches("~1.2.3", "1.3.0");
+        assert matches("~1", "1.7.0");
+        assert matches("^1.2", "1.9.0");
+        assert !matches("^1.2", "2.0.0");
+        assert !matches("^0.3", "0.4.0");
+        assert matches("^0.3", "0.3.7");
+        assert parse_constraint(">= one").is_err();
+        assert parse_constraint(">=1.2,<2").get().to_str() == ~">=1.2, <2";
+    }
+}
@@ -19,3 +19,62 @@ impl Clone for () {
     #[inline(always)]
     fn clone(&self) -> () { () }
 }
+
+impl<T> Clone for @T {
+    /// Returns another managed pointer to the same box
+    #[inline(always)]
+    fn clone(&self) -> @T { *self }
+}
+
+impl<T: Clone> Clone for ~T {
+    /// Returns a new owned box holding a clone of the contents
+    #[inline(always)]
+    fn clone(&self) -> ~T { ~(**self).clone() }
+}
+
+impl Clone for ~str {
+    #[inline(always)]
+    fn clone(&self) -> ~str { copy *self }
+}
+
+macro_rules! clone_impl(
+    ($t:ty) => {
+        impl Clone for $t {
+            #[inline(always)]
+            fn clone(&self) -> $t { *self }
+        }
+    }
+)
+
+clone_impl!(int)
+clone_impl!(i8)
+clone_impl!(i16)
+clone_impl!(i32)
+clone_impl!(i64)
+
+clone_impl!(uint)
+clone_impl!(u8)
+clone_impl!(u16)
+clone_impl!(u32)
+clone_impl!(u64)
+
+clone_impl!(float)
+clone_impl!(f32)
+clone_impl!(f64)
+
+clone_impl!(bool)
+clone_impl!(char)
+
+#[test]
+fn test_owned_clone() {
+    let a: ~int = ~5i;
+    let b: ~int = a.clone();
+    assert a == b;
+}
+
+#[test]
+fn test_managed_clone() {
+    let a: @int = @5i;
+    let b: @int = a.clone();
+    assert a == b;
+}
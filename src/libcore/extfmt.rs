@@ -14,15 +14,17 @@
 //!
 //! ~~~~~~
 //! Format := '%' Parameter? Flag* Width? Precision? Type
-//! Parameter := [0-9]+ '$'
+//! Parameter := [0-9]+ '$' | '(' Name ')'
 //! Flag := [ 0#+-]
-//! Width := Parameter | [0-9]+
-//! Precision := '.' [0-9]+
+//! Width := '*' Parameter? | [0-9]+
+//! Precision := '.' Width?
 //! Type := [bcdfiostuxX?]
 //! ~~~~~~
 //!
-//! * Parameter is the 1-based argument to apply the format to. Currently not
-//! implemented.
+//! * Parameter is the argument to apply the format to: either the 1-based
+//! index of an argument after the format string, or the name of an argument
+//! given as `name = expr`. Without one, each conversion takes the argument
+//! after the one taken by the previous conversion.
 //! * Flag 0 causes leading zeros to be used for padding when converting
 //! numbers.
 //! * Flag # causes the conversion to be done in an *alternative* manner.
@@ -35,6 +37,12 @@
 //! * Precision specifies the minimum number of digits for integral types
 //! and the minimum number
 //! of decimal places for float.
+//! * A width or precision of `*` is taken from a `uint` argument, either the
+//! one given by its parameter or, without one, the next argument, which
+//! comes before the argument being converted.
+//!
+//! An argument may be referred to more than once, in which case it is
+//! evaluated only once, but every argument must be referred to.
 //!
 //! The types currently supported are:
 //!
@@ -73,6 +81,7 @@ combinations at the moment.
 Example:
 
 debug!("hello, %s!", "world");
+debug!("%(who)s sent %2$*1$u bytes", 8u, n, who = name);
 
 */
 
@@ -128,10 +137,17 @@ pub mod ct {
         FlagAlternate,
     }
 
+    // A reference to an argument
+    #[deriving_eq]
+    pub enum Param {
+        ParamIndex(uint),
+        ParamName(~str),
+    }
+
     #[deriving_eq]
     pub enum Count {
         CountIs(uint),
-        CountIsParam(uint),
+        CountIsParam(Param),
         CountIsNextParam,
         CountImplied,
     }
@@ -151,7 +167,7 @@ pub mod ct {
     // A formatted conversion from an expression to a string
     #[deriving_eq]
     pub struct Conv {
-        param: Option<uint>,
+        param: Option<Param>,
         flags: ~[Flag],
         width: Count,
         precision: Count,
@@ -245,16 +261,43 @@ pub mod ct {
     }
 
     pub fn parse_parameter(s: &str, i: uint, lim: uint) ->
-        Parsed<Option<uint>> {
+        Parsed<Option<Param>> {
         if i >= lim { return Parsed::new(None, i); }
 
+        if s[i] == '(' as u8 {
+            return match parse_name(s, i + 1, lim) {
+                Some(name) if name.next < lim && s[name.next] == ')' as u8 =>
+                    Parsed::new(Some(ParamName(name.val)), name.next + 1),
+                _ => Parsed::new(None, i)
+            };
+        }
+
         match peek_num(s, i, lim) {
             Some(num) if num.next < lim && s[num.next] == '$' as u8 =>
-                Parsed::new(Some(num.val), num.next + 1),
+                Parsed::new(Some(ParamIndex(num.val)), num.next + 1),
             _ => Parsed::new(None, i)
         }
     }
 
+    // An identifier, as used for the name of an argument
+    fn parse_name(s: &str, i: uint, lim: uint) -> Option<Parsed<~str>> {
+        let mut j = i;
+        while j < lim {
+            let c = s[j] as char;
+            if !((c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z') ||
+                 (c >= '0' && c <= '9') || c == '_') {
+                break;
+            }
+            j += 1;
+        }
+
+        if j > i && !(s[i] >= '0' as u8 && s[i] <= '9' as u8) {
+            Some(Parsed::new(s.slice(i, j), j))
+        } else {
+            None
+        }
+    }
+
     pub fn parse_flags(s: &str, i: uint, lim: uint) -> Parsed<~[Flag]> {
         let mut i = i;
         let mut flags = ~[];
@@ -346,7 +389,8 @@ pub mod ct {
         assert test("", CountImplied, 0);
         assert test("*", CountIsNextParam, 1);
         assert test("*1", CountIsNextParam, 1);
-        assert test("*1$", CountIsParam(1), 3);
+        assert test("*1$", CountIsParam(ParamIndex(1)), 3);
+        assert test("*(w)", CountIsParam(ParamName(~"w")), 4);
         assert test("123", CountIs(123), 3);
     }
 
@@ -396,14 +440,19 @@ pub mod ct {
 
     #[test]
     fn test_parse_parameter() {
-        fn test(s: &str, param: Option<uint>, next: uint) -> bool {
+        fn test(s: &str, param: Option<Param>, next: uint) -> bool {
             parse_parameter(s, 0, s.len()) == Parsed::new(param, next)
         }
 
         assert test("", None, 0);
         assert test("foo", None, 0);
         assert test("123", None, 0);
-        assert test("123$", Some(123), 4);
+        assert test("123$", Some(ParamIndex(123)), 4);
+        assert test("(foo)", Some(ParamName(~"foo")), 5);
+        assert test("(a_1)s", Some(ParamName(~"a_1")), 5);
+        assert test("(foo", None, 0);
+        assert test("()", None, 0);
+        assert test("(1a)", None, 0);
     }
 
     #[test]
@@ -416,7 +465,7 @@ pub mod ct {
         assert test(".", CountIs(0), 1);
         assert test(".*", CountIsNextParam, 2);
         assert test(".*1", CountIsNextParam, 2);
-        assert test(".*1$", CountIsParam(1), 4);
+        assert test(".*1$", CountIsParam(ParamIndex(1)), 4);
         assert test(".123", CountIs(123), 4);
     }
 
@@ -29,6 +29,7 @@ use prelude::*;
 use ptr;
 use result;
 use str;
+use task;
 use uint;
 use vec;
 
@@ -985,8 +986,42 @@ pub fn buffered_file_writer(path: &Path) -> Result<Writer, ~str> {
 // FIXME (#2004) it would be great if this could be a const
 // FIXME (#2004) why are these different from the way stdin() is
 // implemented?
-pub fn stdout() -> Writer { fd_writer(libc::STDOUT_FILENO as c_int, false) }
-pub fn stderr() -> Writer { fd_writer(libc::STDERR_FILENO as c_int, false) }
+// Key for the writer that stands in for stdout in the current task
+fn task_stdout_key(_v: @Writer) {}
+
+/**
+ * Redirects `stdout`, and with it `print` and `println`, to `w` for the
+ * rest of the current task. Other tasks, including ones spawned by this
+ * one, keep writing to the real stdout.
+ */
+pub fn set_task_stdout(w: Writer) {
+    unsafe { task::local_data::local_data_set(task_stdout_key, @w); }
+}
+
+pub fn stdout() -> Writer {
+    match unsafe { task::local_data::local_data_get(task_stdout_key) } {
+        Some(w) => *w,
+        None => fd_writer(libc::STDOUT_FILENO as c_int, false)
+    }
+}
+
+// Key for the writer that stands in for stderr in the current task
+fn task_stderr_key(_v: @Writer) {}
+
+/**
+ * Redirects `stderr` to `w` for the rest of the current task, the same way
+ * as `set_task_stdout`.
+ */
+pub fn set_task_stderr(w: Writer) {
+    unsafe { task::local_data::local_data_set(task_stderr_key, @w); }
+}
+
+pub fn stderr() -> Writer {
+    match unsafe { task::local_data::local_data_get(task_stderr_key) } {
+        Some(w) => *w,
+        None => fd_writer(libc::STDERR_FILENO as c_int, false)
+    }
+}
 
 pub fn print(s: &str) { stdout().write_str(s); }
 pub fn println(s: &str) { stdout().write_line(s); }
@@ -12,8 +12,10 @@
 
 use cast::transmute;
 use io;
+use io::WriterUtil;
 use libc;
 use repr;
+use task::local_data::{local_data_get, local_data_set};
 use vec;
 
 #[nolink]
@@ -45,9 +47,29 @@ pub fn console_off() {
     }
 }
 
+// Key for the writer that receives the current task's log messages
+fn task_logger_key(_v: @io::Writer) {}
+
+/**
+ * Sends the log messages of the current task to `w` instead of the
+ * console for the rest of the task. Logging levels are still decided by
+ * RUST_LOG.
+ */
+pub fn set_task_logger(w: io::Writer) {
+    unsafe { local_data_set(task_logger_key, @w); }
+}
+
 #[cfg(notest)]
 #[lang="log_type"]
 pub fn log_type<T>(level: u32, object: &T) {
+    match unsafe { local_data_get(task_logger_key) } {
+        Some(w) => {
+            repr::write_repr(*w, object);
+            w.write_char('\n');
+            return;
+        }
+        None => ()
+    }
     let bytes = do io::with_bytes_writer |writer| {
         repr::write_repr(writer, object);
     };
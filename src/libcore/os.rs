@@ -741,6 +741,43 @@ pub fn copy_file(from: &Path, to: &Path) -> bool {
     }
 }
 
+/**
+ * Renames a file, replacing `to` if it already exists. On unix the
+ * replacement is atomic: other processes see either the old or the new
+ * file under `to`, never neither.
+ */
+pub fn rename_file(from: &Path, to: &Path) -> bool {
+    return do_rename_file(from, to);
+
+    #[cfg(windows)]
+    fn do_rename_file(from: &Path, to: &Path) -> bool {
+        // The C runtime refuses to rename over an existing file
+        if path_exists(to) && !remove_file(to) {
+            return false;
+        }
+        unsafe {
+            do as_c_charp(from.to_str()) |frombuf| {
+                do as_c_charp(to.to_str()) |tobuf| {
+                    libc::funcs::c95::stdio::rename(frombuf, tobuf) ==
+                        (0 as c_int)
+                }
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    fn do_rename_file(from: &Path, to: &Path) -> bool {
+        unsafe {
+            do as_c_charp(from.to_str()) |frombuf| {
+                do as_c_charp(to.to_str()) |tobuf| {
+                    libc::funcs::c95::stdio::rename(frombuf, tobuf) ==
+                        (0 as c_int)
+                }
+            }
+        }
+    }
+}
+
 /// Deletes an existing file
 pub fn remove_file(p: &Path) -> bool {
     return unlink(p);
@@ -14,11 +14,13 @@ use cast;
 use cmp::{Eq, Ord};
 use gc;
 use io;
+use io::WriterUtil;
 use libc;
 use libc::{c_void, c_char, size_t};
 use ptr;
 use repr;
 use str;
+use task::local_data::{local_data_get, local_data_set};
 use vec;
 
 pub type FreeGlue = fn(*TypeDesc, *c_void);
@@ -50,6 +52,9 @@ extern mod rusti {
 extern mod rustrt {
     #[rust_stack]
     unsafe fn rust_upcall_fail(expr: *c_char, file: *c_char, line: size_t);
+    unsafe fn rust_task_track_heap_bytes();
+    unsafe fn rust_task_heap_bytes(live: *mut size_t, peak: *mut size_t);
+    unsafe fn rust_task_reset_heap_peak();
 }
 
 /// Compares contents of two pointers using the default method.
@@ -128,6 +133,35 @@ pub pure fn log_str<T>(t: &T) -> ~str {
     }
 }
 
+/**
+ * Starts counting the bytes allocated in the current task's local heap.
+ * Counting costs something on every allocation, so nothing is counted
+ * until this is called.
+ */
+pub fn track_task_heap_bytes() {
+    unsafe { rustrt::rust_task_track_heap_bytes(); }
+}
+
+/**
+ * Returns the number of bytes allocated in the current task's local heap
+ * since `track_task_heap_bytes` was called, less those freed since, and
+ * the most there have been at once since then or since the last call to
+ * `reset_task_heap_peak`. Both are 0 if the heap isn't being tracked.
+ */
+pub fn task_heap_bytes() -> (uint, uint) {
+    let mut live = 0 as size_t, peak = 0 as size_t;
+    unsafe {
+        rustrt::rust_task_heap_bytes(ptr::to_mut_unsafe_ptr(&mut live),
+                                     ptr::to_mut_unsafe_ptr(&mut peak));
+    }
+    (live as uint, peak as uint)
+}
+
+/// Restarts tracking of the peak task heap usage from the current usage
+pub fn reset_task_heap_peak() {
+    unsafe { rustrt::rust_task_reset_heap_peak(); }
+}
+
 /** Initiate task failure */
 pub pure fn begin_unwind(msg: ~str, file: ~str, line: uint) -> ! {
     do str::as_buf(msg) |msg_buf, _msg_len| {
@@ -141,9 +175,28 @@ pub pure fn begin_unwind(msg: ~str, file: ~str, line: uint) -> ! {
     }
 }
 
+// Key for the writer that the current task's failure message is copied to
+fn task_failure_key(_v: @io::Writer) {}
+
+/**
+ * Copies the message that the current task fails with, if it does, to `w`
+ * as well as the console. Test runners use this to report why a test
+ * failed.
+ */
+pub fn set_task_failure_writer(w: io::Writer) {
+    unsafe { local_data_set(task_failure_key, @w); }
+}
+
 // FIXME #4427: Temporary until rt::rt_fail_ goes away
 pub pure fn begin_unwind_(msg: *c_char, file: *c_char, line: size_t) -> ! {
     unsafe {
+        match local_data_get(task_failure_key) {
+            Some(w) => {
+                w.write_str(fmt!("%s, %s:%u", str::raw::from_c_str(msg),
+                                 str::raw::from_c_str(file), line as uint));
+            }
+            None => ()
+        }
         gc::cleanup_stack_for_failure();
         rustrt::rust_upcall_fail(msg, file, line);
         cast::transmute(())
@@ -152,8 +205,10 @@ pub pure fn begin_unwind_(msg: *c_char, file: *c_char, line: size_t) -> ! {
 
 #[cfg(test)]
 pub mod tests {
+    use at_vec;
     use cast;
     use sys::{Closure, pref_align_of, size_of, nonzero_size_of};
+    use sys::{reset_task_heap_peak, task_heap_bytes, track_task_heap_bytes};
 
     #[test]
     pub fn size_of_basic() {
@@ -186,6 +241,19 @@ pub mod tests {
         assert nonzero_size_of::<uint>() == size_of::<uint>();
     }
 
+    #[test]
+    pub fn task_heap_bytes_basic() {
+        track_task_heap_bytes();
+        reset_task_heap_peak();
+        let (live, peak) = task_heap_bytes();
+        assert peak == live;
+        let v = at_vec::from_elem(4096u, 0u8);
+        let (live2, peak2) = task_heap_bytes();
+        assert live2 >= live + 4096u;
+        assert peak2 >= live2;
+        assert v.len() == 4096u;
+    }
+
     #[test]
     pub fn align_of_basic() {
         assert pref_align_of::<u8>() == 1u;
@@ -15,6 +15,7 @@
 use container::{Container, Mutable};
 use cast::transmute;
 use cast;
+use clone::Clone;
 use cmp::{Eq, Ord};
 use iter::BaseIter;
 use iter;
@@ -1604,6 +1605,11 @@ pure fn le<T: Ord>(a: &[T], b: &[T]) -> bool { !lt(b, a) }
 pure fn ge<T: Ord>(a: &[T], b: &[T]) -> bool { !lt(a, b) }
 pure fn gt<T: Ord>(a: &[T], b: &[T]) -> bool { lt(b, a)  }
 
+impl<T: Clone> Clone for ~[T] {
+    /// Returns a new vector holding clones of the elements
+    fn clone(&self) -> ~[T] { map(*self, |e| e.clone()) }
+}
+
 #[cfg(notest)]
 impl<T: Ord> Ord for &[T] {
     #[inline(always)]
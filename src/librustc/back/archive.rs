@@ -0,0 +1,188 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * Static libraries.
+ *
+ * A static library is an `ar` archive, `lib<name>-<hash>-<vers>.rlib`,
+ * holding the object file of the crate and a member with the contents of
+ * its metadata section. The loader reads the metadata straight from that
+ * member rather than having LLVM look through the objects, and linkers
+ * ignore it since it isn't an object file.
+ */
+
+use core::prelude::*;
+
+use driver::session;
+use driver::session::Session;
+use metadata::loader;
+
+use core::io::Writer;
+use core::io;
+use core::os;
+use core::run;
+use core::str;
+use core::uint;
+use core::vec;
+use std::tempfile;
+
+/// The name of the member holding the metadata. It's short enough to fit
+/// in the member header for every flavour of `ar`.
+pub const METADATA_MEMBER: &str = "rust.metadata";
+
+// The magic string at the start of every archive
+const ARMAG: &str = "!<arch>\n";
+// The string at the end of every member header
+const ARFMAG: &str = "`\n";
+const HEADER_SIZE: uint = 60u;
+
+/// Packs `obj_filename`, and its metadata as a member of its own, into a
+/// static library at `output`
+pub fn create_staticlib(sess: Session, output: &Path, obj_filename: &Path) {
+    let os = session::sess_os_to_meta_os(sess.targ_cfg.os);
+    let metadata = match loader::get_metadata_section_bytes(os,
+                                                            obj_filename) {
+      Some(bytes) => bytes,
+      None => sess.fatal(fmt!("no metadata in `%s`", obj_filename.to_str()))
+    };
+
+    // ar names the members after the files they come from
+    let tmpdir = match tempfile::mkdtemp(&os::tmpdir(), "rustc") {
+      Some(dir) => dir,
+      None => sess.fatal(~"could not create a temporary directory")
+    };
+    let meta_path = tmpdir.push(METADATA_MEMBER);
+    match io::file_writer(&meta_path, ~[io::Create, io::Truncate]) {
+      Ok(w) => w.write(metadata),
+      Err(e) => {
+        sess.fatal(fmt!("could not write metadata to %s: %s",
+                        meta_path.to_str(), e));
+      }
+    }
+
+    // ar adds to an existing archive, which may hold stale members
+    os::remove_file(output);
+
+    let ar_prog = if sess.targ_cfg.os == session::os_android {
+        ~"arm-linux-androideabi-ar"
+    } else {
+        ~"ar"
+    };
+    let ar_args = ~[~"crs", output.to_str(), obj_filename.to_str(),
+                    meta_path.to_str()];
+    debug!("%s args: %s", ar_prog, str::connect(ar_args, ~" "));
+    let prog = run::program_output(ar_prog, ar_args);
+    os::remove_file(&meta_path);
+    os::remove_dir(&tmpdir);
+    if 0 != prog.status {
+        sess.err(fmt!("creating archive with `%s` failed with code %d",
+                      ar_prog, prog.status));
+        sess.note(fmt!("%s arguments: %s",
+                       ar_prog, str::connect(ar_args, ~" ")));
+        sess.note(prog.err + prog.out);
+        sess.abort_if_errors();
+    }
+}
+
+/// The contents of the member `name` of the archive at `archive`, or None
+/// if it has no such member or isn't an archive
+pub fn read_member(archive: &Path, name: &str) -> Option<~[u8]> {
+    match io::read_whole_file(archive) {
+      Ok(bytes) => find_member(bytes, name),
+      Err(_) => None
+    }
+}
+
+// Looks `name` up in the bytes of a whole archive. Only names that fit in
+// the member header are found, both in the System V form, ending in a
+// slash, and in the BSD one.
+fn find_member(bytes: &[u8], name: &str) -> Option<~[u8]> {
+    if !has_at(bytes, 0u, ARMAG) { return None; }
+
+    let mut pos = ARMAG.len();
+    while pos + HEADER_SIZE <= bytes.len() {
+        if !has_at(bytes, pos + HEADER_SIZE - ARFMAG.len(), ARFMAG) {
+            return None;
+        }
+        let member_name = match header_field(bytes, pos, 16u) {
+          Some(n) => n,
+          None => return None
+        };
+        let size = match header_field(bytes, pos + 48u, 10u) {
+          Some(s) => match uint::from_str(s) {
+            Some(size) => size,
+            None => return None
+          },
+          None => return None
+        };
+        let start = pos + HEADER_SIZE;
+        if start + size > bytes.len() { return None; }
+
+        if member_name == name.to_owned() ||
+            member_name == fmt!("%s/", name) {
+            return Some(vec::slice(bytes, start, start + size));
+        }
+        // Members start at even offsets
+        pos = start + size + size % 2u;
+    }
+    None
+}
+
+// Whether `bytes` has `s` at `pos`
+fn has_at(bytes: &[u8], pos: uint, s: &str) -> bool {
+    if pos + s.len() > bytes.len() { return false; }
+    for uint::range(0u, s.len()) |i| {
+        if bytes[pos + i] != s[i] { return false; }
+    }
+    true
+}
+
+// A field of a member header, without the spaces it is padded with
+fn header_field(bytes: &[u8], start: uint, len: uint) -> Option<~str> {
+    let field = vec::view(bytes, start, start + len);
+    if !str::is_utf8(field) { return None; }
+    Some(str::trim_right(str::from_bytes(field)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_member;
+    use core::str;
+    use core::vec;
+
+    // An archive member as ar writes it, padded to an even length
+    fn member(name: &str, data: &str) -> ~[u8] {
+        let header = fmt!("%-16s%-12s%-6s%-6s%-8s%-10u`\n",
+                          name, "0", "0", "0", "644", data.len());
+        let mut bytes = str::to_bytes(header) + str::to_bytes(data);
+        if data.len() % 2u == 1u { bytes.push('\n' as u8); }
+        bytes
+    }
+
+    #[test]
+    fn test_find_member() {
+        let ar = str::to_bytes("!<arch>\n") + member("crate.o/", "objects")
+            + member("rust.metadata/", "meta");
+        assert find_member(ar, "rust.metadata") ==
+            Some(str::to_bytes("meta"));
+        assert find_member(ar, "crate.o") == Some(str::to_bytes("objects"));
+        assert find_member(ar, "crate").is_none();
+
+        let truncated = vec::slice(ar, 0u, ar.len() - 2u);
+        assert find_member(truncated, "rust.metadata").is_none();
+        assert find_member(str::to_bytes("crate.o"), "crate.o").is_none();
+    }
+
+    #[test]
+    fn test_find_bsd_member() {
+        let ar = str::to_bytes("!<arch>\n") + member("rust.metadata", "m");
+        assert find_member(ar, "rust.metadata") == Some(str::to_bytes("m"));
+    }
+}
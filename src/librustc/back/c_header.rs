@@ -0,0 +1,292 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * C headers for the functions a crate exports to C (`--c-header`).
+ *
+ * Every public `extern fn` with `#[no_mangle]` gets a prototype, and every
+ * struct that those use, directly or through pointers and other structs,
+ * gets a definition with the same layout, named after its path, so that
+ * `geo::Point` is `struct geo_Point`. Integers become the `stdint.h`
+ * types of their width, so `libc::c_int` is `int32_t`, and `int` and
+ * `uint` are `intptr_t` and `uintptr_t`. Pointers to `libc::c_void` are
+ * `void *`. Types with no C equivalent, such as boxes, vectors and
+ * closures, are errors, as are structs with destructors, whose drop flag
+ * C doesn't know about.
+ */
+
+use core::prelude::*;
+
+use middle::ty;
+use util::ppaux::ty_to_str;
+
+use core::char;
+use core::io::WriterUtil;
+use core::io;
+use core::option;
+use core::str;
+use core::vec;
+use std::oldmap::HashMap;
+use syntax::ast;
+use syntax::ast_map::{path_mod, path_name};
+use syntax::attr;
+use syntax::codemap::span;
+use syntax::parse::token::special_idents;
+
+struct Ctxt {
+    tcx: ty::ctxt,
+    // The struct definitions, each after the ones it holds by value
+    mut structs: ~[~str],
+    seen: HashMap<ast::def_id, ()>,
+    // The struct each C struct name was given to
+    names: HashMap<~str, ast::def_id>
+}
+
+/// Writes the C header of `crate` to `path`. Nothing is written if any of
+/// the exported functions can't be declared in C.
+pub fn write_c_header(tcx: ty::ctxt, crate: @ast::crate, path: &Path) {
+    let header = c_header(tcx, crate, guard_name(path));
+    tcx.sess.abort_if_errors();
+    match io::file_writer(path, ~[io::Create, io::Truncate]) {
+      Ok(w) => w.write_str(header),
+      Err(e) => {
+        tcx.sess.err(fmt!("could not write C header to %s: %s",
+                          path.to_str(), e));
+      }
+    }
+}
+
+// The include guard for the header at `path`, such as FOO_H for foo.h
+fn guard_name(path: &Path) -> ~str {
+    let name = str::to_upper(path.filestem().get_or_default(~"rust"));
+    let mut guard = ~"";
+    for str::each_char(name) |c| {
+        str::push_char(&mut guard,
+                       if char::is_alphanumeric(c) { c } else { '_' });
+    }
+    guard + ~"_H"
+}
+
+fn c_header(tcx: ty::ctxt, crate: @ast::crate, guard: ~str) -> ~str {
+    let cx = Ctxt { tcx: tcx, structs: ~[], seen: HashMap(),
+                    names: HashMap() };
+    let mut fns = ~[];
+    for exported_fns(crate.node.module).each |item| {
+        match c_prototype(&cx, *item) {
+          Some(proto) => fns.push(proto),
+          None => ()
+        }
+    }
+
+    let mut out = ~"/* Generated by rustc --c-header. Do not edit. */\n\n";
+    out += fmt!("#ifndef %s\n#define %s\n\n", guard, guard);
+    out += ~"#include <stdbool.h>\n#include <stdint.h>\n\n";
+    out += ~"#ifdef __cplusplus\nextern \"C\" {\n#endif\n\n";
+    for cx.structs.each |s| {
+        out += *s + ~"\n";
+    }
+    for fns.each |f| {
+        out += *f + ~"\n";
+    }
+    if !fns.is_empty() { out += ~"\n"; }
+    out += ~"#ifdef __cplusplus\n}\n#endif\n\n";
+    out += fmt!("#endif /* %s */\n", guard);
+    out
+}
+
+// The public `#[no_mangle] extern fn`s of `m` and the modules in it
+fn exported_fns(m: &ast::_mod) -> ~[@ast::item] {
+    let mut fns = ~[];
+    for m.items.each |item| {
+        match item.node {
+          ast::item_fn(_, ast::extern_fn, _, _)
+          if item.vis == ast::public &&
+              attr::attrs_contains_name(item.attrs, "no_mangle") => {
+            fns.push(*item);
+          }
+          ast::item_mod(ref m) => fns.push_all(exported_fns(m)),
+          _ => ()
+        }
+    }
+    fns
+}
+
+fn c_prototype(cx: &Ctxt, item: @ast::item) -> Option<~str> {
+    let (decl, tps) = match /*bad*/copy item.node {
+      ast::item_fn(decl, _, tps, _) => (decl, tps),
+      _ => fail!(~"c_prototype: not a function")
+    };
+    if !tps.is_empty() {
+        cx.tcx.sess.span_err(item.span,
+                             ~"a generic function can't be declared in C");
+        return None;
+    }
+
+    let fty = ty::node_id_to_type(cx.tcx, item.id);
+    let mut args = ~[];
+    let mut ok = true;
+    for vec::each2(ty::ty_fn_args(fty), decl.inputs) |arg, input| {
+        let name = match input.pat.node {
+          ast::pat_ident(_, path, None) if path.idents.len() == 1u => {
+            cx.tcx.sess.str_of(path.idents[0])
+          }
+          _ => ~""
+        };
+        match c_decl(cx, arg.ty, name, false, input.ty.span) {
+          Some(arg) => args.push(arg),
+          None => ok = false
+        }
+    }
+    if args.is_empty() { args.push(~"void"); }
+
+    let name = fmt!("%s(%s)", cx.tcx.sess.str_of(item.ident),
+                    str::connect(args, ~", "));
+    let ret = ty::ty_fn_ret(fty);
+    let proto = match ty::get(ret).sty {
+      ty::ty_nil | ty::ty_bot => Some(~"void " + name),
+      _ => c_decl(cx, ret, name, false, decl.output.span)
+    };
+    if !ok { return None; }
+    option::map(&proto, |proto| *proto + ~";")
+}
+
+// The C declaration of `declarator` as a `t`, const-qualified if `konst`
+// is set, or None after reporting an error at `sp` if `t` has no C
+// equivalent
+fn c_decl(cx: &Ctxt, t: ty::t, declarator: &str, konst: bool,
+          sp: span) -> Option<~str> {
+    let base = match ty::get(t).sty {
+      ty::ty_bool => ~"bool",
+      ty::ty_int(ast::ty_i) => ~"intptr_t",
+      ty::ty_int(ast::ty_char) => ~"uint32_t",
+      ty::ty_int(ast::ty_i8) => ~"int8_t",
+      ty::ty_int(ast::ty_i16) => ~"int16_t",
+      ty::ty_int(ast::ty_i32) => ~"int32_t",
+      ty::ty_int(ast::ty_i64) => ~"int64_t",
+      ty::ty_uint(ast::ty_u) => ~"uintptr_t",
+      ty::ty_uint(ast::ty_u8) => ~"uint8_t",
+      ty::ty_uint(ast::ty_u16) => ~"uint16_t",
+      ty::ty_uint(ast::ty_u32) => ~"uint32_t",
+      ty::ty_uint(ast::ty_u64) => ~"uint64_t",
+      ty::ty_float(ast::ty_f32) => ~"float",
+      ty::ty_float(ast::ty_f) | ty::ty_float(ast::ty_f64) => ~"double",
+      ty::ty_enum(did, _) if item_name(cx, did) == ~"c_void" => ~"void",
+      ty::ty_ptr(mt) | ty::ty_rptr(_, mt) => {
+        let declarator = if konst {
+            fmt!("*const %s", declarator)
+        } else {
+            fmt!("*%s", declarator)
+        };
+        return c_decl(cx, mt.ty, str::trim_right(declarator),
+                      mt.mutbl != ast::m_mutbl, sp);
+      }
+      ty::ty_evec(mt, ty::vstore_fixed(n)) => {
+        let declarator = if str::starts_with(declarator, "*") {
+            fmt!("(%s)[%u]", declarator, n)
+        } else {
+            fmt!("%s[%u]", declarator, n)
+        };
+        return c_decl(cx, mt.ty, declarator, konst, sp);
+      }
+      ty::ty_struct(did, ref substs) if substs.tps.is_empty() => {
+        if !declare_struct(cx, did, substs, sp) { return None; }
+        ~"struct " + struct_name(cx, did)
+      }
+      _ => {
+        cx.tcx.sess.span_err(sp, fmt!("type `%s` has no C representation",
+                                      ty_to_str(cx.tcx, t)));
+        return None;
+      }
+    };
+
+    let base = if konst { ~"const " + base } else { base };
+    Some(if declarator.is_empty() {
+        base
+    } else {
+        fmt!("%s %s", base, declarator)
+    })
+}
+
+// Adds the definition of a struct to the header, once, after those of the
+// structs it holds, and returns whether it has a C representation
+fn declare_struct(cx: &Ctxt, did: ast::def_id, substs: &ty::substs,
+                  sp: span) -> bool {
+    if cx.seen.contains_key(&did) { return true; }
+    // A struct can point to itself
+    cx.seen.insert(did, ());
+
+    let name = struct_name(cx, did);
+    match cx.names.find(&name) {
+      Some(other) => {
+        cx.tcx.sess.span_err(sp, fmt!("structs `%s` and `%s` would both be \
+                                       `struct %s` in C",
+                                      ty::item_path_str(cx.tcx, other),
+                                      ty::item_path_str(cx.tcx, did), name));
+        return false;
+      }
+      None => { cx.names.insert(copy name, did); }
+    }
+    if ty::ty_dtor(cx.tcx, did).is_present() {
+        cx.tcx.sess.span_err(sp, fmt!("struct `%s` has a destructor, so its \
+                                       layout has a drop flag that C \
+                                       doesn't know about", name));
+        return false;
+    }
+
+    let fields = ty::struct_fields(cx.tcx, did, substs);
+    if fields.is_empty() {
+        cx.tcx.sess.span_err(sp, fmt!("struct `%s` has no fields, which C \
+                                       doesn't allow", name));
+        return false;
+    }
+
+    let mut def = fmt!("struct %s {\n", name);
+    let mut ok = true;
+    for fields.eachi |i, field| {
+        let field_name = if field.ident == special_idents::unnamed_field {
+            fmt!("_%u", i)
+        } else {
+            cx.tcx.sess.str_of(field.ident)
+        };
+        match c_decl(cx, field.mt.ty, field_name, false, sp) {
+          Some(decl) => def += fmt!("    %s;\n", decl),
+          None => ok = false
+        }
+    }
+    if ok { cx.structs.push(def + ~"};\n"); }
+    ok
+}
+
+// The C name of a struct: its path with the `::`s made underscores
+fn struct_name(cx: &Ctxt, did: ast::def_id) -> ~str {
+    let names = do ty::item_path(cx.tcx, did).map |elt| {
+        match *elt {
+          path_name(id) | path_mod(id) => cx.tcx.sess.str_of(id)
+        }
+    };
+    str::connect(names, ~"_")
+}
+
+fn item_name(cx: &Ctxt, did: ast::def_id) -> ~str {
+    match ty::item_path(cx.tcx, did).last() {
+      path_name(id) | path_mod(id) => cx.tcx.sess.str_of(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::guard_name;
+
+    #[test]
+    fn test_guard_name() {
+        assert guard_name(&Path("out/libfoo-1.0.h")) == ~"LIBFOO_1_0_H";
+        assert guard_name(&Path("bar.h")) == ~"BAR_H";
+    }
+}
@@ -10,6 +10,7 @@
 
 use core::prelude::*;
 
+use back::archive;
 use back::rpath;
 use driver::session;
 use lib::llvm::llvm;
@@ -35,6 +36,8 @@ use core::os;
 use core::ptr;
 use core::run;
 use core::str;
+use core::u64;
+use core::uint;
 use core::vec;
 use std::oldmap::HashMap;
 use std::sha1::sha1;
@@ -588,13 +591,18 @@ pub fn symbol_hash(tcx: ty::ctxt, symbol_hasher: &hash::State, t: ty::t,
     symbol_hasher.write_str(link_meta.extras_hash);
     symbol_hasher.write_str(~"-");
     symbol_hasher.write_str(encoder::encoded_ty(tcx, t));
-    let mut hash = truncated_hash_result(symbol_hasher);
-    // Prefix with _ so that it never blends into adjacent digits
-    str::unshift_char(&mut hash, '_');
+    let hash = symbol_hash_str(symbol_hasher.result_u64());
     // tjc: allocation is unfortunate; need to change core::hash
     hash.to_managed()
 }
 
+// An h and 16 hex digits, which is how demangle tells the hash from the
+// path. The h keeps it from blending into adjacent digits.
+fn symbol_hash_str(hash: u64) -> ~str {
+    let digits = u64::to_str_radix(hash, 16u);
+    ~"h" + str::from_chars(vec::from_elem(16u - digits.len(), '0')) + digits
+}
+
 pub fn get_symbol_hash(ccx: @crate_ctxt, t: ty::t) -> @str {
     match ccx.type_hashcodes.find(&t) {
       Some(h) => h,
@@ -608,18 +616,21 @@ pub fn get_symbol_hash(ccx: @crate_ctxt, t: ty::t) -> @str {
 
 
 // Name sanitation. LLVM will happily accept identifiers with weird names, but
-// gas doesn't!
+// gas doesn't! It does take `$`, which is used to escape every character
+// that can't be part of an identifier, so that `unsanitize` can undo this.
 pub fn sanitize(s: ~str) -> ~str {
     let mut result = ~"";
     for str::chars_each(s) |c| {
         match c {
-          '@' => result += ~"_sbox_",
-          '~' => result += ~"_ubox_",
-          '*' => result += ~"_ptr_",
-          '&' => result += ~"_ref_",
-          ',' => result += ~"_",
-
-          '{' | '(' => result += ~"_of_",
+          '@' => result += ~"$SP$",
+          '~' => result += ~"$UP$",
+          '*' => result += ~"$PTR$",
+          '&' => result += ~"$BP$",
+          '<' => result += ~"$LT$",
+          '>' => result += ~"$GT$",
+          '(' => result += ~"$LP$",
+          ')' => result += ~"$RP$",
+          ',' => result += ~"$C$",
           'a' .. 'z'
           | 'A' .. 'Z'
           | '0' .. '9'
@@ -627,36 +638,188 @@ pub fn sanitize(s: ~str) -> ~str {
           _ => {
             if c > 'z' && char::is_XID_continue(c) {
                 str::push_char(&mut result, c);
+            } else {
+                result += fmt!("$u%s$", uint::to_str_radix(c as uint, 16u));
             }
           }
         }
     }
 
-    // Underscore-qualify anything that didn't start as an ident.
-    if result.len() > 0u &&
-        result[0] != '_' as u8 &&
-        ! char::is_XID_start(result[0] as char) {
+    // Underscore-qualify anything that didn't start as an ident, so that it
+    // can't blend into the length before it in a mangled name.
+    if needs_qualifying(result) {
         return ~"_" + result;
     }
 
     return result;
 }
 
+// Whether `s` starts with a digit or an escape, after any underscores
+fn needs_qualifying(s: &str) -> bool {
+    let rest = str::trim_left_chars(s, ['_']);
+    rest.len() > 0u &&
+        (char::is_digit(rest[0] as char) || rest[0] == '$' as u8)
+}
+
+/// Undoes `sanitize`, or returns None if `s` isn't something that it
+/// produces
+pub fn unsanitize(s: &str) -> Option<~str> {
+    let mut s = s;
+    if needs_qualifying(s) {
+        if !str::starts_with(s, "_") { return None; }
+        s = str::view(s, 1u, s.len());
+    }
+
+    let mut result = ~"";
+    let mut i = 0u;
+    while i < s.len() {
+        if s[i] != '$' as u8 {
+            let str::CharRange {ch, next} = str::char_range_at(s, i);
+            str::push_char(&mut result, ch);
+            i = next;
+            loop;
+        }
+        let end = match str::find_char_from(s, '$', i + 1u) {
+          Some(end) => end,
+          None => return None
+        };
+        let c = match str::slice(s, i + 1u, end) {
+          ~"SP" => '@',
+          ~"UP" => '~',
+          ~"PTR" => '*',
+          ~"BP" => '&',
+          ~"LT" => '<',
+          ~"GT" => '>',
+          ~"LP" => '(',
+          ~"RP" => ')',
+          ~"C" => ',',
+          code => {
+            if !str::starts_with(code, "u") { return None; }
+            match uint::from_str_radix(str::view(code, 1u, code.len()),
+                                       16u) {
+              Some(n) => n as char,
+              None => return None
+            }
+          }
+        };
+        str::push_char(&mut result, c);
+        i = end + 1u;
+    }
+    Some(result)
+}
+
 pub fn mangle(sess: Session, ss: path) -> ~str {
+    let names = do ss.map |s| {
+        match *s { path_name(s) | path_mod(s) => sess.str_of(s) }
+    };
+    mangle_names(names)
+}
+
+fn mangle_names(names: &[~str]) -> ~str {
     // Follow C++ namespace-mangling style
 
     let mut n = ~"_ZN"; // Begin name-sequence.
 
-    for ss.each |s| {
-        match *s { path_name(s) | path_mod(s) => {
-          let sani = sanitize(sess.str_of(s));
-          n += fmt!("%u%s", str::len(sani), sani);
-        } }
+    for names.each |s| {
+        let sani = sanitize(copy *s);
+        n += fmt!("%u%s", str::len(sani), sani);
     }
     n += ~"E"; // End name-sequence.
     n
 }
 
+// Whether `s` is a hash made by `symbol_hash`
+fn is_symbol_hash(s: &str) -> bool {
+    s.len() == 17u && s[0] == 'h' as u8 &&
+        str::all(str::view(s, 1u, 17u),
+                 |c| char::is_digit(c) || (c >= 'a' && c <= 'f'))
+}
+
+// Reads the name-sequence of a symbol made by `mangle` at the start of
+// `s`, returning its names and the length of the symbol
+fn demangle_prefix(s: &str) -> Option<(~[~str], uint)> {
+    if !str::starts_with(s, "_ZN") { return None; }
+    let mut names = ~[];
+    let mut i = 3u;
+    while i < s.len() && s[i] != 'E' as u8 {
+        let start = i;
+        while i < s.len() && char::is_digit(s[i] as char) { i += 1u; }
+        let len = match uint::from_str(str::view(s, start, i)) {
+          Some(len) => len,
+          None => return None
+        };
+        if i + len > s.len() || !str::is_char_boundary(s, i + len) {
+            return None;
+        }
+        match unsanitize(str::view(s, i, i + len)) {
+          Some(name) => names.push(name),
+          None => return None
+        }
+        i += len;
+    }
+    if i == s.len() || names.is_empty() { return None; }
+    Some((names, i + 1u))
+}
+
+// The path that `names` make up. With `strip_hash`, the hash that
+// `exported_name` and `mangle_internal_name_by_type_only` add, and the
+// version after it, are left out.
+fn demangled_path(names: ~[~str], strip_hash: bool) -> ~str {
+    let names = if strip_hash {
+        match vec::position(names, |n| is_symbol_hash(*n)) {
+          Some(i) if i > 0u => vec::slice(names, 0u, i),
+          _ => names
+        }
+    } else {
+        names
+    };
+    str::connect(names, "::")
+}
+
+/**
+ * Turns a symbol made by `mangle` back into the path it names, such as
+ * `core::vec::push`, or returns None if `sym` isn't such a symbol. The
+ * hash of the symbol's type, and the crate version that follows it in
+ * exported names, are kept unless `strip_hash` is set.
+ */
+pub fn demangle(sym: &str, strip_hash: bool) -> Option<~str> {
+    match demangle_prefix(sym) {
+      Some((names, len)) if len == sym.len() => {
+        Some(demangled_path(names, strip_hash))
+      }
+      _ => None
+    }
+}
+
+/// Demangles every mangled symbol in `text`, such as a line of profiler
+/// output, leaving everything else as it is
+pub fn demangle_text(text: &str, strip_hash: bool) -> ~str {
+    let mut result = ~"";
+    let mut i = 0u;
+    loop {
+        match str::find_str_from(text, "_ZN", i) {
+          None => {
+            result += str::view(text, i, text.len());
+            return result;
+          }
+          Some(start) => {
+            result += str::view(text, i, start);
+            let rest = str::view(text, start, text.len());
+            match demangle_prefix(rest) {
+              Some((names, len)) => {
+                result += demangled_path(names, strip_hash);
+                i = start + len;
+              }
+              None => {
+                result += "_ZN";
+                i = start + 3u;
+              }
+            }
+          }
+        }
+    }
+}
+
 pub fn exported_name(sess: Session,
                      +path: path,
                      hash: &str,
@@ -714,12 +877,57 @@ pub fn output_dll_filename(os: session::os, lm: link_meta) -> ~str {
            str::from_slice(dll_suffix);
 }
 
+pub fn output_archive_filename(lm: link_meta) -> ~str {
+    fmt!("lib%s-%s-%s.rlib", lm.name, lm.extras_hash, lm.vers)
+}
+
+// The file link_binary will actually produce: libraries get their name
+// from the link metadata rather than from <out_filename>
+pub fn linked_filename(sess: Session, out_filename: &Path,
+                       lm: link_meta) -> Path {
+    if *sess.building_library {
+        let long_libname = if *sess.building_staticlib {
+            output_archive_filename(lm)
+        } else {
+            output_dll_filename(sess.targ_cfg.os, lm)
+        };
+        debug!("link_meta.name:  %s", lm.name);
+        debug!("long_libname: %s", long_libname);
+        debug!("out_filename: %s", out_filename.to_str());
+        debug!("dirname(out_filename): %s", out_filename.dir_path().to_str());
+
+        out_filename.dir_path().push(long_libname)
+    } else {
+        /*bad*/copy *out_filename
+    }
+}
+
 // If the user wants an exe generated we need to invoke
-// cc to link the object file with some libs
+// cc to link the object file with some libs, or ar for a static library
 pub fn link_binary(sess: Session,
                    obj_filename: &Path,
                    out_filename: &Path,
                    lm: link_meta) {
+    let output = linked_filename(sess, out_filename, lm);
+
+    log(debug, ~"output: " + output.to_str());
+
+    if *sess.building_staticlib {
+        archive::create_staticlib(sess, &output, obj_filename);
+    } else {
+        link_with_cc(sess, obj_filename, &output);
+    }
+
+    // Remove the temporary object file if we aren't saving temps
+    if !sess.opts.save_temps {
+        if ! os::remove_file(obj_filename) {
+            sess.warn(fmt!("failed to delete object file `%s`",
+                           obj_filename.to_str()));
+        }
+    }
+}
+
+fn link_with_cc(sess: Session, obj_filename: &Path, output: &Path) {
     // Converts a library file-stem into a cc -l argument
     fn unlib(config: @session::config, +stem: ~str) -> ~str {
         if stem.starts_with("lib") &&
@@ -730,20 +938,6 @@ pub fn link_binary(sess: Session,
         }
     }
 
-    let output = if *sess.building_library {
-        let long_libname = output_dll_filename(sess.targ_cfg.os, lm);
-        debug!("link_meta.name:  %s", lm.name);
-        debug!("long_libname: %s", long_libname);
-        debug!("out_filename: %s", out_filename.to_str());
-        debug!("dirname(out_filename): %s", out_filename.dir_path().to_str());
-
-        out_filename.dir_path().push(long_libname)
-    } else {
-        /*bad*/copy *out_filename
-    };
-
-    log(debug, ~"output: " + output.to_str());
-
     // The default library location, we need this to find the runtime.
     // The location of crates will be determined as needed.
     let stage: ~str = ~"-L" + sess.filesearch.get_target_lib_path().to_str();
@@ -776,10 +970,18 @@ pub fn link_binary(sess: Session,
 
     // # Crate linking
 
+    // Crates come after the crates they use in the used crate files, so the
+    // static ones are linked the other way around, which is the order that
+    // the linker needs to resolve their symbols in a single pass
     let cstore = sess.cstore;
-    for cstore::get_used_crate_files(cstore).each |cratepath| {
+    let crate_files = cstore::get_used_crate_files(cstore);
+    for vec::rev_each(crate_files) |cratepath| {
         if cratepath.filetype() == Some(~".rlib") {
             cc_args.push(cratepath.to_str());
+        }
+    }
+    for crate_files.each |cratepath| {
+        if cratepath.filetype() == Some(~".rlib") {
             loop;
         }
         let dir = cratepath.dirname();
@@ -861,7 +1063,7 @@ pub fn link_binary(sess: Session,
 
     // FIXME (#2397): At some point we want to rpath our guesses as to where
     // extern libraries might live, based on the addl_lib_search_paths
-    cc_args.push_all(rpath::get_rpath_flags(sess, &output));
+    cc_args.push_all(rpath::get_rpath_flags(sess, output));
 
     debug!("%s link args: %s", cc_prog, str::connect(cc_args, ~" "));
     // We run 'cc' here
@@ -879,15 +1081,91 @@ pub fn link_binary(sess: Session,
     if sess.targ_cfg.os == session::os_macos {
         run::run_program(~"dsymutil", ~[output.to_str()]);
     }
+}
 
-    // Remove the temporary object file if we aren't saving temps
-    if !sess.opts.save_temps {
-        if ! os::remove_file(obj_filename) {
-            sess.warn(fmt!("failed to delete object file `%s`",
-                           obj_filename.to_str()));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::{mangle_names, symbol_hash_str};
+    use core::char;
+    use core::str;
+    use core::vec;
+
+    #[test]
+    fn test_sanitize_round_trip() {
+        for [~"main", ~"_1abc", ~"1abc", ~"@int", ~"~[int]", ~"&'a str",
+             ~"*u8", ~"fn(int) -> int", ~"Option<@str>", ~"0.6", ~"$u40$",
+             ~"_", ~"\u03bb"].each |name| {
+            let sani = sanitize(copy *name);
+            assert str::all(sani, |c| c == '$' || c == '_' ||
+                                      char::is_XID_continue(c));
+            assert unsanitize(sani) == Some(copy *name);
         }
+        assert sanitize(~"@int") != sanitize(~"~int");
+        assert unsanitize("$bogus$").is_none();
+        assert unsanitize("_$u$").is_none();
+        assert unsanitize("$SP").is_none();
+    }
+
+    #[test]
+    fn test_demangle() {
+        let hash = ~"h0123456789abcdef";
+        let sym = mangle_names([~"core", ~"vec", ~"push", copy hash,
+                                ~"0.6"]);
+        assert demangle(sym, false) ==
+            Some(~"core::vec::push::" + hash + ~"::0.6");
+        assert demangle(sym, true) == Some(~"core::vec::push");
+
+        let sym = mangle_names([~"glue_drop", ~"~[@int]", copy hash]);
+        assert demangle(sym, true) == Some(~"glue_drop::~[@int]");
+
+        assert demangle("main", true).is_none();
+        assert demangle("_ZN4mainE_", true).is_none();
+        assert demangle("_ZN5mainE", true).is_none();
+    }
+
+    // The names below are put together the way `exported_name`, the
+    // `mangle_internal_name_by_*` functions and the crate context's name
+    // generator do
+    #[test]
+    fn test_demangle_round_trip() {
+        let path = ~[~"core", ~"vec", ~"push"];
+        let hash = symbol_hash_str(0xfeedu64);
+        assert hash == ~"h000000000000feed";
+
+        // mangle_exported_name: the path, the type's hash, the version
+        let sym = mangle_names(vec::append(copy path, [copy hash, ~"0.6"]));
+        assert demangle(sym, false) ==
+            Some(~"core::vec::push::h000000000000feed::0.6");
+        assert demangle(sym, true) == Some(~"core::vec::push");
+
+        // mangle_internal_name_by_type_only: a name, the type, its hash
+        let sym = mangle_names([~"glue_drop", ~"~[@int]", copy hash]);
+        assert demangle(sym, false) ==
+            Some(~"glue_drop::~[@int]::h000000000000feed");
+        assert demangle(sym, true) == Some(~"glue_drop::~[@int]");
+
+        // mangle_internal_name_by_path_and_seq: the path and a gensym,
+        // with no hash to drop
+        let gensym = fmt!("%s_%u", ~"closure", 42u);
+        let sym = mangle_names(vec::append_one(copy path, gensym));
+        assert demangle(sym, false) == Some(~"core::vec::push::closure_42");
+        assert demangle(sym, true) == Some(~"core::vec::push::closure_42");
+
+        // mangle_internal_name_by_seq isn't mangled at all
+        let sym = fmt!("%s_%u", ~"glue_drop", 42u);
+        assert demangle(sym, false).is_none();
+        assert demangle_text(sym, true) == sym;
+    }
+
+    #[test]
+    fn test_demangle_text() {
+        let sym = mangle_names([~"a", ~"b", ~"h0123456789abcdef"]);
+        assert demangle_text(fmt!("at %s+0x10 (_ZN)", sym), true) ==
+            ~"at a::b+0x10 (_ZN)";
     }
 }
+
 //
 // Local Variables:
 // mode: rust
@@ -40,7 +40,10 @@ pub fn get_rpath_flags(sess: session::Session, out_filename: &Path)
 
     let sysroot = sess.filesearch.sysroot();
     let output = out_filename;
-    let libs = cstore::get_used_crate_files(sess.cstore);
+    // Static libraries are linked in, so they need no rpath
+    let libs = do cstore::get_used_crate_files(sess.cstore).filtered |lib| {
+        lib.filetype() != Some(~".rlib")
+    };
     // We don't currently rpath extern libraries, but we know
     // where rustrt is and we know every rust program needs it
     let libs = vec::append_one(libs, get_sysroot_absolute_rt_lib(sess));
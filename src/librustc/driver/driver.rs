@@ -23,6 +23,7 @@ use session;
 use util::ppaux;
 
 use core::cmp;
+use core::hashmap::linear::LinearMap;
 use core::int;
 use core::io::WriterUtil;
 use core::io;
@@ -30,16 +31,19 @@ use core::option;
 use core::os;
 use core::result::{Ok, Err};
 use core::str;
+use core::sys;
 use core::vec;
 use std::getopts::groups::{optopt, optmulti, optflag, optflagopt, getopts};
 use std::getopts::groups;
 use std::getopts::{opt_present};
 use std::getopts;
+use std::json;
 use std::oldmap::HashMap;
 use std;
 use syntax::ast;
 use syntax::ast_map;
 use syntax::attr;
+use syntax::codemap::Pos;
 use syntax::codemap;
 use syntax::diagnostic;
 use syntax::parse;
@@ -175,6 +179,109 @@ pub fn time<T>(do_it: bool, what: ~str, thunk: fn() -> T) -> T {
     move rv
 }
 
+/// The cost of one compiler pass, as written out by `--pass-profile`
+pub struct PassProfile {
+    name: ~str,
+    secs: float,
+    // Task heap allocated since compilation started, less what was freed,
+    // when the pass finished, and the most there was at any point while
+    // it ran
+    heap_bytes: uint,
+    peak_heap_bytes: uint,
+    // AST nodes and types created by the pass
+    ast_nodes: uint,
+    types: uint
+}
+
+pub fn pass_profile_to_json(passes: &[PassProfile]) -> json::Json {
+    let mut list = ~[];
+    let mut total = 0f;
+    for passes.each |p| {
+        let mut o = ~LinearMap::new();
+        o.insert(~"name", json::String(copy p.name));
+        o.insert(~"time_s", json::Number(p.secs));
+        o.insert(~"heap_bytes", json::Number(p.heap_bytes as float));
+        o.insert(~"peak_heap_bytes",
+                 json::Number(p.peak_heap_bytes as float));
+        o.insert(~"ast_nodes", json::Number(p.ast_nodes as float));
+        o.insert(~"types", json::Number(p.types as float));
+        list.push(json::Object(o));
+        total += p.secs;
+    }
+    let mut o = ~LinearMap::new();
+    o.insert(~"passes", json::List(list));
+    o.insert(~"total_time_s", json::Number(total));
+    json::Object(o)
+}
+
+pub fn write_pass_profile(sess: Session, path: &Path,
+                          passes: &[PassProfile]) {
+    match io::file_writer(path, ~[io::Create, io::Truncate]) {
+      Ok(w) => {
+        json::to_pretty_writer(w, &pass_profile_to_json(passes));
+        w.write_char('\n');
+      }
+      Err(e) => {
+        sess.err(fmt!("could not write pass profile to %s: %s",
+                      path.to_str(), e));
+      }
+    }
+}
+
+// Runs the passes of `compile_upto`, timing them for -Z time-passes and
+// recording what they cost for --pass-profile
+struct Profiler {
+    sess: Session,
+    mut passes: ~[PassProfile],
+    // Types can only be counted once there is a type context
+    mut tcx: Option<ty::ctxt>
+}
+
+impl Profiler {
+    fn pass<T>(&self, what: ~str, thunk: fn() -> T) -> T {
+        if self.sess.opts.pass_profile.is_none() {
+            return time(self.sess.time_passes(), what, thunk);
+        }
+        let nodes = self.sess.parse_sess.next_id;
+        let types = self.type_count();
+        sys::reset_task_heap_peak();
+        let start = std::time::precise_time_s();
+        let rv = time(self.sess.time_passes(), copy what, thunk);
+        let end = std::time::precise_time_s();
+        let (heap, peak) = sys::task_heap_bytes();
+        self.passes.push(PassProfile {
+            name: what,
+            secs: end - start,
+            heap_bytes: heap,
+            peak_heap_bytes: peak,
+            ast_nodes: (self.sess.parse_sess.next_id - nodes) as uint,
+            types: self.type_count() - types
+        });
+        move rv
+    }
+
+    fn type_count(&self) -> uint {
+        match self.tcx {
+          Some(tcx) => tcx.next_id,
+          None => 0u
+        }
+    }
+}
+
+// Written on the way out, so that compilations that stop early or fail
+// still leave a profile of the passes they ran
+impl Drop for Profiler {
+    fn finalize(&self) {
+        match self.sess.opts.pass_profile {
+          Some(ref path) => {
+            let passes = copy self.passes;
+            write_pass_profile(self.sess, path, passes);
+          }
+          None => ()
+        }
+    }
+}
+
 pub enum compile_upto {
     cu_parse,
     cu_expand,
@@ -194,61 +301,72 @@ pub fn compile_upto(sess: Session, cfg: ast::crate_cfg,
                     input: input, upto: compile_upto,
                     outputs: Option<output_filenames>)
                  -> {crate: @ast::crate, tcx: Option<ty::ctxt>} {
-    let time_passes = sess.time_passes();
-    let mut crate = time(time_passes, ~"parsing",
-                         || parse_input(sess, copy cfg, input) );
+    let prof = @Profiler { sess: sess, mut passes: ~[], mut tcx: None };
+    if sess.opts.pass_profile.is_some() {
+        sys::track_task_heap_bytes();
+    }
+    let mut crate = prof.pass(~"parsing",
+                              || parse_input(sess, copy cfg, input) );
     if upto == cu_parse { return {crate: crate, tcx: None}; }
 
     *sess.building_library = session::building_library(
         sess.opts.crate_type, crate, sess.opts.test);
+    // -Z static makes every library a static one
+    *sess.building_staticlib = *sess.building_library &&
+        (sess.opts.static || session::building_staticlib(
+            sess.opts.crate_type, crate, sess.opts.test));
 
-    crate = time(time_passes, ~"configuration", ||
+    crate = prof.pass(~"configuration", ||
         front::config::strip_unconfigured_items(crate));
 
-    crate = time(time_passes, ~"maybe building test harness", ||
+    crate = prof.pass(~"maybe building test harness", ||
         front::test::modify_for_testing(sess, crate));
 
-    crate = time(time_passes, ~"expansion", ||
-        syntax::ext::expand::expand_crate(sess.parse_sess, copy cfg,
-                                          crate));
+    let exts = syntax::ext::base::syntax_expander_table();
+    prof.pass(~"loading syntax plugins", ||
+        front::plugin::load_plugins(sess, crate, exts));
+
+    crate = prof.pass(~"expansion", ||
+        syntax::ext::expand::expand_crate_with_exts(sess.parse_sess,
+                                                    copy cfg, crate, exts));
 
     if upto == cu_expand { return {crate: crate, tcx: None}; }
 
-    crate = time(time_passes, ~"intrinsic injection", ||
+    crate = prof.pass(~"intrinsic injection", ||
         front::intrinsic_inject::inject_intrinsic(sess, crate));
 
-    crate = time(time_passes, ~"core injection", ||
+    crate = prof.pass(~"core injection", ||
         front::core_inject::maybe_inject_libcore_ref(sess, crate));
 
-    time(time_passes, ~"building lint settings table", ||
+    prof.pass(~"building lint settings table", ||
         lint::build_settings_crate(sess, crate));
 
-    let ast_map = time(time_passes, ~"ast indexing", ||
+    let ast_map = prof.pass(~"ast indexing", ||
             syntax::ast_map::map_crate(sess.diagnostic(), *crate));
 
-    time(time_passes, ~"external crate/lib resolution", ||
+    prof.pass(~"external crate/lib resolution", ||
         creader::read_crates(sess.diagnostic(), *crate, sess.cstore,
                              sess.filesearch,
                              session::sess_os_to_meta_os(sess.targ_cfg.os),
                              sess.opts.static,
                              sess.parse_sess.interner));
 
-    let lang_items = time(time_passes, ~"language item collection", ||
+    let lang_items = prof.pass(~"language item collection", ||
          middle::lang_items::collect_language_items(crate, sess));
 
     let { def_map: def_map,
           exp_map2: exp_map2,
           trait_map: trait_map } =
-        time(time_passes, ~"resolution", ||
+        prof.pass(~"resolution", ||
              middle::resolve::resolve_crate(sess, lang_items, crate));
 
-    let freevars = time(time_passes, ~"freevar finding", ||
+    let freevars = prof.pass(~"freevar finding", ||
         freevars::annotate_freevars(def_map, crate));
 
-    let region_map = time(time_passes, ~"region resolution", ||
+    let region_map = prof.pass(~"region resolution", ||
         middle::region::resolve_crate(sess, def_map, crate));
 
-    let rp_set = time(time_passes, ~"region parameterization inference", ||
+    let rp_set = prof.pass(~"region parameterization inference", ||
         middle::region::determine_rp_in_crate(sess, ast_map, def_map, crate));
 
 
@@ -258,56 +376,63 @@ pub fn compile_upto(sess: Session, cfg: ast::crate_cfg,
 
         let ty_cx = ty::mk_ctxt(sess, def_map, ast_map, freevars,
                                 region_map, rp_set, move lang_items, crate);
+        prof.tcx = Some(ty_cx);
 
         let (method_map, vtable_map) =
-            time(time_passes, ~"typechecking", ||
+            prof.pass(~"typechecking", ||
                  typeck::check_crate(ty_cx,
                                      trait_map,
                                      crate));
 
         // These next two const passes can probably be merged
-        time(time_passes, ~"const marking", ||
+        prof.pass(~"const marking", ||
              middle::const_eval::process_crate(crate, def_map, ty_cx));
 
-        time(time_passes, ~"const checking", ||
+        prof.pass(~"const checking", ||
              middle::check_const::check_crate(sess, crate, ast_map, def_map,
                                               method_map, ty_cx));
 
         if upto == cu_typeck { return {crate: crate, tcx: Some(ty_cx)}; }
 
-        time(time_passes, ~"privacy checking", ||
+        prof.pass(~"privacy checking", ||
              middle::privacy::check_crate(ty_cx, &method_map, crate));
 
-        time(time_passes, ~"loop checking", ||
+        prof.pass(~"loop checking", ||
              middle::check_loop::check_crate(ty_cx, crate));
 
         let middle::moves::MoveMaps {moves_map, variable_moves_map,
                                      capture_map} =
-            time(time_passes, ~"compute moves", ||
+            prof.pass(~"compute moves", ||
                  middle::moves::compute_moves(ty_cx, method_map, crate));
 
-        time(time_passes, ~"match checking", ||
+        prof.pass(~"match checking", ||
              middle::check_match::check_crate(ty_cx, method_map,
                                               moves_map, crate));
 
         let last_use_map =
-            time(time_passes, ~"liveness checking", ||
+            prof.pass(~"liveness checking", ||
                  middle::liveness::check_crate(ty_cx, method_map,
                                                variable_moves_map,
                                                capture_map, crate));
 
         let (root_map, mutbl_map, write_guard_map) =
-            time(time_passes, ~"borrow checking", ||
+            prof.pass(~"borrow checking", ||
                  middle::borrowck::check_crate(ty_cx, method_map,
                                                moves_map, capture_map,
                                                crate));
 
-        time(time_passes, ~"kind checking", ||
+        prof.pass(~"kind checking", ||
              kind::check_crate(ty_cx, method_map, last_use_map, crate));
 
-        time(time_passes, ~"lint checking", ||
+        prof.pass(~"lint checking", ||
              lint::check_crate(ty_cx, crate));
 
+        if sess.opts.c_header {
+            prof.pass(~"C header writing", ||
+                 back::c_header::write_c_header(ty_cx, crate,
+                                                &outputs.header_filename));
+        }
+
         if upto == cu_no_trans { return {crate: crate, tcx: Some(ty_cx)}; }
 
         let maps = astencode::Maps {
@@ -321,7 +446,7 @@ pub fn compile_upto(sess: Session, cfg: ast::crate_cfg,
             capture_map: capture_map
         };
 
-        time(time_passes, ~"translation", ||
+        prof.pass(~"translation", ||
              trans::base::trans_crate(sess, crate, ty_cx,
                                       &outputs.obj_filename,
                                       exp_map2, maps))
@@ -329,33 +454,166 @@ pub fn compile_upto(sess: Session, cfg: ast::crate_cfg,
     };
 
 
-    time(time_passes, ~"LLVM passes", ||
+    prof.pass(~"LLVM passes", ||
         link::write::run_passes(sess, llmod,
                                 &outputs.obj_filename));
 
     let stop_after_codegen =
         sess.opts.output_type != link::output_type_exe ||
-        (sess.opts.static && *sess.building_library)   ||
         sess.opts.jit;
 
-    if stop_after_codegen { return {crate: crate, tcx: None}; }
+    if stop_after_codegen {
+        if sess.opts.dep_info {
+            write_dep_info(sess, &outputs.dep_filename,
+                           &outputs.obj_filename);
+        }
+        return {crate: crate, tcx: None};
+    }
 
-    time(time_passes, ~"linking", ||
+    prof.pass(~"linking", ||
          link::link_binary(sess,
                            &outputs.obj_filename,
                            &outputs.out_filename, link_meta));
 
+    if sess.opts.dep_info {
+        write_dep_info(sess, &outputs.dep_filename,
+                       &link::linked_filename(sess, &outputs.out_filename,
+                                              link_meta));
+    }
+
     return {crate: crate, tcx: None};
 }
 
+// Escapes a path for use in a Makefile rule
+fn make_escape(path: &str) -> ~str {
+    let mut out = ~"";
+    for str::each_char(path) |c| {
+        match c {
+          ' ' | '#' | '\\' => {
+            str::push_char(&mut out, '\\');
+            str::push_char(&mut out, c);
+          }
+          '$' => str::push_str(&mut out, ~"$$"),
+          _ => str::push_char(&mut out, c)
+        }
+    }
+    out
+}
+
+/**
+ * Formats a Makefile rule making `target` depend on each of `deps`,
+ * followed by an empty rule for each dependency so that make doesn't
+ * complain when one of them is deleted.
+ */
+pub fn dep_info_to_str(target: &Path, deps: &[~str]) -> ~str {
+    let mut s = make_escape(target.to_str()) + ~":";
+    for deps.each |d| {
+        s += ~" \\\n    " + make_escape(*d);
+    }
+    s += ~"\n";
+    for deps.each |d| {
+        s += ~"\n" + make_escape(*d) + ~":\n";
+    }
+    s
+}
+
+// Every source file loaded into the CodeMap (through the crate file,
+// `mod` declarations and include!, include_str! and include_bin!) and
+// every extern crate that was linked against
+fn dep_info_files(sess: Session) -> ~[~str] {
+    let mut deps = ~[];
+    for sess.codemap.files.each |fm| {
+        // Names in angle brackets aren't files: `<anon>`, quoted code...
+        if fm.name.starts_with("<") || fm.name == ~"-" { loop; }
+        match fm.substr {
+          codemap::FssNone => (),
+          _ => loop
+        }
+        if !deps.contains(&fm.name) { deps.push(copy fm.name); }
+    }
+    for cstore::get_used_crate_files(sess.cstore).each |p| {
+        let p = p.to_str();
+        if !deps.contains(&p) { deps.push(p); }
+    }
+    deps
+}
+
+pub fn write_dep_info(sess: Session, dep_filename: &Path, target: &Path) {
+    match io::file_writer(dep_filename, ~[io::Create, io::Truncate]) {
+      Ok(w) => w.write_str(dep_info_to_str(target, dep_info_files(sess))),
+      Err(e) => {
+        sess.err(fmt!("could not write dependency info to %s: %s",
+                      dep_filename.to_str(), e));
+      }
+    }
+}
+
 pub fn compile_input(sess: Session, +cfg: ast::crate_cfg, input: input,
                      outdir: &Option<Path>, output: &Option<Path>) {
 
     let upto = if sess.opts.parse_only { cu_parse }
-               else if sess.opts.no_trans { cu_no_trans }
+               else if sess.opts.no_trans || sess.opts.apply_fixes {
+                   cu_no_trans
+               }
                else { cu_everything };
     let outputs = build_output_filenames(input, outdir, output, sess);
     compile_upto(sess, cfg, input, upto, Some(outputs));
+    if sess.opts.apply_fixes {
+        apply_fixes(sess);
+    }
+}
+
+/**
+ * Writes the machine-applicable suggestions made while compiling back
+ * into the source files they are about. Suggestions in code produced by
+ * macros are left alone, since the code they would change is not there.
+ */
+pub fn apply_fixes(sess: Session) {
+    let mut files: ~[@codemap::FileMap] = ~[];
+    let mut edits: ~[(@codemap::FileMap, (uint, uint, ~str))] = ~[];
+    for sess.span_diagnostic.handler().suggestions().each |s| {
+        if !s.machine_applicable || s.span.expn_info.is_some() {
+            loop;
+        }
+        let lo = sess.codemap.lookup_char_pos(s.span.lo);
+        let hi = sess.codemap.lookup_char_pos(s.span.hi);
+        if lo.file.start_pos != hi.file.start_pos {
+            loop;
+        }
+        let fm = lo.file;
+        // Source that doesn't come from a file has a name like `<anon>`
+        if str::starts_with(fm.name, "<") {
+            loop;
+        }
+        if !vec::any(files, |f| f.start_pos == fm.start_pos) {
+            files.push(fm);
+        }
+        edits.push((fm, ((s.span.lo - fm.start_pos).to_uint(),
+                         (s.span.hi - fm.start_pos).to_uint(),
+                         copy s.replacement)));
+    }
+
+    for files.each |fm| {
+        let mut file_edits = ~[];
+        for edits.each |e| {
+            let (f, ref edit) = *e;
+            if f.start_pos == fm.start_pos {
+                file_edits.push(copy *edit);
+            }
+        }
+        let (src, n) = diagnostic::apply_replacements(*fm.src, file_edits);
+        let path = Path(fm.name);
+        match io::file_writer(&path, ~[io::Create, io::Truncate]) {
+          Ok(w) => {
+            w.write_str(src);
+            sess.note(fmt!("applied %u fixes to %s", n, fm.name));
+          }
+          Err(e) => {
+            sess.err(fmt!("could not apply fixes to %s: %s",
+                          fm.name, e));
+          }
+        }
+    }
 }
 
 pub fn pretty_print_input(sess: Session, +cfg: ast::crate_cfg, input: input,
@@ -518,11 +776,18 @@ pub fn build_session_options(+binary: ~str,
         session::lib_crate
     } else if opt_present(matches, ~"bin") {
         session::bin_crate
+    } else if opt_present(matches, ~"staticlib") {
+        session::staticlib_crate
     } else {
         session::unknown_crate
     };
     let parse_only = opt_present(matches, ~"parse-only");
     let no_trans = opt_present(matches, ~"no-trans");
+    let dep_info = opt_present(matches, ~"dep-info");
+    let c_header = opt_present(matches, ~"c-header");
+    let pass_profile = getopts::opt_maybe_str(matches, ~"pass-profile")
+        .map(|p| Path(*p));
+    let apply_fixes = opt_present(matches, ~"apply-fixes");
 
     let lint_levels = [lint::allow, lint::warn,
                        lint::deny, lint::forbid];
@@ -568,7 +833,7 @@ pub fn build_session_options(+binary: ~str,
     }
 
     let output_type =
-        if parse_only || no_trans {
+        if parse_only || no_trans || apply_fixes {
             link::output_type_none
         } else if opt_present(matches, ~"S") &&
                   opt_present(matches, ~"emit-llvm") {
@@ -614,7 +879,7 @@ pub fn build_session_options(+binary: ~str,
     let jit = debugging_opts & session::jit != 0;
     let extra_debuginfo = debugging_opts & session::extra_debug_info != 0;
     let debuginfo = debugging_opts & session::debug_info != 0 ||
-        extra_debuginfo;
+        extra_debuginfo || opt_present(matches, ~"g");
     let static = debugging_opts & session::static != 0;
     let target =
         match target_opt {
@@ -646,6 +911,10 @@ pub fn build_session_options(+binary: ~str,
           test: test,
           parse_only: parse_only,
           no_trans: no_trans,
+          dep_info: dep_info,
+          c_header: c_header,
+          pass_profile: pass_profile,
+          apply_fixes: apply_fixes,
           debugging_opts: debugging_opts};
     return sopts;
 }
@@ -685,6 +954,7 @@ pub fn build_session_(sopts: @session::options,
         span_diagnostic: span_diagnostic_handler,
         filesearch: filesearch,
         building_library: @mut false,
+        building_staticlib: @mut false,
         working_dir: os::getcwd(),
         lint_settings: lint_settings
     }
@@ -708,12 +978,33 @@ pub fn parse_pretty(sess: Session, &&name: ~str) -> pp_mode {
 // rustc command line options
 pub fn optgroups() -> ~[getopts::groups::OptGroup] {
  ~[
+  optflag(~"",  ~"apply-fixes",
+                        ~"Check the crate and write the fixes suggested
+                          by lints back into its source files"),
   optflag(~"",  ~"bin", ~"Compile an executable crate (default)"),
   optflag(~"c", ~"",    ~"Compile and assemble, but do not link"),
+  optflag(~"",  ~"c-header",
+                        ~"Write a C header declaring the public
+                          #[no_mangle] extern fns of the crate"),
   optmulti(~"", ~"cfg", ~"Configure the compilation
                           environment", ~"SPEC"),
+  optflagopt(~"", ~"demangle",
+                        ~"Demangle the symbols in the text read from
+                          stdin, leaving out their hashes unless the
+                          value is full", ~"full"),
+  optflag(~"",  ~"dep-info",
+                        ~"Write a Makefile-compatible .d file listing the
+                          files the output depends on"),
   optflag(~"",  ~"emit-llvm",
                         ~"Produce an LLVM bitcode file"),
+  optopt(~"",  ~"error-format",
+                        ~"How to print errors and warnings: human
+                          (default) or json, one object per line",
+                        ~"FORMAT"),
+  optopt(~"",  ~"explain",
+                        ~"Explain an error code, such as E0001, with
+                          examples", ~"CODE"),
+  optflag(~"g", ~"",    ~"Produce debug info for gdb"),
   optflag(~"h", ~"help",~"Display this message"),
   optmulti(~"L", ~"",   ~"Add a directory to the library search path",
                               ~"PATH"),
@@ -728,6 +1019,10 @@ pub fn optgroups() -> ~[getopts::groups::OptGroup] {
   optopt( ~"",  ~"out-dir",
                         ~"Write output to compiler-chosen filename
                           in <dir>", ~"DIR"),
+  optopt(~"",  ~"pass-profile",
+                        ~"Write the time, heap usage, and AST nodes and
+                          types created by each pass to <file> as JSON",
+                        ~"FILE"),
   optflag(~"", ~"parse-only",
                         ~"Parse only; do not compile, assemble, or link"),
   optflagopt(~"", ~"pretty",
@@ -741,6 +1036,9 @@ pub fn optgroups() -> ~[getopts::groups::OptGroup] {
   optflag(~"", ~"save-temps",
                         ~"Write intermediate files (.bc, .opt.bc, .o)
                           in addition to normal output"),
+  optflag(~"", ~"staticlib",
+                        ~"Compile a static library crate, an archive of
+                          its object code and metadata"),
   optopt(~"", ~"sysroot",
                         ~"Override the system root", ~"PATH"),
   optflag(~"", ~"test", ~"Build a test harness"),
@@ -763,7 +1061,8 @@ pub fn optgroups() -> ~[getopts::groups::OptGroup] {
  ]
 }
 
-pub type output_filenames = @{out_filename:Path, obj_filename:Path};
+pub type output_filenames = @{out_filename:Path, obj_filename:Path,
+                              dep_filename:Path, header_filename:Path};
 
 pub fn build_output_filenames(input: input,
                               odir: &Option<Path>,
@@ -773,9 +1072,7 @@ pub fn build_output_filenames(input: input,
     let obj_path;
     let out_path;
     let sopts = sess.opts;
-    let stop_after_codegen =
-        sopts.output_type != link::output_type_exe ||
-            sopts.static && *sess.building_library;
+    let stop_after_codegen = sopts.output_type != link::output_type_exe;
 
 
     let obj_suffix =
@@ -835,12 +1132,22 @@ pub fn build_output_filenames(input: input,
         }
       }
     }
+    // The dependency file goes next to whatever we write, named after it
+    let dep_path = if stop_after_codegen {
+        obj_path.with_filetype("d")
+    } else {
+        out_path.with_filetype("d")
+    };
+    let header_path = dep_path.with_filetype("h");
+
     return @{out_filename: out_path,
-             obj_filename: obj_path};
+             obj_filename: obj_path,
+             dep_filename: dep_path,
+             header_filename: header_path};
 }
 
 pub fn early_error(emitter: diagnostic::Emitter, msg: ~str) -> ! {
-    emitter(None, msg, diagnostic::fatal);
+    emitter(None, msg, diagnostic::fatal, None, []);
     fail!();
 }
 
@@ -856,13 +1163,55 @@ pub mod test {
 
     use driver::driver::{build_configuration, build_session};
     use driver::driver::{build_session_options, optgroups, str_input};
+    use driver::driver::dep_info_to_str;
+    use driver::driver::{PassProfile, pass_profile_to_json};
 
     use core::vec;
     use std::getopts::groups::getopts;
     use std::getopts;
+    use std::json;
     use syntax::attr;
     use syntax::diagnostic;
 
+    #[test]
+    pub fn test_dep_info_to_str() {
+        let s = dep_info_to_str(&Path("out/foo"),
+                                [~"src/foo.rc", ~"src/my file.rs"]);
+        assert s == ~"out/foo: \\\n    src/foo.rc \\\n" +
+            ~"    src/my\\ file.rs\n" +
+            ~"\nsrc/foo.rc:\n\nsrc/my\\ file.rs:\n";
+    }
+
+    #[test]
+    pub fn test_pass_profile_to_json() {
+        let p = PassProfile { name: ~"parsing", secs: 0.5f,
+                              heap_bytes: 1024u, peak_heap_bytes: 4096u,
+                              ast_nodes: 12u, types: 0u };
+        let q = PassProfile { name: ~"typechecking", types: 7u, .. copy p };
+        match pass_profile_to_json([p, q]) {
+          json::Object(ref o) => {
+            assert *o.get(&~"total_time_s") == json::Number(1f);
+            match *o.get(&~"passes") {
+              json::List(ref l) => {
+                assert l.len() == 2u;
+                match l[1] {
+                  json::Object(ref pass) => {
+                    assert *pass.get(&~"name") ==
+                        json::String(~"typechecking");
+                    assert *pass.get(&~"peak_heap_bytes") ==
+                        json::Number(4096f);
+                    assert *pass.get(&~"types") == json::Number(7f);
+                  }
+                  _ => fail!()
+                }
+              }
+              _ => fail!()
+            }
+          }
+          _ => fail!()
+        }
+    }
+
     // When the user supplies --test we should implicitly supply --cfg test
     #[test]
     pub fn test_switch_implies_cfg_test() {
@@ -47,7 +47,7 @@ pub impl arch : cmp::Eq {
     pure fn ne(&self, other: &arch) -> bool { !(*self).eq(other) }
 }
 
-pub enum crate_type { bin_crate, lib_crate, unknown_crate, }
+pub enum crate_type { bin_crate, lib_crate, staticlib_crate, unknown_crate, }
 
 pub type config =
     {os: os,
@@ -156,6 +156,14 @@ pub type options =
      test: bool,
      parse_only: bool,
      no_trans: bool,
+     // Write a Makefile dependency file next to the output
+     dep_info: bool,
+     // Write a C header declaring the functions exported to C
+     c_header: bool,
+     // Write a JSON profile of each compiler pass to this file
+     pass_profile: Option<Path>,
+     // Write machine-applicable lint fixes back into the source files
+     apply_fixes: bool,
      debugging_opts: uint,
     };
 
@@ -172,6 +180,8 @@ pub struct Session_ {
     span_diagnostic: diagnostic::span_handler,
     filesearch: filesearch::FileSearch,
     building_library: @mut bool,
+    // Whether the library is a static one, an archive rather than a dylib
+    building_staticlib: @mut bool,
     working_dir: Path,
     lint_settings: lint::lint_settings
 }
@@ -191,6 +201,19 @@ pub impl Session {
     fn err(msg: ~str) {
         self.span_diagnostic.handler().err(msg)
     }
+    // `code` must be listed in middle::error_codes
+    fn span_err_with_code(sp: span, msg: ~str, code: &str) {
+        self.span_diagnostic.span_err_with_code(sp, msg, code)
+    }
+    fn span_err_labelled(sp: span, msg: ~str,
+                         labels: &[diagnostic::SpanLabel]) {
+        self.span_diagnostic.span_err_labelled(sp, msg, None, labels)
+    }
+    fn span_err_labelled_with_code(sp: span, msg: ~str, code: &str,
+                                   labels: &[diagnostic::SpanLabel]) {
+        self.span_diagnostic.span_err_labelled(sp, msg,
+                                               Some(code.to_owned()), labels)
+    }
     fn has_errors() -> bool {
         self.span_diagnostic.handler().has_errors()
     }
@@ -206,6 +229,9 @@ pub impl Session {
     fn span_note(sp: span, msg: ~str) {
         self.span_diagnostic.span_note(sp, msg)
     }
+    fn span_suggest(+suggestion: diagnostic::Suggestion) {
+        self.span_diagnostic.span_suggest(suggestion)
+    }
     fn note(msg: ~str) {
         self.span_diagnostic.handler().note(msg)
     }
@@ -239,6 +265,21 @@ pub impl Session {
             self.lint_settings, lint_mode, expr_id, item_id);
         self.span_lint_level(level, span, msg);
     }
+    // Like span_lint, followed by a fix for `--apply-fixes` if the lint
+    // is not allowed
+    fn span_lint_with_suggestion(lint_mode: lint::lint,
+                                 expr_id: ast::node_id,
+                                 item_id: ast::node_id,
+                                 span: span,
+                                 +msg: ~str,
+                                 +suggestion: diagnostic::Suggestion) {
+        let level = lint::get_lint_settings_level(
+            self.lint_settings, lint_mode, expr_id, item_id);
+        if level != lint::allow {
+            self.span_lint_level(level, span, msg);
+            self.span_suggest(suggestion);
+        }
+    }
     fn next_node_id() -> ast::node_id {
         return syntax::parse::next_node_id(self.parse_sess);
     }
@@ -303,6 +344,10 @@ pub fn basic_options() -> @options {
         test: false,
         parse_only: false,
         no_trans: false,
+        dep_info: false,
+        c_header: false,
+        pass_profile: None,
+        apply_fixes: false,
         debugging_opts: 0u
     }
 }
@@ -320,7 +365,7 @@ pub fn building_library(req_crate_type: crate_type,
                         testing: bool) -> bool {
     match req_crate_type {
       bin_crate => false,
-      lib_crate => true,
+      lib_crate | staticlib_crate => true,
       unknown_crate => {
         if testing {
             false
@@ -328,7 +373,7 @@ pub fn building_library(req_crate_type: crate_type,
             match syntax::attr::first_attr_value_str_by_name(
                 crate.node.attrs,
                 ~"crate_type") {
-              option::Some(~"lib") => true,
+              option::Some(~"lib") | option::Some(~"staticlib") => true,
               _ => false
             }
         }
@@ -336,6 +381,20 @@ pub fn building_library(req_crate_type: crate_type,
     }
 }
 
+pub fn building_staticlib(req_crate_type: crate_type,
+                          crate: @ast::crate,
+                          testing: bool) -> bool {
+    match req_crate_type {
+      bin_crate | lib_crate => false,
+      staticlib_crate => true,
+      unknown_crate => {
+        !testing &&
+            syntax::attr::first_attr_value_str_by_name(
+                crate.node.attrs, ~"crate_type") == option::Some(~"staticlib")
+      }
+    }
+}
+
 pub fn sess_os_to_meta_os(os: os) -> metadata::loader::os {
     use metadata::loader;
 
@@ -353,7 +412,7 @@ pub mod test {
     use core::prelude::*;
 
     use driver::session::{bin_crate, building_library, lib_crate};
-    use driver::session::{unknown_crate};
+    use driver::session::{building_staticlib, staticlib_crate, unknown_crate};
 
     use syntax::ast;
     use syntax::ast_util;
@@ -375,6 +434,10 @@ pub mod test {
         let mut attrs = ~[];
         if with_bin { attrs += ~[make_crate_type_attr(~"bin")]; }
         if with_lib { attrs += ~[make_crate_type_attr(~"lib")]; }
+        make_crate_with_attrs(attrs)
+    }
+
+    pub fn make_crate_with_attrs(+attrs: ~[ast::attribute]) -> @ast::crate {
         @codemap::respan(codemap::dummy_sp(), ast::crate_ {
             module: ast::_mod { view_items: ~[], items: ~[] },
             attrs: attrs,
@@ -423,6 +486,22 @@ pub mod test {
         let crate = make_crate(false, false);
         assert building_library(lib_crate, crate, true);
     }
+
+    #[test]
+    pub fn staticlib_crate_type_attr_results_in_staticlib_output() {
+        let crate = make_crate_with_attrs(
+            ~[make_crate_type_attr(~"staticlib")]);
+        assert building_library(unknown_crate, crate, false);
+        assert building_staticlib(unknown_crate, crate, false);
+    }
+
+    #[test]
+    pub fn lib_crate_type_is_not_static() {
+        let crate = make_crate(false, true);
+        assert !building_staticlib(unknown_crate, crate, false);
+        assert building_staticlib(staticlib_crate, crate, false);
+        assert !building_staticlib(lib_crate, crate, false);
+    }
 }
 
 // Local Variables:
@@ -0,0 +1,113 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * Loading of syntax extension plugins.
+ *
+ * A crate attribute `#[syntax_plugin = "name"];` names a library, found
+ * like any `extern mod`, whose registration function adds expanders to
+ * the table used by `syntax::ext::expand`. The library is loaded into
+ * the compiler and stays loaded, since the code and type descriptors of
+ * the expanders are referenced from the AST they produce.
+ */
+
+use core::prelude::*;
+
+use driver::driver::{get_os, host_triple};
+use driver::session::{Session, sess_os_to_meta_os};
+use metadata::loader;
+
+use core::cast;
+use core::ptr;
+use std::dynamic_lib::DynamicLibrary;
+use std::oldmap::HashMap;
+use syntax::ast;
+use syntax::attr;
+use syntax::codemap::span;
+use syntax::ext::base::{SyntaxExtension, PluginRegistrarFn};
+use syntax::ext::base::plugin_registrar_symbol;
+
+// The representation of a Rust `fn`, used to call the registrar
+struct Closure {
+    code: *(),
+    env: *(),
+}
+
+/// Adds the expanders of every plugin named by `crate` to `exts`
+pub fn load_plugins(sess: Session, crate: @ast::crate,
+                    exts: HashMap<~str, SyntaxExtension>) {
+    for attr::find_attrs_by_name(crate.node.attrs,
+                                 ~"syntax_plugin").each |a| {
+        match attr::get_meta_item_value_str(attr::attr_meta(*a)) {
+          Some(name) => load_plugin(sess, a.span, name, exts),
+          None => sess.span_err(a.span,
+                                ~"expected `#[syntax_plugin = \"name\"]`")
+        }
+    }
+    sess.abort_if_errors();
+}
+
+fn load_plugin(sess: Session, sp: span, name: ~str,
+               exts: HashMap<~str, SyntaxExtension>) {
+    // Plugins run inside the compiler, so they are built for the host
+    let os = match get_os(host_triple()) {
+      Some(os) => os,
+      None => sess.span_fatal(sp, ~"unknown host operating system")
+    };
+    let load_ctxt: loader::ctxt = {
+        diag: sess.diagnostic(),
+        filesearch: sess.filesearch,
+        span: sp,
+        ident: sess.ident_of(copy name),
+        metas: ~[attr::mk_name_value_item_str(~"name", copy name)],
+        hash: ~"",
+        os: sess_os_to_meta_os(os),
+        static: false,
+        intr: sess.intr()
+    };
+    let lib_path = Path(loader::load_library_crate(load_ctxt).ident);
+
+    let lib = match DynamicLibrary::open(&lib_path) {
+      Ok(move lib) => lib,
+      Err(move e) => {
+        sess.span_fatal(sp, fmt!("couldn't load syntax plugin `%s`: %s",
+                                 name, e))
+      }
+    };
+    let registrar: PluginRegistrarFn = unsafe {
+        match lib.symbol(plugin_registrar_symbol) {
+          Ok(code) => {
+            let closure = Closure { code: cast::reinterpret_cast(&code),
+                                    env: ptr::null() };
+            cast::transmute(move closure)
+          }
+          Err(move e) => {
+            sess.span_fatal(sp, fmt!("syntax plugin `%s` has no `%s`: %s",
+                                     name, plugin_registrar_symbol, e))
+          }
+        }
+    };
+
+    let table = HashMap();
+    registrar(table);
+    for table.each |ext_name, ext| {
+        if exts.contains_key(ext_name) {
+            sess.span_err(sp, fmt!("syntax plugin `%s` redefines the \
+                                    syntax extension `%s`",
+                                   name, *ext_name));
+        } else {
+            exts.insert(copy *ext_name, *ext);
+        }
+    }
+
+    // Expanders from the library can end up anywhere in the crate, so it
+    // must never be unloaded.
+    unsafe { cast::forget(move lib); }
+}
@@ -18,6 +18,7 @@ use session::Session;
 
 use core::dvec::DVec;
 use core::option;
+use core::uint;
 use core::vec;
 use syntax::ast_util::*;
 use syntax::attr;
@@ -37,7 +38,8 @@ type test = {
     path: ~[ast::ident],
     bench: bool,
     ignore: bool,
-    should_fail: bool
+    should_fail: bool,
+    timeout: Option<uint>
 };
 
 struct TestCtxt {
@@ -157,7 +159,8 @@ fn fold_item(cx: @mut TestCtxt, &&i: @ast::item, fld: fold::ast_fold)
                         path: /*bad*/copy cx.path,
                         bench: is_bench_fn(i),
                         ignore: is_ignored(cx, i),
-                        should_fail: should_fail(i)};
+                        should_fail: should_fail(i),
+                        timeout: timeout(cx, i)};
             cx.testfns.push(test);
             debug!("have %u test/bench functions", cx.testfns.len());
           }
@@ -232,6 +235,30 @@ fn should_fail(i: @ast::item) -> bool {
     vec::len(attr::find_attrs_by_name(i.attrs, ~"should_fail")) > 0u
 }
 
+// #[timeout = "ms"] kills the test if it is still running after ms
+// milliseconds
+fn timeout(cx: @mut TestCtxt, i: @ast::item) -> Option<uint> {
+    let timeoutattrs = attr::find_attrs_by_name(i.attrs, "timeout");
+    if timeoutattrs.is_empty() {
+        return None;
+    }
+    let meta = attr::attr_meta(timeoutattrs[0]);
+    let ms = match attr::get_meta_item_value_str(meta) {
+      Some(ref ms) => uint::from_str(*ms),
+      None => None
+    };
+    match ms {
+      Some(ms) if ms > 0 => Some(ms),
+      _ => {
+        cx.sess.span_err(
+            timeoutattrs[0].span,
+            ~"timeout attribute must be of the form #[timeout = \"ms\"] \
+              with ms a positive number of milliseconds");
+        None
+      }
+    }
+}
+
 fn add_test_module(cx: &TestCtxt, +m: ast::_mod) -> ast::_mod {
     let testmod = mk_test_module(cx);
     ast::_mod {
@@ -444,12 +471,27 @@ fn mk_test_desc_and_fn_rec(cx: &TestCtxt, test: test) -> @ast::expr {
         quote_expr!( false )
     };
 
+    let timeout_expr = match test.timeout {
+        Some(ms) => {
+            let ms_expr = @ast::expr {
+                id: cx.sess.next_node_id(),
+                callee_id: cx.sess.next_node_id(),
+                node: ast::expr_lit(@nospan(ast::lit_uint(ms as u64,
+                                                          ast::ty_u))),
+                span: span
+            };
+            quote_expr!( ::core::option::Some($ms_expr) )
+        }
+        None => quote_expr!( ::core::option::None )
+    };
+
     let e = quote_expr!(
         self::std::test::TestDescAndFn {
             desc: self::std::test::TestDesc {
                 name: self::std::test::StaticTestName($name_expr),
                 ignore: $ignore_expr,
-                should_fail: $fail_expr
+                should_fail: $fail_expr,
+                timeout: $timeout_expr
             },
             testfn: $t_expr,
         }
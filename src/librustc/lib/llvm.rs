@@ -1150,6 +1150,11 @@ pub extern mod llvm {
     /** Returns the minimum alignment of a type when part of a call frame. */
     pub unsafe fn LLVMCallFrameAlignmentOfType(TD: TargetDataRef,
                                     Ty: TypeRef) -> c_uint;
+    /** Computes the byte offset of the indexed struct element for a
+        target. */
+    pub unsafe fn LLVMOffsetOfElement(TD: TargetDataRef,
+                                      StructTy: TypeRef,
+                                      Element: c_uint) -> c_ulonglong;
 
     /** Disposes target data. */
     pub unsafe fn LLVMDisposeTargetData(TD: TargetDataRef);
@@ -156,5 +156,16 @@ pub const tag_lang_items_item_node_id: uint = 0x75;
 pub const tag_item_unnamed_field: uint = 0x76;
 pub const tag_items_data_item_struct_ctor: uint = 0x77;
 
+// The native libraries and link arguments that a crate needs, which a static
+// library leaves to be linked into whatever uses it. Hierarchy:
+//
+// tag_native_libraries
+// - tag_native_libraries_lib: str
+// - tag_native_libraries_link_arg: str
+
+pub const tag_native_libraries: uint = 0x78;
+pub const tag_native_libraries_lib: uint = 0x79;
+pub const tag_native_libraries_link_arg: uint = 0x7a;
+
 pub type link_meta = {name: @str, vers: @str, extras_hash: @str};
 
@@ -268,6 +268,18 @@ fn resolve_crate(e: @mut Env,
         let cstore = e.cstore;
         cstore::set_crate_data(cstore, cnum, cmeta);
         cstore::add_used_crate_file(cstore, &cfilename);
+
+        // A static library still needs the native libraries it uses, which
+        // a dynamic one would have brought along
+        if cfilename.filetype() == Some(~".rlib") {
+            let natives = decoder::get_native_libraries(cdata);
+            for natives.libs.each |lib| {
+                cstore::add_used_library(cstore, copy *lib);
+            }
+            for natives.link_args.each |arg| {
+                cstore::add_used_link_args(cstore, copy *arg);
+            }
+        }
         return cnum;
       }
       Some(cnum) => {
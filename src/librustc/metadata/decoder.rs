@@ -1068,6 +1068,28 @@ pub fn get_crate_hash(data: @~[u8]) -> ~str {
     return str::from_bytes(reader::doc_data(hashdoc));
 }
 
+/// The native libraries and link arguments that the crate was built with,
+/// which a static library needs its users to link against
+pub fn get_native_libraries(data: @~[u8]) -> {libs: ~[~str],
+                                             link_args: ~[~str]} {
+    let mut libs = ~[];
+    let mut link_args = ~[];
+    let cratedoc = reader::Doc(data);
+    match reader::maybe_get_doc(cratedoc, tag_native_libraries) {
+      None => (),
+      Some(libsdoc) => {
+        for reader::tagged_docs(libsdoc, tag_native_libraries_lib) |doc| {
+            libs.push(reader::doc_as_str(doc));
+        }
+        for reader::tagged_docs(libsdoc,
+                                tag_native_libraries_link_arg) |doc| {
+            link_args.push(reader::doc_as_str(doc));
+        }
+      }
+    }
+    return {libs: libs, link_args: link_args};
+}
+
 pub fn get_crate_vers(data: @~[u8]) -> ~str {
     let attrs = decoder::get_crate_attributes(data);
     return match attr::last_meta_item_value_str_by_name(
@@ -1185,6 +1185,24 @@ fn encode_lang_items(ecx: @encode_ctxt, ebml_w: writer::Encoder) {
     ebml_w.end_tag();   // tag_lang_items
 }
 
+fn encode_native_libraries(ecx: @encode_ctxt, ebml_w: writer::Encoder) {
+    ebml_w.start_tag(tag_native_libraries);
+
+    for cstore::get_used_libraries(ecx.cstore).each |lib| {
+        ebml_w.start_tag(tag_native_libraries_lib);
+        ebml_w.writer.write(str::to_bytes(*lib));
+        ebml_w.end_tag();   // tag_native_libraries_lib
+    }
+    for cstore::get_used_link_args(ecx.cstore).each |arg| {
+        if arg.is_empty() { loop; }
+        ebml_w.start_tag(tag_native_libraries_link_arg);
+        ebml_w.writer.write(str::to_bytes(*arg));
+        ebml_w.end_tag();   // tag_native_libraries_link_arg
+    }
+
+    ebml_w.end_tag();   // tag_native_libraries
+}
+
 fn encode_crate_dep(ecx: @encode_ctxt, ebml_w: writer::Encoder,
                     dep: decoder::crate_dep) {
     ebml_w.start_tag(tag_crate_dep);
@@ -1258,6 +1276,8 @@ pub fn encode_metadata(parms: encode_parms, crate: &crate) -> ~[u8] {
     encode_lang_items(ecx, ebml_w);
     ecx.stats.lang_item_bytes = wr.pos - i;
 
+    encode_native_libraries(ecx, ebml_w);
+
     // Encode and index the items.
     ebml_w.start_tag(tag_items);
     i = wr.pos;
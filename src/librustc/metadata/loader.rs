@@ -13,6 +13,7 @@
 
 use core::prelude::*;
 
+use back::archive;
 use lib::llvm::{False, llvm, mk_object_file, mk_section_iter};
 use metadata::decoder;
 use metadata::encoder;
@@ -30,9 +31,7 @@ use core::io::WriterUtil;
 use core::io;
 use core::os::consts::{macos, freebsd, linux, android, win32};
 use core::option;
-use core::ptr;
 use core::str;
-use core::uint;
 use core::vec;
 
 pub enum os {
@@ -68,7 +67,14 @@ pub fn load_library_crate(cx: ctxt) -> {ident: ~str, data: @~[u8]} {
 
 fn find_library_crate(cx: ctxt) -> Option<{ident: ~str, data: @~[u8]}> {
     attr::require_unique_names(cx.diag, cx.metas);
-    find_library_crate_aux(cx, libname(cx), cx.filesearch)
+    match find_library_crate_aux(cx, libname(cx), cx.filesearch) {
+      // Fall back to a static library when there's no dynamic one
+      None if !cx.static => {
+        find_library_crate_aux(cx, {prefix: ~"lib", suffix: ~".rlib"},
+                               cx.filesearch)
+      }
+      found => found
+    }
 }
 
 fn libname(cx: ctxt) -> {prefix: ~str, suffix: ~str} {
@@ -193,14 +199,29 @@ pub fn metadata_matches(extern_metas: ~[@ast::meta_item],
 
 fn get_metadata_section(os: os,
                         filename: &Path) -> Option<@~[u8]> {
+    let bytes = if filename.filetype() == Some(~".rlib") {
+        archive::read_member(filename, archive::METADATA_MEMBER)
+    } else {
+        get_metadata_section_bytes(os, filename)
+    };
+    match bytes {
+      Some(bytes) => decode_metadata(bytes),
+      None => None
+    }
+}
+
+/// The contents of the metadata section of an object file or a dynamic
+/// library: the version stamp followed by the compressed metadata
+pub fn get_metadata_section_bytes(os: os,
+                                  filename: &Path) -> Option<~[u8]> {
     unsafe {
         let mb = str::as_c_str(filename.to_str(), |buf| {
             llvm::LLVMRustCreateMemoryBufferWithContentsOfFile(buf)
         });
-        if mb as int == 0 { return option::None::<@~[u8]>; }
+        if mb as int == 0 { return option::None::<~[u8]>; }
         let of = match mk_object_file(mb) {
             option::Some(of) => of,
-            _ => return option::None::<@~[u8]>
+            _ => return option::None::<~[u8]>
         };
         let si = mk_section_iter(of.llof);
         while llvm::LLVMIsSectionIteratorAtEnd(of.llof, si.llsi) == False {
@@ -209,36 +230,28 @@ fn get_metadata_section(os: os,
             if name == meta_section_name(os) {
                 let cbuf = llvm::LLVMGetSectionContents(si.llsi);
                 let csz = llvm::LLVMGetSectionSize(si.llsi) as uint;
-                let mut found = None;
                 unsafe {
                     let cvbuf: *u8 = cast::reinterpret_cast(&cbuf);
-                    let vlen = vec::len(encoder::metadata_encoding_version);
-                    debug!("checking %u bytes of metadata-version stamp",
-                           vlen);
-                    let minsz = uint::min(vlen, csz);
-                    let mut version_ok = false;
-                    do vec::raw::buf_as_slice(cvbuf, minsz) |buf0| {
-                        version_ok = (buf0 ==
-                                      encoder::metadata_encoding_version);
-                    }
-                    if !version_ok { return None; }
-
-                    let cvbuf1 = ptr::offset(cvbuf, vlen);
-                    debug!("inflating %u bytes of compressed metadata",
-                           csz - vlen);
-                    do vec::raw::buf_as_slice(cvbuf1, csz-vlen) |bytes| {
-                        let inflated = flate::inflate_bytes(bytes);
-                        found = move Some(@(move inflated));
-                    }
-                    if found != None {
-                        return found;
-                    }
+                    return Some(vec::from_buf(cvbuf, csz));
                 }
             }
             llvm::LLVMMoveToNextSection(si.llsi);
         }
-        return option::None::<@~[u8]>;
+        return option::None::<~[u8]>;
+    }
+}
+
+// Checks the version stamp of the contents of a metadata section and
+// inflates the metadata that follows it
+fn decode_metadata(bytes: &[u8]) -> Option<@~[u8]> {
+    let vlen = vec::len(encoder::metadata_encoding_version);
+    debug!("checking %u bytes of metadata-version stamp", vlen);
+    if bytes.len() < vlen ||
+        vec::view(bytes, 0u, vlen) != encoder::metadata_encoding_version {
+        return None;
     }
+    debug!("inflating %u bytes of compressed metadata", bytes.len() - vlen);
+    Some(@flate::inflate_bytes(vec::view(bytes, vlen, bytes.len())))
 }
 
 pub fn meta_section_name(os: os) -> ~str {
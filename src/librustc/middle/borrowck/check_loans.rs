@@ -40,6 +40,7 @@ use syntax::ast::{m_const, m_imm, m_mutbl};
 use syntax::ast;
 use syntax::ast_util;
 use syntax::codemap::span;
+use syntax::diagnostic::SpanLabel;
 use syntax::print::pprust;
 use syntax::visit;
 
@@ -320,16 +321,19 @@ impl CheckLoanCtxt {
             (TotalTake, PartialFreeze) | (PartialFreeze, TotalTake) |
             (TotalTake, PartialTake) | (PartialTake, TotalTake) |
             (TotalTake, TotalTake) => {
-                self.bccx.span_err(
+                self.bccx.span_err_labelled(
                     new_loan.cmt.span,
                     fmt!("loan of %s as %s \
                           conflicts with prior loan",
                          self.bccx.cmt_to_str(new_loan.cmt),
-                         self.bccx.loan_kind_to_str(new_loan.kind)));
-                self.bccx.span_note(
-                    old_loan.cmt.span,
-                    fmt!("prior loan as %s granted here",
-                         self.bccx.loan_kind_to_str(old_loan.kind)));
+                         self.bccx.loan_kind_to_str(new_loan.kind)),
+                    "E0017",
+                    [SpanLabel {
+                        span: old_loan.cmt.span,
+                        label: fmt!("prior loan as %s granted here",
+                                    self.bccx.loan_kind_to_str(
+                                        old_loan.kind))
+                    }]);
             }
         }
     }
@@ -359,9 +363,10 @@ impl CheckLoanCtxt {
             match cmt.mutbl {
                 McDeclared | McInherited => { /*ok*/ }
                 McReadOnly | McImmutable => {
-                    self.bccx.span_err(
+                    self.bccx.span_err_with_code(
                         ex.span,
-                        at.ing_form(self.bccx.cmt_to_str(cmt)));
+                        at.ing_form(self.bccx.cmt_to_str(cmt)),
+                        "E0018");
                     return;
                 }
             }
@@ -441,14 +446,16 @@ impl CheckLoanCtxt {
                 Immobile => { /* ok */ }
                 TotalFreeze | PartialFreeze |
                 TotalTake | PartialTake => {
-                    self.bccx.span_err(
+                    self.bccx.span_err_labelled(
                         ex.span,
                         fmt!("%s prohibited due to outstanding loan",
-                             at.ing_form(self.bccx.cmt_to_str(cmt))));
-                    self.bccx.span_note(
-                        loan.cmt.span,
-                        fmt!("loan of %s granted here",
-                             self.bccx.cmt_to_str(loan.cmt)));
+                             at.ing_form(self.bccx.cmt_to_str(cmt))),
+                        "E0019",
+                        [SpanLabel {
+                            span: loan.cmt.span,
+                            label: fmt!("loan of %s granted here",
+                                        self.bccx.cmt_to_str(loan.cmt))
+                        }]);
                     return;
                 }
             }
@@ -505,21 +512,24 @@ impl CheckLoanCtxt {
                 match self.analyze_move_out_from_cmt(cmt) {
                     MoveOk => {}
                     MoveFromIllegalCmt(_) => {
-                        self.bccx.span_err(
+                        self.bccx.span_err_with_code(
                             cmt.span,
                             fmt!("moving out of %s",
-                                 self.bccx.cmt_to_str(cmt)));
+                                 self.bccx.cmt_to_str(cmt)),
+                            "E0020");
                     }
                     MoveWhileBorrowed(_, loan_cmt) => {
-                        self.bccx.span_err(
+                        self.bccx.span_err_labelled(
                             cmt.span,
                             fmt!("moving out of %s prohibited \
                                   due to outstanding loan",
-                                 self.bccx.cmt_to_str(cmt)));
-                        self.bccx.span_note(
-                            loan_cmt.span,
-                            fmt!("loan of %s granted here",
-                                 self.bccx.cmt_to_str(loan_cmt)));
+                                 self.bccx.cmt_to_str(cmt)),
+                            "E0021",
+                            [SpanLabel {
+                                span: loan_cmt.span,
+                                label: fmt!("loan of %s granted here",
+                                            self.bccx.cmt_to_str(loan_cmt))
+                            }]);
                     }
                 }
             }
@@ -660,21 +670,25 @@ fn check_loans_in_fn(fk: visit::fn_kind,
                     match move_err {
                         MoveOk => {}
                         MoveFromIllegalCmt(move_cmt) => {
-                            self.bccx.span_err(
+                            self.bccx.span_err_with_code(
                                 cap_var.span,
                                 fmt!("illegal by-move capture of %s",
-                                     self.bccx.cmt_to_str(move_cmt)));
+                                     self.bccx.cmt_to_str(move_cmt)),
+                                "E0020");
                         }
                         MoveWhileBorrowed(move_cmt, loan_cmt) => {
-                            self.bccx.span_err(
+                            self.bccx.span_err_labelled(
                                 cap_var.span,
                                 fmt!("by-move capture of %s prohibited \
                                       due to outstanding loan",
-                                     self.bccx.cmt_to_str(move_cmt)));
-                            self.bccx.span_note(
-                                loan_cmt.span,
-                                fmt!("loan of %s granted here",
-                                     self.bccx.cmt_to_str(loan_cmt)));
+                                     self.bccx.cmt_to_str(move_cmt)),
+                                "E0021",
+                                [SpanLabel {
+                                    span: loan_cmt.span,
+                                    label: fmt!("loan of %s granted here",
+                                                self.bccx.cmt_to_str(
+                                                    loan_cmt))
+                                }]);
                         }
                     }
                 }
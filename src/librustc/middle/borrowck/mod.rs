@@ -247,6 +247,7 @@ use syntax::ast;
 use syntax::ast_map;
 use syntax::ast_util;
 use syntax::codemap::span;
+use syntax::diagnostic::SpanLabel;
 use syntax::print::pprust;
 use syntax::visit;
 
@@ -518,10 +519,11 @@ pub impl BorrowckCtxt {
     }
 
     fn report(&self, err: bckerr) {
-        self.span_err(
+        self.span_err_with_code(
             err.cmt.span,
             fmt!("illegal borrow: %s",
-                 self.bckerr_to_str(err)));
+                 self.bckerr_to_str(err)),
+            "E0022");
         self.note_and_explain_bckerr(err);
     }
 
@@ -529,6 +531,15 @@ pub impl BorrowckCtxt {
         self.tcx.sess.span_err(s, m);
     }
 
+    fn span_err_with_code(&self, s: span, +m: ~str, code: &str) {
+        self.tcx.sess.span_err_with_code(s, m, code);
+    }
+
+    fn span_err_labelled(&self, s: span, +m: ~str, code: &str,
+                         labels: &[SpanLabel]) {
+        self.tcx.sess.span_err_labelled_with_code(s, m, code, labels);
+    }
+
     fn span_note(&self, s: span, +m: ~str) {
         self.tcx.sess.span_note(s, m);
     }
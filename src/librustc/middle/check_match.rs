@@ -83,9 +83,10 @@ pub fn check_expr(cx: @MatchCheckCtxt, ex: @expr, &&s: (), v: visit::vt<()>) {
        if (*arms).is_empty() {
            if !type_is_empty(cx.tcx, pat_ty) {
                // We know the type is inhabited, so this must be wrong
-               cx.tcx.sess.span_err(ex.span, fmt!("non-exhaustive patterns: \
-                            type %s is non-empty",
-                            ty_to_str(cx.tcx, pat_ty)));
+               cx.tcx.sess.span_err_with_code(ex.span,
+                            fmt!("non-exhaustive patterns: \
+                                  type %s is non-empty",
+                                 ty_to_str(cx.tcx, pat_ty)), "E0002");
            }
            // If the type *is* empty, it's vacuously exhaustive
            return;
@@ -115,7 +116,9 @@ pub fn check_arms(cx: @MatchCheckCtxt, arms: ~[arm]) {
             let v = ~[*pat];
             match is_useful(cx, copy seen, v) {
               not_useful => {
-                cx.tcx.sess.span_err(pat.span, ~"unreachable pattern");
+                cx.tcx.sess.span_err_with_code(pat.span,
+                                               ~"unreachable pattern",
+                                               "E0001");
               }
               _ => ()
             }
@@ -168,7 +171,7 @@ pub fn check_exhaustive(cx: @MatchCheckCtxt, sp: span, pats: ~[@pat]) {
       Some(ref s) => ~": " + (*s) + ~" not covered",
       None => ~""
     };
-    cx.tcx.sess.span_err(sp, msg);
+    cx.tcx.sess.span_err_with_code(sp, msg, "E0003");
 }
 
 pub type matrix = ~[~[@pat]];
@@ -648,8 +651,9 @@ pub fn check_local(cx: @MatchCheckCtxt,
                    v: visit::vt<()>) {
     visit::visit_local(loc, s, v);
     if is_refutable(cx, loc.node.pat) {
-        cx.tcx.sess.span_err(loc.node.pat.span,
-                          ~"refutable pattern in local binding");
+        cx.tcx.sess.span_err_with_code(loc.node.pat.span,
+                                       ~"refutable pattern in local binding",
+                                       "E0004");
     }
 
     // Check legality of move bindings.
@@ -671,8 +675,10 @@ pub fn check_fn(cx: @MatchCheckCtxt,
     visit::visit_fn(kind, decl, body, sp, id, s, v);
     for decl.inputs.each |input| {
         if is_refutable(cx, input.pat) {
-            cx.tcx.sess.span_err(input.pat.span,
-                              ~"refutable pattern in function argument");
+            cx.tcx.sess.span_err_with_code(
+                input.pat.span,
+                ~"refutable pattern in function argument",
+                "E0005");
         }
     }
 }
@@ -745,26 +751,30 @@ pub fn check_legality_of_move_bindings(cx: @MatchCheckCtxt,
     let check_move: &fn(@pat, Option<@pat>) = |p, sub| {
         // check legality of moving out of the enum
         if sub.is_some() {
-            tcx.sess.span_err(
+            tcx.sess.span_err_with_code(
                 p.span,
-                ~"cannot bind by-move with sub-bindings");
+                ~"cannot bind by-move with sub-bindings",
+                "E0006");
         } else if has_guard {
-            tcx.sess.span_err(
+            tcx.sess.span_err_with_code(
                 p.span,
-                ~"cannot bind by-move into a pattern guard");
+                ~"cannot bind by-move into a pattern guard",
+                "E0007");
         } else if by_ref_span.is_some() {
-            tcx.sess.span_err(
+            tcx.sess.span_err_with_code(
                 p.span,
                 ~"cannot bind by-move and by-ref \
-                  in the same pattern");
+                  in the same pattern",
+                "E0008");
             tcx.sess.span_note(
                 by_ref_span.get(),
                 ~"by-ref binding occurs here");
         } else if is_lvalue {
-            tcx.sess.span_err(
+            tcx.sess.span_err_with_code(
                 p.span,
                 ~"cannot bind by-move when \
-                  matching an lvalue");
+                  matching an lvalue",
+                "E0009");
         }
     };
 
@@ -0,0 +1,351 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * The registry of numbered error codes.
+ *
+ * Errors reported with `sess.span_err_with_code` carry one of these codes,
+ * which is printed after the message and can be looked up with
+ * `rustc --explain CODE`. Codes are never reused: if an error goes away,
+ * its entry stays here with an explanation saying so.
+ */
+
+use core::prelude::*;
+
+use core::str;
+
+/// Every error code as (code, summary, long explanation with examples)
+pub fn error_codes() -> ~[(~str, ~str, ~str)] {
+    ~[
+    // check_match
+    (~"E0001", ~"unreachable pattern", ~"\
+This pattern can never match, because every value it would match is
+already matched by an earlier arm:
+
+    match x {
+        Some(_) => 1,
+        Some(0) => 2,   // unreachable
+        None => 3
+    }
+
+Remove the arm, or move it before the arm that covers it."),
+
+    (~"E0002", ~"empty match on a non-empty type", ~"\
+A `match` with no arms is only exhaustive if its type has no values at
+all, such as an enum without variants:
+
+    enum Void {}
+    fn absurd(v: Void) -> int { match v {} }   // ok
+
+    fn f(x: int) -> int { match x {} }          // error
+
+Add arms covering the values of the type."),
+
+    (~"E0003", ~"non-exhaustive patterns", ~"\
+Every possible value of the matched expression must be covered by some
+arm, so that the `match` always has a result:
+
+    match x {
+        Some(n) => n
+    }                   // error: None not covered
+
+Add arms for the missing cases, or a wildcard arm `_ => ...` at the
+end."),
+
+    (~"E0004", ~"refutable pattern in local binding", ~"\
+The pattern of a `let` must match any value of its type, because there
+is nowhere to go if it doesn't:
+
+    let Some(n) = opt;      // error: what if opt is None?
+
+Use a `match` instead:
+
+    let n = match opt { Some(n) => n, None => 0 };"),
+
+    (~"E0005", ~"refutable pattern in function argument", ~"\
+The patterns of function arguments must match any value of their type,
+because a call cannot fail to bind its arguments:
+
+    fn first(Some(n): Option<int>) -> int { n }     // error
+
+Take the whole value and `match` on it in the body instead."),
+
+    (~"E0006", ~"by-move binding with sub-bindings", ~"\
+A by-move binding cannot also have sub-bindings, since the value would
+be moved and borrowed at the same time:
+
+    match x {
+        y @ Some(ref z) => ...      // error if y is moved
+    }
+
+Bind `y` by reference, or drop the sub-binding."),
+
+    (~"E0007", ~"by-move binding in a pattern guard", ~"\
+The guard of an arm runs before the arm is chosen, so it cannot see
+values moved out of the matched expression:
+
+    match x {
+        Some(s) if s.len() > 0u => ...     // error: s is moved
+        _ => ...
+    }
+
+Bind by reference with `Some(ref s)` instead."),
+
+    (~"E0008", ~"by-move and by-ref bindings in the same pattern", ~"\
+A pattern cannot move part of a value out while also borrowing another
+part of it:
+
+    match pair {
+        (a, ref b) => ...   // error if a is moved
+    }
+
+Bind every part by reference, or every part by move."),
+
+    (~"E0009", ~"by-move binding when matching an lvalue", ~"\
+Matching a place, such as a local variable or a field, cannot move out
+of it, because the place would be left without a value:
+
+    match self.name {
+        Some(s) => s,       // error: would move out of self.name
+        None => ~\"\"
+    }
+
+Bind by reference with `Some(ref s)`, or match on a copy."),
+
+    // resolve
+    (~"E0010", ~"unresolved import", ~"\
+The path in a `use` declaration does not name any item:
+
+    use core::vec::no_such_fn;      // error
+
+Check the spelling and that the item is public in the module it is
+imported from."),
+
+    (~"E0011", ~"failed to resolve import", ~"\
+A `use` declaration could not be resolved because a module along its
+path could not be found. The errors reported before this one say which
+part of the path is at fault:
+
+    use nonexistent::foo;           // error
+
+Check each component of the path."),
+
+    (~"E0012", ~"unresolved name", ~"\
+A path or a name used as a value does not refer to anything in scope:
+
+    fn main() { io::println(greeting); }     // error
+
+Declare the name, import it with `use`, or fix its spelling. Inside
+methods, fields have to be reached through `self`, as in
+`self.greeting`."),
+
+    (~"E0013", ~"not a module", ~"\
+A path goes through something that is not a module, such as a type or a
+function:
+
+    struct Point { x: int, y: int }
+    use Point::x;                   // error
+
+Only modules, and the enums and traits they contain, can appear in the
+middle of a path."),
+
+    (~"E0014", ~"not a structure", ~"\
+A structure expression names something that is not a struct or a
+struct-like enum variant:
+
+    type Meters = int;
+    let m = Meters { value: 3 };    // error
+
+Use the name of the struct itself."),
+
+    (~"E0015", ~"use of undeclared label", ~"\
+`break` and `loop` can only name the label of a loop that encloses
+them:
+
+    loop outer: {
+        break inner;                // error
+    }
+
+Use the name of an enclosing loop's label."),
+
+    // typeck
+    (~"E0016", ~"mismatched types", ~"\
+An expression has a different type than the one expected where it is
+used:
+
+    let x: int = ~\"three\";          // error
+
+Convert the value, or change the type that is expected. The note that
+follows the error gives the part of the two types that differ."),
+
+    (~"E0023", ~"unresolved method", ~"\
+A method was called on a value whose type has no method of that name,
+either of its own or from a trait that is in scope:
+
+    let x = 5;
+    x.frobnicate();                 // error
+
+Check the spelling, or import the trait that provides the method."),
+
+    (~"E0024", ~"no such field", ~"\
+A field was accessed on a value whose type has neither a field nor a
+method of that name:
+
+    struct Point { x: int, y: int }
+    let p = Point { x: 1, y: 2 };
+    p.z;                            // error
+
+Check the spelling against the definition of the type."),
+
+    (~"E0025", ~"wrong number of arguments", ~"\
+A function was called with more or fewer arguments than it declares:
+
+    fn add(a: int, b: int) -> int { a + b }
+    add(1);                         // error
+
+A closure passed with `do` or `for` counts as the last argument."),
+
+    (~"E0026", ~"wrong number of type parameters", ~"\
+A type, function or method was given more or fewer explicit type
+parameters than it declares:
+
+    fn id<T>(x: T) -> T { x }
+    id::<int, int>(5);              // error
+
+Supply one type for each parameter, or none at all to have them
+inferred where that is allowed."),
+
+    (~"E0027", ~"no field of that name in structure", ~"\
+A structure literal or pattern names a field the structure does not
+have:
+
+    struct Point { x: int, y: int }
+    let p = Point { x: 1, z: 2 };   // error
+
+Use the field names from the definition of the structure."),
+
+    (~"E0028", ~"field specified more than once", ~"\
+A structure literal gives the same field twice:
+
+    let p = Point { x: 1, x: 2 };   // error
+
+Remove one of them."),
+
+    (~"E0029", ~"missing fields in structure literal", ~"\
+A structure literal must give a value for every field of the structure:
+
+    struct Point { x: int, y: int }
+    let p = Point { x: 1 };         // error: missing field `y`
+
+Add the missing fields, or use functional update syntax,
+`Point { x: 1, .. other }`, to copy the rest from another value."),
+
+    // borrowck
+    (~"E0017", ~"conflicting loans", ~"\
+A value is borrowed in a way that conflicts with a borrow that is still
+in effect, for example mutably while it is also borrowed immutably:
+
+    let mut v = ~[1, 2, 3];
+    let first = &v[0];
+    let all = &mut v;               // error
+
+Limit the first borrow to a smaller scope, or copy the value out."),
+
+    (~"E0018", ~"assignment to an immutable location", ~"\
+Only mutable locals, fields and dereferences of mutable pointers can be
+assigned to:
+
+    let x = 5;
+    x = 6;                          // error
+
+Declare the location with `mut`."),
+
+    (~"E0019", ~"assignment to a borrowed location", ~"\
+A location cannot change while it is borrowed, because the borrowed
+pointer would see the change:
+
+    let mut x = 5;
+    let p = &x;
+    x = 6;                          // error
+
+Let the borrow end before assigning."),
+
+    (~"E0020", ~"move out of a location that cannot be moved from", ~"\
+Values can only be moved out of locals and arguments, not out of
+dereferenced pointers or fields of borrowed structures:
+
+    fn f(v: &~str) -> ~str { *v }   // error
+
+Copy the value instead, or take it by value."),
+
+    (~"E0021", ~"move out of a borrowed location", ~"\
+A value cannot be moved while it is borrowed, because the borrowed
+pointer would be left dangling:
+
+    let s = ~\"hi\";
+    let p = &s;
+    let t = move s;                 // error
+
+Let the borrow end before moving."),
+
+    (~"E0022", ~"illegal borrow", ~"\
+A borrowed pointer cannot be taken because the value may not live as
+long as the pointer, or may be changed while it is borrowed:
+
+    fn f(x: @mut Option<~int>) {
+        match *x {
+            Some(ref y) => ...      // error: *x may change
+            None => ()
+        }
+    }
+
+The notes after the error explain the specific reason. Copying the
+value first, or borrowing something that cannot change, avoids it.")
+    ]
+}
+
+/// The long explanation of `code`, or `None` if there is no such code
+pub fn explain(code: &str) -> Option<~str> {
+    let code = str::to_upper(code);
+    for error_codes().each |entry| {
+        let (c, summary, text) = copy *entry;
+        if c == code {
+            return Some(fmt!("%s: %s\n\n%s", c, summary, text));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::char;
+    use core::str;
+
+    #[test]
+    fn test_codes_unique_and_well_formed() {
+        let mut seen = ~[];
+        for error_codes().each |entry| {
+            let (code, summary, text) = copy *entry;
+            assert code.len() == 5u && code[0] == 'E' as u8;
+            assert str::all(str::slice(code, 1u, 5u), char::is_digit);
+            assert !seen.contains(&code);
+            assert !summary.is_empty() && !text.is_empty();
+            seen.push(code);
+        }
+    }
+
+    #[test]
+    fn test_explain() {
+        let text = explain("e0003").get();
+        assert str::starts_with(text, "E0003: non-exhaustive patterns\n");
+        assert explain("E9999").is_none();
+    }
+}
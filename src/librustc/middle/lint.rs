@@ -38,8 +38,9 @@ use std::oldsmallintmap::{Map, SmallIntMap};
 use std::oldsmallintmap;
 use syntax::ast_util::{path_to_ident};
 use syntax::attr;
-use syntax::codemap::span;
+use syntax::codemap::{Pos, span};
 use syntax::codemap;
+use syntax::diagnostic;
 use syntax::print::pprust::{expr_to_str, mode_to_str, pat_to_str};
 use syntax::{ast, ast_util, visit};
 
@@ -171,6 +172,8 @@ pub fn get_lint_dict() -> lint_dict {
            desc: "warn about deprecated uses of modes",
            default: warn}),
 
+        // No check emits this lint; it is kept so crates that set its
+        // level still compile.
         (~"deprecated_pattern",
          @{lint: deprecated_pattern,
            desc: "warn about deprecated uses of pattern bindings",
@@ -459,11 +462,17 @@ fn check_item_while_true(cx: ty::ctxt, it: @ast::item) {
                             ast::expr_lit(@codemap::spanned {
                                 node: ast::lit_bool(true), _}) =>
                             {
-                                cx.sess.span_lint(
+                                cx.sess.span_lint_with_suggestion(
                                     while_true, e.id, it.id,
                                     e.span,
                                     ~"denote infinite loops \
-                                      with loop { ... }");
+                                      with loop { ... }",
+                                    diagnostic::Suggestion {
+                                        span: codemap::mk_sp(e.span.lo,
+                                                             cond.span.hi),
+                                        replacement: ~"loop",
+                                        machine_applicable: true
+                                    });
                             }
                             _ => ()
                         }
@@ -852,14 +861,50 @@ fn check_item_non_camel_case_types(cx: ty::ctxt, it: @ast::item) {
         }
     }
 
+    fn to_camel_case(ident: &str) -> ~str {
+        let mut out = ~"";
+        for str::split_char(ident, '_').each |word| {
+            if !word.is_empty() {
+                out += word.substr(0, 1).to_upper();
+                out += word.substr(1, word.len() - 1);
+            }
+        }
+        out
+    }
+
     fn check_case(cx: ty::ctxt, ident: ast::ident,
                   expr_id: ast::node_id, item_id: ast::node_id,
                   span: span) {
-        if !is_camel_case(cx, ident) {
-            cx.sess.span_lint(
-                non_camel_case_types, expr_id, item_id, span,
-                ~"type, variant, or trait should have \
-                  a camel case identifier");
+        if is_camel_case(cx, ident) {
+            return;
+        }
+        let msg = ~"type, variant, or trait should have \
+                    a camel case identifier";
+        // We only have the span of the whole item, so look for the name
+        // in it. Renaming the declaration alone would break every use of
+        // the name, so this is never applied automatically.
+        let name = cx.sess.str_of(ident);
+        let found = if span.expn_info.is_none() {
+            str::find_str(cx.sess.codemap.span_to_snippet(span), name)
+        } else {
+            None
+        };
+        match found {
+            Some(idx) => {
+                let lo = span.lo + codemap::BytePos(idx);
+                let hi = lo + codemap::BytePos(name.len());
+                cx.sess.span_lint_with_suggestion(
+                    non_camel_case_types, expr_id, item_id, span, msg,
+                    diagnostic::Suggestion {
+                        span: codemap::mk_sp(lo, hi),
+                        replacement: to_camel_case(name),
+                        machine_applicable: false
+                    });
+            }
+            _ => {
+                cx.sess.span_lint(non_camel_case_types, expr_id, item_id,
+                                  span, msg);
+            }
         }
     }
 
@@ -894,6 +939,40 @@ fn check_fn(tcx: ty::ctxt, fk: visit::fn_kind, decl: ast::fn_decl,
     check_fn_deprecated_modes(tcx, fn_ty, decl, span, id);
 }
 
+// The span of the mode sigil written just before an argument's pattern,
+// if it is really there in the source
+fn arg_mode_span(tcx: ty::ctxt, arg: &ast::arg) -> Option<span> {
+    let lo = arg.pat.span.lo;
+    // `-` is the outdated spelling of `+`
+    for [mode_to_str(arg.mode), ~"-"].each |sigil| {
+        let n = sigil.len();
+        if n == 0u || lo.to_uint() < n {
+            loop;
+        }
+        let sp = codemap::mk_sp(lo - codemap::BytePos(n), lo);
+        if tcx.sess.codemap.span_to_snippet(sp) == *sigil {
+            return Some(sp);
+        }
+    }
+    None
+}
+
+fn lint_explicit_mode(tcx: ty::ctxt, id: ast::node_id, span: span,
+                      arg: &ast::arg, +msg: ~str, machine_applicable: bool) {
+    match arg_mode_span(tcx, arg) {
+        Some(sp) => {
+            tcx.sess.span_lint_with_suggestion(
+                deprecated_mode, id, id, span, msg,
+                diagnostic::Suggestion {
+                    span: sp,
+                    replacement: ~"",
+                    machine_applicable: machine_applicable
+                });
+        }
+        None => tcx.sess.span_lint(deprecated_mode, id, id, span, msg)
+    }
+}
+
 fn check_fn_deprecated_modes(tcx: ty::ctxt, fn_ty: ty::t, decl: ast::fn_decl,
                              span: span, id: ast::node_id) {
     match ty::get(fn_ty).sty {
@@ -909,18 +988,21 @@ fn check_fn_deprecated_modes(tcx: ty::ctxt, fn_ty: ty::t, decl: ast::fn_decl,
                 match arg_ast.mode {
                     ast::expl(ast::by_copy) => {
                         if !tcx.legacy_modes {
-                            tcx.sess.span_lint(
-                                deprecated_mode, id, id, span,
-                                fmt!("argument %d uses by-copy mode",
-                                     counter));
+                            // The default mode copies too, so dropping the
+                            // `+` changes nothing
+                            lint_explicit_mode(tcx, id, span, arg_ast,
+                                               fmt!("argument %d uses \
+                                                     by-copy mode", counter),
+                                               true);
                         }
                     }
 
                     ast::expl(_) => {
-                        tcx.sess.span_lint(
-                            deprecated_mode, id, id,
-                            span,
-                         fmt!("argument %d uses an explicit mode", counter));
+                        // The argument's type has to change as well
+                        lint_explicit_mode(tcx, id, span, arg_ast,
+                                           fmt!("argument %d uses an \
+                                                 explicit mode", counter),
+                                           false);
                     }
 
                     ast::infer(_) => {
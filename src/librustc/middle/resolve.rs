@@ -67,7 +67,8 @@ use syntax::attr::{attr_metas, contains_name, attrs_contains_name};
 use syntax::parse::token::ident_interner;
 use syntax::parse::token::special_idents;
 use syntax::print::pprust::{pat_to_str, path_to_str};
-use syntax::codemap::{span, dummy_sp};
+use syntax::codemap::{BytePos, Pos, span, dummy_sp, mk_sp};
+use syntax::diagnostic::Suggestion;
 use syntax::visit::{default_visitor, fk_method, mk_vt, Visitor, visit_block};
 use syntax::visit::{visit_crate, visit_expr, visit_expr_opt, visit_fn};
 use syntax::visit::{visit_foreign_item, visit_item, visit_method_helper};
@@ -774,6 +775,7 @@ pub fn Resolver(session: Session,
         graph_root: graph_root,
 
         unused_import_lint_level: unused_import_lint_level(session),
+        single_imports: DVec(),
 
         trait_info: HashMap(),
         structs: HashMap(),
@@ -821,6 +823,11 @@ pub struct Resolver {
 
     unused_import_lint_level: level,
 
+    // The spans of the path and of the whole `use` statement of every
+    // import statement that imports only one name, which is all that has
+    // to go if that name is unused.
+    single_imports: DVec<(span, span)>,
+
     trait_info: HashMap<def_id,@HashMap<ident,()>>,
     structs: HashMap<def_id,()>,
 
@@ -1434,6 +1441,12 @@ pub impl Resolver {
                                 type_value_ns => AnyNS
                             };
 
+                            if view_paths.len() == 1u &&
+                                    view_item.attrs.is_empty() {
+                                self.single_imports.push((view_path.span,
+                                                          view_item.span));
+                            }
+
                             let source_ident = full_path.idents.last();
                             let subclass = @SingleImport(binding,
                                                          source_ident,
@@ -1981,7 +1994,8 @@ pub impl Resolver {
                     let msg = fmt!("failed to resolve import: %s",
                                    self.import_path_to_str(idents,
                                    *import_directive.subclass));
-                    self.session.span_err(import_directive.span, msg);
+                    self.session.span_err_with_code(import_directive.span,
+                                                    msg, "E0011");
                 }
                 Indeterminate => {
                     // Bail out. We'll come around next time.
@@ -2576,7 +2590,9 @@ pub impl Resolver {
                                               TypeNS,
                                               false) {
                 Failed => {
-                    self.session.span_err(span, ~"unresolved name");
+                    self.session.span_err_with_code(span,
+                                                    ~"unresolved name",
+                                                    "E0012");
                     return Failed;
                 }
                 Indeterminate => {
@@ -2593,12 +2609,11 @@ pub impl Resolver {
                             match type_def.module_def {
                                 None => {
                                     // Not a module.
-                                    self.session.span_err(span,
-                                                          fmt!("not a \
-                                                                module: %s",
-                                                               self.session.
-                                                                   str_of(
-                                                                    name)));
+                                    self.session.span_err_with_code(
+                                        span,
+                                        fmt!("not a module: %s",
+                                             self.session.str_of(name)),
+                                        "E0013");
                                     return Failed;
                                 }
                                 Some(copy module_def) => {
@@ -2608,10 +2623,11 @@ pub impl Resolver {
                         }
                         None => {
                             // There are no type bindings at all.
-                            self.session.span_err(span,
-                                                  fmt!("not a module: %s",
-                                                       self.session.str_of(
-                                                            name)));
+                            self.session.span_err_with_code(
+                                span,
+                                fmt!("not a module: %s",
+                                     self.session.str_of(name)),
+                                "E0013");
                             return Failed;
                         }
                     }
@@ -2650,7 +2666,8 @@ pub impl Resolver {
         let mut start_index;
         match module_prefix_result {
             Failed => {
-                self.session.span_err(span, ~"unresolved name");
+                self.session.span_err_with_code(span, ~"unresolved name",
+                                                "E0012");
                 return Failed;
             }
             Indeterminate => {
@@ -2678,8 +2695,8 @@ pub impl Resolver {
                             module_path.get_elt(0));
                         match result {
                             Failed => {
-                                self.session.span_err(span,
-                                                      ~"unresolved name");
+                                self.session.span_err_with_code(
+                                    span, ~"unresolved name", "E0012");
                                 return Failed;
                             }
                             Indeterminate => {
@@ -3145,8 +3162,8 @@ pub impl Resolver {
                 is_none(&value_result) &&
                 is_none(&type_result) {
 
-            self.session.span_err(import_directive.span,
-                                  ~"unresolved import");
+            self.session.span_err_with_code(import_directive.span,
+                                            ~"unresolved import", "E0010");
             return Failed;
         }
 
@@ -3180,8 +3197,9 @@ pub impl Resolver {
         let index = module_.resolved_import_count;
         let import_count = module_.imports.len();
         if index != import_count {
-            self.session.span_err(module_.imports.get_elt(index).span,
-                                  ~"unresolved import");
+            self.session.span_err_with_code(
+                module_.imports.get_elt(index).span,
+                ~"unresolved import", "E0010");
         }
 
         // Descend into children and anonymous children.
@@ -4464,6 +4482,9 @@ pub impl Resolver {
 
     fn resolve_bare_identifier_pattern(name: ident)
                                     -> BareIdentifierPatternResolution {
+        // Items are never bound to a hygiene mark, so a marked pattern
+        // from a macro expansion can only name one under its plain name.
+        let name = self.session.intr().unmark(name);
         match self.resolve_item_in_lexical_scope(self.current_module,
                                                  name,
                                                  ValueNS,
@@ -4538,6 +4559,10 @@ pub impl Resolver {
                           span: span)
                        -> Option<def> {
 
+        // A name marked by macro expansion that the expansion doesn't bind
+        // means whatever the plain name means where it is used.
+        let unmarked = self.session.intr().unmark(identifier);
+
         if check_ribs {
             match self.resolve_identifier_in_local_ribs(identifier,
                                                       namespace,
@@ -4545,13 +4570,21 @@ pub impl Resolver {
                 Some(def) => {
                     return Some(def);
                 }
+                None if unmarked != identifier => {
+                    match self.resolve_identifier_in_local_ribs(unmarked,
+                                                              namespace,
+                                                              span) {
+                        Some(def) => return Some(def),
+                        None => ()
+                    }
+                }
                 None => {
                     // Continue.
                 }
             }
         }
 
-        return self.resolve_item_by_identifier_in_lexical_scope(identifier,
+        return self.resolve_item_by_identifier_in_lexical_scope(unmarked,
                                                                 namespace);
     }
 
@@ -4844,16 +4877,16 @@ pub impl Resolver {
                         let wrong_name = self.idents_to_str(
                             /*bad*/copy path.idents);
                         if self.name_exists_in_scope_struct(wrong_name) {
-                            self.session.span_err(expr.span,
+                            self.session.span_err_with_code(expr.span,
                                         fmt!("unresolved name: `%s`. \
                                             Did you mean: `self.%s`?",
                                         wrong_name,
-                                        wrong_name));
+                                        wrong_name), "E0012");
                         }
                         else {
-                            self.session.span_err(expr.span,
+                            self.session.span_err_with_code(expr.span,
                                                 fmt!("unresolved name: %s",
-                                                wrong_name));
+                                                wrong_name), "E0012");
                         }
                     }
                 }
@@ -4898,10 +4931,11 @@ pub impl Resolver {
                         self.record_def(expr.id, definition);
                     }
                     _ => {
-                        self.session.span_err(
+                        self.session.span_err_with_code(
                             path.span,
                             fmt!("`%s` does not name a structure",
-                                 self.idents_to_str(path.idents)));
+                                 self.idents_to_str(path.idents)),
+                            "E0014");
                     }
                 }
 
@@ -4921,10 +4955,11 @@ pub impl Resolver {
                 match self.search_ribs(self.label_ribs, label, expr.span,
                                        DontAllowCapturingSelf) {
                     None =>
-                        self.session.span_err(expr.span,
-                                              fmt!("use of undeclared label \
-                                                   `%s`", self.session.str_of(
-                                                  label))),
+                        self.session.span_err_with_code(
+                            expr.span,
+                            fmt!("use of undeclared label `%s`",
+                                 self.session.str_of(label)),
+                            "E0015"),
                     Some(dl_def(def @ def_label(_))) =>
                         self.record_def(expr.id, def),
                     Some(_) =>
@@ -5267,8 +5302,41 @@ pub impl Resolver {
                                               is allowed");
                     }
                 }
+                match self.unused_import_removal(import_resolution.span) {
+                    Some(suggestion) => self.session.span_suggest(suggestion),
+                    None => ()
+                }
+            }
+        }
+    }
+
+    // How to delete an unused import: its whole `use` statement, and the
+    // line it is on if there is nothing else there
+    fn unused_import_removal(path_span: span) -> Option<Suggestion> {
+        for self.single_imports.each |&(path_sp, item_sp)| {
+            if path_sp != path_span || item_sp.expn_info.is_some() {
+                loop;
             }
+            let lo = self.session.codemap.lookup_char_pos(item_sp.lo);
+            let fm = lo.file;
+            let line_lo = fm.lines[lo.line - 1u];
+            let start = (line_lo - fm.start_pos).to_uint();
+            let end = (item_sp.hi - fm.start_pos).to_uint();
+            let before = str::slice(*fm.src, start,
+                                    (item_sp.lo - fm.start_pos).to_uint());
+            let sp = if str::is_whitespace(before) && end < fm.src.len() &&
+                    fm.src[end] == '\n' as u8 {
+                mk_sp(line_lo, item_sp.hi + BytePos(1u))
+            } else {
+                item_sp
+            };
+            return Some(Suggestion {
+                span: sp,
+                replacement: ~"",
+                machine_applicable: true
+            });
         }
+        None
     }
 
 
@@ -1143,7 +1143,7 @@ pub fn trans_stmt(cx: block, s: ast::stmt) -> block {
                 ast::decl_local(locals) => {
                     for vec::each(locals) |local| {
                         bcx = init_local(bcx, *local);
-                        if cx.sess().opts.extra_debuginfo {
+                        if cx.sess().opts.debuginfo {
                             debuginfo::create_local_var(bcx, *local);
                         }
                     }
@@ -1699,7 +1699,7 @@ pub fn copy_args_to_allocas(fcx: fn_ctxt,
 
         fcx.llargs.insert(arg_id, local_mem(llarg));
 
-        if fcx.ccx.sess.opts.extra_debuginfo {
+        if fcx.ccx.sess.opts.debuginfo {
             debuginfo::create_arg(bcx, args[arg_n], args[arg_n].ty.span);
         }
     }
@@ -1817,7 +1817,7 @@ pub fn trans_fn(ccx: @crate_ctxt,
     trans_closure(ccx, copy path, decl, body, llfndecl, ty_self,
                   param_substs, id, impl_id,
                   |fcx| {
-                      if ccx.sess.opts.extra_debuginfo {
+                      if ccx.sess.opts.debuginfo {
                           debuginfo::create_function(fcx);
                       }
                   },
@@ -2986,7 +2986,7 @@ pub fn trans_crate(sess: session::Session,
             /*bad*/copy sess.targ_cfg.target_strs.data_layout);
         let tn = mk_type_names();
         let intrinsics = declare_intrinsics(llmod);
-        if sess.opts.extra_debuginfo {
+        if sess.opts.debuginfo {
             declare_dbg_intrinsics(llmod, intrinsics);
         }
         let int_type = T_int(targ_cfg);
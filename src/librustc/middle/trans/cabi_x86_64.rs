@@ -24,7 +24,6 @@ enum x86_64_reg_class {
     sse_fv_class,
     sse_ds_class,
     sse_dv_class,
-    sse_int_class,
     sseup_class,
     x87_class,
     x87up_class,
@@ -59,74 +58,74 @@ fn is_ymm(cls: &[x86_64_reg_class]) -> bool {
          cls[3] == sseup_class);
 }
 
-fn classify_ty(ty: TypeRef) -> ~[x86_64_reg_class] {
-    fn align(off: uint, ty: TypeRef) -> uint {
-        let a = ty_align(ty);
-        return (off + a - 1u) / a * a;
-    }
+fn align(off: uint, ty: TypeRef) -> uint {
+    let a = ty_align(ty);
+    return (off + a - 1u) / a * a;
+}
 
-    fn struct_tys(ty: TypeRef) -> ~[TypeRef] {
-        unsafe {
-            let n = llvm::LLVMCountStructElementTypes(ty);
+fn struct_tys(ty: TypeRef) -> ~[TypeRef] {
+    unsafe {
+        let n = llvm::LLVMCountStructElementTypes(ty);
         if (n == 0) {
             return ~[];
         }
-            let mut elts = vec::from_elem(n as uint, ptr::null());
-            llvm::LLVMGetStructElementTypes(ty,
-                ptr::to_mut_unsafe_ptr(&mut elts[0]));
-            return elts;
-        }
+        let mut elts = vec::from_elem(n as uint, ptr::null());
+        llvm::LLVMGetStructElementTypes(ty,
+            ptr::to_mut_unsafe_ptr(&mut elts[0]));
+        return elts;
     }
+}
 
-    fn ty_align(ty: TypeRef) -> uint {
-        unsafe {
-            return match llvm::LLVMGetTypeKind(ty) {
-                Integer => {
-                    ((llvm::LLVMGetIntTypeWidth(ty) as uint) + 7) / 8
-                }
-                Pointer => 8,
-                Float => 4,
-                Double => 8,
-                Struct => {
-                  do vec::foldl(1, struct_tys(ty)) |a, t| {
-                      uint::max(a, ty_align(*t))
-                  }
-                }
-                Array => {
-                    let elt = llvm::LLVMGetElementType(ty);
-                    ty_align(elt)
-                }
-                _ => fail!(~"ty_size: unhandled type")
-            };
-        }
+fn ty_align(ty: TypeRef) -> uint {
+    unsafe {
+        return match llvm::LLVMGetTypeKind(ty) {
+            Integer => {
+                ((llvm::LLVMGetIntTypeWidth(ty) as uint) + 7) / 8
+            }
+            Pointer => 8,
+            Float => 4,
+            Double => 8,
+            Struct => {
+              do vec::foldl(1, struct_tys(ty)) |a, t| {
+                  uint::max(a, ty_align(*t))
+              }
+            }
+            Array => {
+                let elt = llvm::LLVMGetElementType(ty);
+                ty_align(elt)
+            }
+            _ => fail!(~"ty_size: unhandled type")
+        };
     }
+}
 
-    fn ty_size(ty: TypeRef) -> uint {
-        unsafe {
-            return match llvm::LLVMGetTypeKind(ty) {
-                Integer => {
-                    ((llvm::LLVMGetIntTypeWidth(ty) as uint) + 7) / 8
-                }
-                Pointer => 8,
-                Float => 4,
-                Double => 8,
-                Struct => {
-                  let size = do vec::foldl(0, struct_tys(ty)) |s, t| {
-                      align(s, *t) + ty_size(*t)
-                  };
-                  align(size, ty)
-                }
-                Array => {
-                  let len = llvm::LLVMGetArrayLength(ty) as uint;
-                  let elt = llvm::LLVMGetElementType(ty);
-                  let eltsz = ty_size(elt);
-                  len * eltsz
-                }
-                _ => fail!(~"ty_size: unhandled type")
-            };
-        }
+fn ty_size(ty: TypeRef) -> uint {
+    unsafe {
+        return match llvm::LLVMGetTypeKind(ty) {
+            Integer => {
+                ((llvm::LLVMGetIntTypeWidth(ty) as uint) + 7) / 8
+            }
+            Pointer => 8,
+            Float => 4,
+            Double => 8,
+            Struct => {
+              let size = do vec::foldl(0, struct_tys(ty)) |s, t| {
+                  align(s, *t) + ty_size(*t)
+              };
+              align(size, ty)
+            }
+            Array => {
+              let len = llvm::LLVMGetArrayLength(ty) as uint;
+              let elt = llvm::LLVMGetElementType(ty);
+              let eltsz = ty_size(elt);
+              len * eltsz
+            }
+            _ => fail!(~"ty_size: unhandled type")
+        };
     }
+}
 
+fn classify_ty(ty: TypeRef) -> ~[x86_64_reg_class] {
     fn all_mem(cls: &mut [x86_64_reg_class]) {
         for uint::range(0, cls.len()) |i| {
             cls[i] = memory_class;
@@ -254,13 +253,14 @@ fn classify_ty(ty: TypeRef) -> ~[x86_64_reg_class] {
                         return;
                     }
                     if cls[i] == sseup_class {
-                        cls[i] = sse_int_class;
+                        // SSEUP that doesn't follow SSE is just SSE
+                        cls[i] = sse_ds_class;
                     } else if is_sse(cls[i]) {
                         i += 1;
-                        while cls[i] == sseup_class { i += 1u; }
+                        while i < e && cls[i] == sseup_class { i += 1u; }
                     } else if cls[i] == x87_class {
                         i += 1;
-                        while cls[i] == x87up_class { i += 1u; }
+                        while i < e && cls[i] == x87up_class { i += 1u; }
                     } else {
                         i += 1;
                     }
@@ -281,7 +281,9 @@ fn classify_ty(ty: TypeRef) -> ~[x86_64_reg_class] {
     return move cls;
 }
 
-fn llreg_ty(cls: &[x86_64_reg_class]) -> TypeRef {
+// The type that a value of type `ty` classified as `cls` is passed as, one
+// register per eightbyte
+fn llreg_ty(cls: &[x86_64_reg_class], ty: TypeRef) -> TypeRef {
     fn llvec_len(cls: &[x86_64_reg_class]) -> uint {
         let mut len = 1u;
         for vec::each(cls) |c| {
@@ -294,20 +296,24 @@ fn llreg_ty(cls: &[x86_64_reg_class]) -> TypeRef {
     }
 
     unsafe {
+        let size = ty_size(ty);
         let mut tys = ~[];
         let mut i = 0u;
         let e = vec::len(cls);
         while i < e {
             match cls[i] {
                 integer_class => {
-                    tys.push(T_i64());
+                    // The last eightbyte can be partly padding, which must
+                    // not be loaded or stored along with the value
+                    let bytes = uint::min(size - i * 8u, 8u);
+                    tys.push(llvm::LLVMIntType((bytes * 8u) as c_uint));
                 }
                 sse_fv_class => {
-                    let vec_len = llvec_len(vec::tailn(cls, i + 1u)) * 2u;
+                    let words = llvec_len(vec::tailn(cls, i + 1u));
                     let vec_ty = llvm::LLVMVectorType(T_f32(),
-                                                      vec_len as c_uint);
+                                                      (words * 2u) as c_uint);
                     tys.push(vec_ty);
-                    i += vec_len;
+                    i += words;
                     loop;
                 }
                 sse_fs_class => {
@@ -363,22 +369,63 @@ fn x86_64_tys(atys: &[TypeRef],
                 ty_attr = option::Some(attr);
             } else {
                 cast = true;
-                llty = llreg_ty(cls);
+                llty = llreg_ty(cls, ty);
             }
         }
         return (LLVMType { cast: cast, ty: llty }, ty_attr);
     }
 
+    // The integer and SSE registers that an argument takes up
+    fn regs_needed(ty: TypeRef) -> (uint, uint) {
+        unsafe {
+            if is_reg_ty(ty) {
+                return match llvm::LLVMGetTypeKind(ty) {
+                    Float | Double => (0u, 1u),
+                    _ => (1u, 0u)
+                };
+            }
+        }
+        let cls = classify_ty(ty);
+        if is_pass_byval(cls) { return (0u, 0u); }
+        let mut int_regs = 0u;
+        let mut sse_regs = 0u;
+        for cls.each |c| {
+            if *c == integer_class {
+                int_regs += 1u;
+            } else if is_sse(*c) {
+                sse_regs += 1u;
+            }
+        }
+        (int_regs, sse_regs)
+    }
+
+    let mut (ret_ty, ret_attr) = x86_64_ty(rty, is_ret_bysret,
+                                       StructRetAttribute);
+    let sret = ret_attr.is_some();
+
+    // An aggregate goes on the stack as a whole if the registers that are
+    // left can't hold all of it. The hidden sret pointer takes the first
+    // integer register.
+    let mut int_regs = if sret { 5u } else { 6u };
+    let mut sse_regs = 8u;
     let mut arg_tys = ~[];
     let mut attrs = ~[];
     for vec::each(atys) |t| {
-        let (ty, attr) = x86_64_ty(*t, is_pass_byval, ByValAttribute);
+        let (int_needed, sse_needed) = regs_needed(*t);
+        let fits = int_needed <= int_regs && sse_needed <= sse_regs;
+        let (ty, attr) = if fits || is_reg_ty(*t) {
+            x86_64_ty(*t, is_pass_byval, ByValAttribute)
+        } else {
+            (LLVMType { cast: false, ty: T_ptr(*t) },
+             option::Some(ByValAttribute))
+        };
+        if fits && attr.is_none() {
+            int_regs -= int_needed;
+            sse_regs -= sse_needed;
+        }
         arg_tys.push(ty);
         attrs.push(attr);
     }
-    let mut (ret_ty, ret_attr) = x86_64_ty(rty, is_ret_bysret,
-                                       StructRetAttribute);
-    let sret = ret_attr.is_some();
     if sret {
         arg_tys = vec::append(~[ret_ty], arg_tys);
         ret_ty = LLVMType {
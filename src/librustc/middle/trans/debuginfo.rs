@@ -11,7 +11,7 @@
 use core::prelude::*;
 
 use driver::session;
-use lib::llvm::ValueRef;
+use lib::llvm::{TypeRef, ValueRef, struct_element_types};
 use lib::llvm::llvm;
 use middle::pat_util::*;
 use middle::trans::base;
@@ -25,10 +25,8 @@ use util::ppaux::ty_to_str;
 
 use core::libc;
 use core::option;
-use core::sys;
 use std::oldmap::HashMap;
 use std::oldmap;
-use syntax::ast::Ty;
 use syntax::codemap::{span, CharPos};
 use syntax::parse::token::ident_interner;
 use syntax::{ast, codemap, ast_util, ast_map};
@@ -52,6 +50,14 @@ const StructureTypeTag: int = 19;
 const MemberTag: int = 13;
 const ArrayTypeTag: int = 1;
 const SubrangeTag: int = 33;
+const EnumerationTypeTag: int = 4;
+const EnumeratorTag: int = 40;
+const UnionTypeTag: int = 23;
+
+// The descriptors of all types are cached under this key, by type id
+const TypeCacheKey: int = 0;
+
+const FlagFwdDecl: int = 1 << 2;
 
 const DW_ATE_boolean: int = 0x02;
 const DW_ATE_float: int = 0x04;
@@ -107,13 +113,16 @@ fn add_named_metadata(cx: @crate_ctxt, name: ~str, val: ValueRef) {
 pub type debug_ctxt = {
     llmetadata: metadata_cache,
     names: namegen,
-    crate_file: ~str
+    crate_file: ~str,
+    // The ids of the types whose descriptors are being created
+    pending: HashMap<uint, ()>
 };
 
 pub fn mk_ctxt(+crate: ~str, intr: @ident_interner) -> debug_ctxt {
     {llmetadata: oldmap::HashMap(),
      names: new_namegen(intr),
-     crate_file: crate}
+     crate_file: crate,
+     pending: oldmap::HashMap()}
 }
 
 fn update_cache(cache: metadata_cache, mdtag: int, val: debug_metadata) {
@@ -129,10 +138,10 @@ type metadata<T> = {node: ValueRef, data: T};
 
 type file_md = {path: ~str};
 type compile_unit_md = {name: ~str};
-type subprogram_md = {id: ast::node_id};
+type subprogram_md = {llfn: ValueRef};
 type local_var_md = {id: ast::node_id};
 type tydesc_md = {hash: uint};
-type block_md = {start: codemap::Loc, end: codemap::Loc};
+type block_md = {start: codemap::Loc, end: codemap::Loc, llfn: ValueRef};
 type argument_md = {id: ast::node_id};
 type retval_md = {id: ast::node_id};
 
@@ -277,12 +286,18 @@ fn create_block(cx: block) -> @metadata<block_md> {
     let fname = /*bad*/copy start.file.name;
     let end = cx.sess().codemap.lookup_char_pos(sp.hi);
     let tg = LexicalBlockTag;
-    /*match cached_metadata::<@metadata<block_md>>(
+    // Each monomorphized instance of a fn has scopes of its own
+    let llfn = cx.fcx.llfn;
+    match cached_metadata::<@metadata<block_md>>(
         cache, tg,
-        {|md| start == md.data.start && end == md.data.end}) {
-      option::Some(md) { return md; }
-      option::None {}
-    }*/
+        |md| md.data.llfn == llfn &&
+             md.data.start.file.name == start.file.name &&
+             md.data.start.line == start.line &&
+             md.data.start.col == start.col &&
+             md.data.end.line == end.line && md.data.end.col == end.col) {
+      option::Some(md) => return md,
+      option::None => ()
+    }
 
     let parent = match cx.parent {
         None => create_function(cx.fcx).node,
@@ -301,28 +316,31 @@ fn create_block(cx: block) -> @metadata<block_md> {
                   lli32(unique_id)
                  ];
     let val = llmdnode(lldata);
-    let mdval = @{node: val, data: {start: start, end: end}};
-    //update_cache(cache, tg, block_metadata(mdval));
+    let mdval = @{node: val, data: {start: start, end: end, llfn: llfn}};
+    update_cache(cache, tg, block_metadata(mdval));
     return mdval;
 }
 
 fn size_and_align_of(cx: @crate_ctxt, t: ty::t) -> (int, int) {
-    let llty = type_of::type_of(cx, t);
+    size_and_align_of_llty(cx, type_of::type_of(cx, t))
+}
+
+fn size_and_align_of_llty(cx: @crate_ctxt, llty: TypeRef) -> (int, int) {
     (machine::llsize_of_real(cx, llty) as int,
-     machine::llalign_of_pref(cx, llty) as int)
+     machine::llalign_of_min(cx, llty) as int)
 }
 
-fn create_basic_type(cx: @crate_ctxt, t: ty::t, span: span)
-    -> @metadata<tydesc_md> {
-    let cache = get_cache(cx);
+fn create_basic_type(cx: @crate_ctxt, t: ty::t, span: span) -> ValueRef {
     let tg = BasicTypeDescriptorTag;
-    match cached_metadata::<@metadata<tydesc_md>>(
-        cache, tg, |md| ty::type_id(t) == md.data.hash) {
-      option::Some(md) => return md,
-      option::None => ()
-    }
-
-    let (name, encoding) = (~"uint", DW_ATE_unsigned);
+    let encoding = match ty::get(t).sty {
+      ty::ty_bool => DW_ATE_boolean,
+      ty::ty_int(ast::ty_char) => DW_ATE_unsigned,
+      ty::ty_int(_) => DW_ATE_signed,
+      ty::ty_uint(_) => DW_ATE_unsigned,
+      ty::ty_float(_) => DW_ATE_float,
+      // (), and the opaque pointers of the runtime
+      _ => DW_ATE_unsigned
+    };
 
     let fname = filename_from_span(cx, span);
     let file_node = create_file(cx, fname);
@@ -330,7 +348,7 @@ fn create_basic_type(cx: @crate_ctxt, t: ty::t, span: span)
     let (size, align) = size_and_align_of(cx, t);
     let lldata = ~[lltag(tg),
                   cu_node.node,
-                  llstr(name),
+                  llstr(ty_to_str(cx.tcx, t)),
                   file_node.node,
                   lli32(0), //XXX source line
                   lli64(size * 8),  // size in bits
@@ -339,32 +357,20 @@ fn create_basic_type(cx: @crate_ctxt, t: ty::t, span: span)
                   lli32(0), //XXX flags?
                   lli32(encoding)];
     let llnode = llmdnode(lldata);
-    let mdval = @{node: llnode, data: {hash: ty::type_id(t)}};
-    update_cache(cache, tg, tydesc_metadata(mdval));
     add_named_metadata(cx, ~"llvm.dbg.ty", llnode);
-    return mdval;
+    return llnode;
 }
 
-fn create_pointer_type(cx: @crate_ctxt, t: ty::t, span: span,
-                       pointee: @metadata<tydesc_md>)
-    -> @metadata<tydesc_md> {
+fn create_pointer_type(cx: @crate_ctxt, span: span, pointee: ValueRef)
+    -> ValueRef {
     let tg = PointerTypeTag;
-    /*let cache = cx.llmetadata;
-    match cached_metadata::<@metadata<tydesc_md>>(
-        cache, tg, {|md| ty::hash_ty(t) == ty::hash_ty(md.data.hash)}) {
-      option::Some(md) { return md; }
-      option::None {}
-    }*/
-    let (size, align) = size_and_align_of(cx, t);
+    let (size, align) = size_and_align_of_llty(cx, T_ptr(T_i8()));
     let fname = filename_from_span(cx, span);
     let file_node = create_file(cx, fname);
-    //let cu_node = create_compile_unit(cx, fname);
     let llnode = create_derived_type(tg, file_node.node, ~"", 0, size * 8,
-                                     align * 8, 0, pointee.node);
-    let mdval = @{node: llnode, data: {hash: ty::type_id(t)}};
-    //update_cache(cache, tg, tydesc_metadata(mdval));
+                                     align * 8, 0, pointee);
     add_named_metadata(cx, ~"llvm.dbg.ty", llnode);
-    return mdval;
+    return llnode;
 }
 
 struct StructCtxt {
@@ -384,19 +390,21 @@ fn finish_structure(cx: @mut StructCtxt) -> ValueRef {
                                  cx.total_size,
                                  cx.align,
                                  0,
+                                 0,
                                  option::None,
                                  option::Some(/*bad*/copy cx.members));
 }
 
-fn create_structure(file: @metadata<file_md>, +name: ~str, line: int)
-                 -> @mut StructCtxt {
+// `size` and `align` are in bytes
+fn create_structure(file: @metadata<file_md>, +name: ~str, line: int,
+                    size: int, align: int) -> @mut StructCtxt {
     let cx = @mut StructCtxt {
         file: file.node,
         name: name,
         line: line,
         members: ~[],
-        total_size: 0,
-        align: 64 //XXX different alignment per arch?
+        total_size: size * 8,
+        align: align * 8
     };
     return cx;
 }
@@ -417,71 +425,75 @@ fn create_derived_type(type_tag: int, file: ValueRef, +name: ~str, line: int,
     return llmdnode(lldata);
 }
 
+// `size`, `align` and `offset` are in bytes
 fn add_member(cx: @mut StructCtxt,
               +name: ~str,
               line: int,
               size: int,
               align: int,
+              offset: int,
               ty: ValueRef) {
     cx.members.push(create_derived_type(MemberTag, cx.file, name, line,
-                                       size * 8, align * 8, cx.total_size,
+                                       size * 8, align * 8, offset * 8,
                                        ty));
-    cx.total_size += size * 8;
 }
 
-fn create_record(cx: @crate_ctxt, t: ty::t, fields: ~[ast::ty_field],
-                 span: span) -> @metadata<tydesc_md> {
+// Describes a value of the LLVM type `outer`, whose fields are the elements
+// of the struct type `inner` at its start, named and described by `fields`
+fn create_struct(cx: @crate_ctxt, +name: ~str, outer: TypeRef,
+                 inner: TypeRef, fields: &[(~str, ValueRef)], span: span)
+    -> ValueRef {
     let fname = filename_from_span(cx, span);
     let file_node = create_file(cx, fname);
-    let scx = create_structure(file_node,
-                               cx.sess.str_of(
-                                   ((/*bad*/copy cx.dbg_cx).get().names)
-                                   (~"rec")),
-                               line_from_span(cx.sess.codemap,
-                                              span) as int);
-    for fields.each |field| {
-        let field_t = ty::get_field(cx.tcx, t, field.node.ident).mt.ty;
-        let ty_md = create_ty(cx, field_t, field.node.mt.ty);
-        let (size, align) = size_and_align_of(cx, field_t);
-        add_member(scx, cx.sess.str_of(field.node.ident),
-                   line_from_span(cx.sess.codemap, field.span) as int,
-                   size as int, align as int, ty_md.node);
+    let (size, align) = size_and_align_of_llty(cx, outer);
+    let scx = create_structure(file_node, name,
+                               line_from_span(cx.sess.codemap, span) as int,
+                               size, align);
+    let lltys = struct_element_types(inner);
+    for fields.eachi |i, field| {
+        let (field_name, ty_md) = copy *field;
+        let (field_size, field_align) = size_and_align_of_llty(cx, lltys[i]);
+        add_member(scx, field_name, 0, field_size, field_align,
+                   machine::llelement_offset(cx, inner, i) as int, ty_md);
     }
-    let mdval = @{node: finish_structure(scx), data:{hash: ty::type_id(t)}};
-    return mdval;
+    return finish_structure(scx);
 }
 
-fn create_boxed_type(cx: @crate_ctxt, outer: ty::t, _inner: ty::t,
-                     span: span, boxed: @metadata<tydesc_md>)
-    -> @metadata<tydesc_md> {
-    //let tg = StructureTypeTag;
-    /*let cache = cx.llmetadata;
-    match cached_metadata::<@metadata<tydesc_md>>(
-        cache, tg, {|md| ty::hash_ty(outer) == ty::hash_ty(md.data.hash)}) {
-      option::Some(md) { return md; }
-      option::None {}
-    }*/
-    let fname = filename_from_span(cx, span);
-    let file_node = create_file(cx, fname);
-    //let cu_node = create_compile_unit_metadata(cx, fname);
-    let uint_t = ty::mk_uint(cx.tcx);
-    let refcount_type = create_basic_type(cx, uint_t, span);
-    let scx = create_structure(file_node, ty_to_str(cx.tcx, outer), 0);
-    add_member(scx, ~"refcnt", 0, sys::size_of::<uint>() as int,
-               sys::min_align_of::<uint>() as int, refcount_type.node);
-    add_member(scx, ~"boxed", 0, 8, //XXX member_size_and_align(??)
-               8, //XXX just a guess
-               boxed.node);
-    let llnode = finish_structure(scx);
-    let mdval = @{node: llnode, data: {hash: ty::type_id(outer)}};
-    //update_cache(cache, tg, tydesc_metadata(mdval));
-    add_named_metadata(cx, ~"llvm.dbg.ty", llnode);
-    return mdval;
+// Like create_struct, for fields given by their types
+fn create_fields(cx: @crate_ctxt, +name: ~str, outer: TypeRef,
+                 inner: TypeRef, fields: &[(~str, ty::t)], span: span)
+    -> ValueRef {
+    let members = do fields.map |field| {
+        let (field_name, field_t) = copy *field;
+        (field_name, create_ty(cx, field_t, span).node)
+    };
+    return create_struct(cx, name, outer, inner, members, span);
+}
+
+// The elements of a tuple are named __0, __1 and so on, as in the pretty
+// printed names of the values that patterns bind them to
+fn tuple_fields(elts: &[ty::t]) -> ~[(~str, ty::t)] {
+    do vec::mapi(elts) |i, elt| { (fmt!("__%u", i), *elt) }
+}
+
+// Describes the box that @T and ~T point to: the header the runtime uses
+// to manage it, followed by the contents
+fn create_boxed_type(cx: @crate_ctxt, +name: ~str, llcontents: TypeRef,
+                     contents: ValueRef, span: span) -> ValueRef {
+    let uint_md = create_ty(cx, ty::mk_uint(cx.tcx), span).node;
+    let ptr_md = create_ty(cx, ty::mk_nil_ptr(cx.tcx), span).node;
+    let llbox = T_box(cx, llcontents);
+    return create_struct(cx, ~"box<" + name + ~">", llbox, llbox,
+                         ~[(~"refcnt", uint_md),
+                           (~"tydesc", ptr_md),
+                           (~"prev", ptr_md),
+                           (~"next", ptr_md),
+                           (~"boxed", contents)], span);
 }
 
 fn create_composite_type(type_tag: int, +name: ~str, file: ValueRef,
                          line: int, size: int, align: int, offset: int,
-                         derived: Option<ValueRef>,
+                         flags: int, derived: Option<ValueRef>,
                          +members: Option<~[ValueRef]>)
     -> ValueRef {
     let lldata = ~[lltag(type_tag),
@@ -492,7 +504,7 @@ fn create_composite_type(type_tag: int, +name: ~str, file: ValueRef,
                   lli64(size), // size of members
                   lli64(align), // align
                   lli32/*64*/(offset), // offset
-                  lli32(0), // flags
+                  lli32(flags), // flags
                   if derived.is_none() {
                       llnull()
                   } else { // derived from
@@ -509,132 +521,271 @@ fn create_composite_type(type_tag: int, +name: ~str, file: ValueRef,
     return llmdnode(lldata);
 }
 
-fn create_vec(cx: @crate_ctxt, vec_t: ty::t, elem_t: ty::t,
-              vec_ty_span: codemap::span, elem_ty: @ast::Ty)
-    -> @metadata<tydesc_md> {
-    let fname = filename_from_span(cx, vec_ty_span);
+// Describes the vector that ~[T], @[T], ~str and @str box: how many bytes
+// of it are in use and allocated, followed by the elements
+fn create_vec(cx: @crate_ctxt, elem_t: ty::t, span: span) -> ValueRef {
+    let fname = filename_from_span(cx, span);
     let file_node = create_file(cx, fname);
-    let elem_ty_md = create_ty(cx, elem_t, elem_ty);
-    let scx = create_structure(file_node, ty_to_str(cx.tcx, vec_t), 0);
-    let size_t_type = create_basic_type(cx, ty::mk_uint(cx.tcx), vec_ty_span);
-    add_member(scx, ~"fill", 0, sys::size_of::<libc::size_t>() as int,
-               sys::min_align_of::<libc::size_t>() as int, size_t_type.node);
-    add_member(scx, ~"alloc", 0, sys::size_of::<libc::size_t>() as int,
-               sys::min_align_of::<libc::size_t>() as int, size_t_type.node);
+    let elem_ty_md = create_ty(cx, elem_t, span).node;
+    let size_t_type = create_ty(cx, ty::mk_uint(cx.tcx), span).node;
     let subrange = llmdnode(~[lltag(SubrangeTag), lli64(0), lli64(0)]);
-    let (arr_size, arr_align) = size_and_align_of(cx, elem_t);
-    let data_ptr = create_composite_type(ArrayTypeTag, ~"", file_node.node, 0,
-                                         arr_size, arr_align, 0,
-                                         option::Some(elem_ty_md.node),
-                                         option::Some(~[subrange]));
-    add_member(scx, ~"data", 0, 0, // clang says the size should be 0
-               sys::min_align_of::<u8>() as int, data_ptr);
-    let llnode = finish_structure(scx);
-    return @{node: llnode, data: {hash: ty::type_id(vec_t)}};
-}
-
-fn create_ty(_cx: @crate_ctxt, _t: ty::t, _ty: @ast::Ty)
+    let (_, elem_align) = size_and_align_of(cx, elem_t);
+    let data = create_composite_type(ArrayTypeTag, ~"", file_node.node, 0,
+                                     0, elem_align * 8, 0, 0,
+                                     option::Some(elem_ty_md),
+                                     option::Some(~[subrange]));
+    let llvec = T_vec(cx, type_of::type_of(cx, elem_t));
+    return create_struct(cx, fmt!("vec<%s>", ty_to_str(cx.tcx, elem_t)),
+                         llvec, llvec,
+                         ~[(~"fill", size_t_type),
+                           (~"alloc", size_t_type),
+                           // clang says the size of the data should be 0
+                           (~"data", data)], span);
+}
+
+fn create_boxed_vec(cx: @crate_ctxt, elem_t: ty::t, span: span)
+    -> ValueRef {
+    let vec = create_vec(cx, elem_t, span);
+    let llvec = T_vec(cx, type_of::type_of(cx, elem_t));
+    let boxed = create_boxed_type(cx, fmt!("vec<%s>",
+                                           ty_to_str(cx.tcx, elem_t)),
+                                  llvec, vec, span);
+    return create_pointer_type(cx, span, boxed);
+}
+
+fn create_fixed_vec(cx: @crate_ctxt, vec_t: ty::t, elem_t: ty::t,
+                    len: uint, span: span) -> ValueRef {
+    let fname = filename_from_span(cx, span);
+    let file_node = create_file(cx, fname);
+    let elem_ty_md = create_ty(cx, elem_t, span).node;
+    let (size, align) = size_and_align_of(cx, vec_t);
+    let subrange = llmdnode(~[lltag(SubrangeTag), lli64(0),
+                              lli64(len as int - 1)]);
+    return create_composite_type(ArrayTypeTag, ~"", file_node.node, 0,
+                                 size * 8, align * 8, 0, 0,
+                                 option::Some(elem_ty_md),
+                                 option::Some(~[subrange]));
+}
+
+// A slice is a pointer to its first element and its length in bytes
+fn create_slice(cx: @crate_ctxt, vec_t: ty::t, elem_t: ty::t, span: span)
+    -> ValueRef {
+    let elem_ty_md = create_ty(cx, elem_t, span).node;
+    let data_ptr = create_pointer_type(cx, span, elem_ty_md);
+    let uint_md = create_ty(cx, ty::mk_uint(cx.tcx), span).node;
+    let llty = type_of::type_of(cx, vec_t);
+    return create_struct(cx, ty_to_str(cx.tcx, vec_t), llty, llty,
+                         ~[(~"data", data_ptr), (~"len", uint_md)], span);
+}
+
+// An enum with no fields in any variant is described as a C enum. Any
+// other is a struct of the discriminant, unless there is only one variant,
+// and a union with one member per variant, each a struct of the variant's
+// fields.
+fn create_enum(cx: @crate_ctxt, enum_t: ty::t, did: ast::def_id,
+               substs: &ty::substs, span: span) -> ValueRef {
+    let name = ty_to_str(cx.tcx, enum_t);
+    let fname = filename_from_span(cx, span);
+    let file_node = create_file(cx, fname);
+    let line = line_from_span(cx.sess.codemap, span) as int;
+    let variants = ty::enum_variants(cx.tcx, did);
+    let univariant = ty::enum_is_univariant(cx.tcx, did);
+
+    let enumerators = do variants.map |v| {
+        llmdnode(~[lltag(EnumeratorTag),
+                   llstr(cx.sess.str_of(v.name)),
+                   lli64(v.disr_val)])
+    };
+    let (discrim_size, discrim_align) =
+        size_and_align_of_llty(cx, T_enum_discrim(cx));
+    let discrim = create_composite_type(EnumerationTypeTag, copy name,
+                                        file_node.node, line,
+                                        discrim_size * 8, discrim_align * 8,
+                                        0, 0, option::None,
+                                        option::Some(enumerators));
+    if !univariant && vec::all(*variants, |v| v.args.is_empty()) {
+        return discrim;
+    }
+
+    let members = do variants.map |v| {
+        let args = do v.args.map |arg| { ty::subst(cx.tcx, substs, *arg) };
+        let tup = ty::mk_tup(cx.tcx, copy args);
+        let lltup = type_of::type_of(cx, tup);
+        let variant_name = cx.sess.str_of(v.name);
+        let md = create_fields(cx, copy variant_name, lltup, lltup,
+                               tuple_fields(args), span);
+        let (size, align) = size_and_align_of_llty(cx, lltup);
+        create_derived_type(MemberTag, file_node.node, variant_name, line,
+                            size * 8, align * 8, 0, md)
+    };
+    let llty = type_of::type_of(cx, enum_t);
+    let payload_ix = if univariant { 0u } else { 1u };
+    let (payload_size, payload_align) =
+        size_and_align_of_llty(cx, struct_element_types(llty)[payload_ix]);
+    let payload = create_composite_type(UnionTypeTag, ~"", file_node.node,
+                                        line, payload_size * 8,
+                                        payload_align * 8, 0, 0,
+                                        option::None,
+                                        option::Some(members));
+    let fields = if univariant {
+        ~[(~"variants", payload)]
+    } else {
+        ~[(~"discriminant", discrim), (~"variants", payload)]
+    };
+    return create_struct(cx, name, llty, llty, fields, span);
+}
+
+// Describes the type of the code of a fn or closure
+fn create_subroutine_type(cx: @crate_ctxt, fn_t: ty::t, span: span)
+    -> ValueRef {
+    let fname = filename_from_span(cx, span);
+    let file_node = create_file(cx, fname);
+    let ret_t = ty::ty_fn_ret(fn_t);
+    let mut elems = ~[if ty::type_is_nil(ret_t) {
+        llnull()
+    } else {
+        create_ty(cx, ret_t, span).node
+    }];
+    for ty::ty_fn_args(fn_t).each |arg| {
+        elems.push(create_ty(cx, arg.ty, span).node);
+    }
+    return create_composite_type(SubroutineTag, ~"", file_node.node, 0, 0,
+                                 0, 0, 0, option::None,
+                                 option::Some(elems));
+}
+
+// Fns and closures are a pointer to their code and one to their
+// environment, which is a box
+fn create_fn_pair(cx: @crate_ctxt, fn_t: ty::t, span: span) -> ValueRef {
+    let code = create_pointer_type(cx, span,
+                                   create_subroutine_type(cx, fn_t, span));
+    let env = create_ty(cx, ty::mk_nil_ptr(cx.tcx), span).node;
+    let llty = type_of::type_of(cx, fn_t);
+    return create_struct(cx, ty_to_str(cx.tcx, fn_t), llty, llty,
+                         ~[(~"code", code), (~"env", env)], span);
+}
+
+// A trait object is its vtable and the value it was made from, which is
+// boxed unless it is borrowed. Owned ones also keep the value's tydesc.
+fn create_trait_object(cx: @crate_ctxt, trait_t: ty::t,
+                       vstore: ty::vstore, span: span) -> ValueRef {
+    let ptr_md = create_ty(cx, ty::mk_nil_ptr(cx.tcx), span).node;
+    let fields = match vstore {
+      ty::vstore_uniq => {
+        ~[(~"vtable", ptr_md), (~"box", ptr_md), (~"tydesc", ptr_md)]
+      }
+      ty::vstore_box => ~[(~"vtable", ptr_md), (~"box", ptr_md)],
+      _ => ~[(~"vtable", ptr_md), (~"self", ptr_md)]
+    };
+    let llty = type_of::type_of(cx, trait_t);
+    return create_struct(cx, ty_to_str(cx.tcx, trait_t), llty, llty, fields,
+                         span);
+}
+
+// Refers by name to a struct or enum whose descriptor is still being
+// created, which is how one that contains itself through a pointer is
+// described
+fn create_forward_decl(cx: @crate_ctxt, t: ty::t, span: span) -> ValueRef {
+    let fname = filename_from_span(cx, span);
+    let file_node = create_file(cx, fname);
+    return create_composite_type(StructureTypeTag, ty_to_str(cx.tcx, t),
+                                 file_node.node, 0, 0, 0, 0, FlagFwdDecl,
+                                 option::None, option::None);
+}
+
+fn create_ty(cx: @crate_ctxt, t: ty::t, span: span)
     -> @metadata<tydesc_md> {
-    /*let cache = get_cache(cx);
+    let cache = get_cache(cx);
+    let tg = TypeCacheKey;
     match cached_metadata::<@metadata<tydesc_md>>(
-        cache, tg, {|md| t == md.data.hash}) {
-      option::Some(md) { return md; }
-      option::None {}
-    }*/
-
-    /* FIXME (#2012): disabled this code as part of the patch that moves
-     * recognition of named builtin types into resolve. I tried to fix
-     * it, but it seems to already be broken -- it's only called when
-     * --xg is given, and compiling with --xg fails on trivial programs.
-     *
-     * Generating an ast::ty from a ty::t seems like it should not be
-     * needed. It is only done to track spans, but you will not get the
-     * right spans anyway -- types tend to refer to stuff defined
-     * elsewhere, not be self-contained.
-     */
-
-    fail!();
-    /*
-    fn t_to_ty(cx: crate_ctxt, t: ty::t, span: span) -> @ast::ty {
-        let ty = match ty::get(t).struct {
-          ty::ty_nil { ast::ty_nil }
-          ty::ty_bot { ast::ty_bot }
-          ty::ty_bool { ast::ty_bool }
-          ty::ty_int(t) { ast::ty_int(t) }
-          ty::ty_float(t) { ast::ty_float(t) }
-          ty::ty_uint(t) { ast::ty_uint(t) }
-          ty::ty_box(mt) { ast::ty_box({ty: t_to_ty(cx, mt.ty, span),
-                                        mutbl: mt.mutbl}) }
-          ty::ty_uniq(mt) { ast::ty_uniq({ty: t_to_ty(cx, mt.ty, span),
-                                          mutbl: mt.mutbl}) }
-          ty::ty_rec(fields) {
-            let fs = ~[];
-            for field in fields {
-                fs.push({node: {ident: field.ident,
-                               mt: {ty: t_to_ty(cx, field.mt.ty, span),
-                                    mutbl: field.mt.mutbl}},
-                        span: span});
-            }
-            ast::ty_rec(fs)
-          }
-          ty::ty_vec(mt) { ast::ty_vec({ty: t_to_ty(cx, mt.ty, span),
-                                        mutbl: mt.mutbl}) }
-          _ {
-            cx.sess.span_bug(span, "t_to_ty: Can't handle this type");
-          }
-        };
-        return @{node: ty, span: span};
+        cache, tg, |md| ty::type_id(t) == md.data.hash) {
+      option::Some(md) => return md,
+      option::None => ()
     }
 
-    match ty.node {
-      ast::ty_box(mt) {
-        let inner_t = match ty::get(t).struct {
-          ty::ty_box(boxed) { boxed.ty }
-          _ { cx.sess.span_bug(ty.span, "t_to_ty was incoherent"); }
+    let pending = (/*bad*/copy cx.dbg_cx).get().pending;
+    if pending.contains_key(&ty::type_id(t)) {
+        return @{node: create_forward_decl(cx, t, span),
+                 data: {hash: ty::type_id(t)}};
+    }
+    pending.insert(ty::type_id(t), ());
+
+    let llnode = match ty::get(t).sty {
+      ty::ty_nil | ty::ty_bot | ty::ty_bool | ty::ty_int(_) |
+      ty::ty_uint(_) | ty::ty_float(_) | ty::ty_type | ty::ty_opaque_box |
+      ty::ty_opaque_closure_ptr(_) => {
+        create_basic_type(cx, t, span)
+      }
+      ty::ty_estr(ty::vstore_uniq) | ty::ty_estr(ty::vstore_box) => {
+        create_boxed_vec(cx, ty::mk_u8(cx.tcx), span)
+      }
+      ty::ty_evec(mt, ty::vstore_uniq) | ty::ty_evec(mt, ty::vstore_box) => {
+        create_boxed_vec(cx, mt.ty, span)
+      }
+      ty::ty_estr(ty::vstore_slice(_)) => {
+        create_slice(cx, t, ty::mk_u8(cx.tcx), span)
+      }
+      ty::ty_evec(mt, ty::vstore_slice(_)) => {
+        create_slice(cx, t, mt.ty, span)
+      }
+      ty::ty_estr(ty::vstore_fixed(n)) => {
+        // +1 for the trailing null
+        create_fixed_vec(cx, t, ty::mk_u8(cx.tcx), n + 1u, span)
+      }
+      ty::ty_evec(mt, ty::vstore_fixed(n)) => {
+        create_fixed_vec(cx, t, mt.ty, n, span)
+      }
+      ty::ty_unboxed_vec(mt) => create_vec(cx, mt.ty, span),
+      ty::ty_box(mt) | ty::ty_uniq(mt) => {
+        let contents = create_ty(cx, mt.ty, span).node;
+        let boxed = create_boxed_type(cx, ty_to_str(cx.tcx, mt.ty),
+                                      type_of::type_of(cx, mt.ty),
+                                      contents, span);
+        create_pointer_type(cx, span, boxed)
+      }
+      ty::ty_ptr(mt) | ty::ty_rptr(_, mt) => {
+        create_pointer_type(cx, span, create_ty(cx, mt.ty, span).node)
+      }
+      ty::ty_rec(ref fields) => {
+        // Records, like structs, keep their fields in an inner struct
+        let llty = type_of::type_of(cx, t);
+        let fields = do fields.map |f| {
+            (cx.sess.str_of(f.ident), f.mt.ty)
         };
-        let md = create_ty(cx, inner_t, mt.ty);
-        let box = create_boxed_type(cx, t, inner_t, ty.span, md);
-        return create_pointer_type(cx, t, ty.span, box);
+        create_fields(cx, ty_to_str(cx.tcx, t), llty,
+                      struct_element_types(llty)[0], fields, span)
       }
-
-      ast::ty_uniq(mt) {
-        let inner_t = match ty::get(t).struct {
-          ty::ty_uniq(boxed) { boxed.ty }
-          // Hoping we'll have a way to eliminate this check soon.
-          _ { cx.sess.span_bug(ty.span, "t_to_ty was incoherent"); }
+      ty::ty_struct(did, ref substs) => {
+        let llty = type_of::type_of(cx, t);
+        let fields = do ty::lookup_struct_fields(cx.tcx, did).map |f| {
+            (cx.sess.str_of(f.ident),
+             ty::lookup_field_type(cx.tcx, did, f.id, substs))
         };
-        let md = create_ty(cx, inner_t, mt.ty);
-        return create_pointer_type(cx, t, ty.span, md);
+        create_fields(cx, ty_to_str(cx.tcx, t), llty,
+                      struct_element_types(llty)[0], fields, span)
       }
-
-      ast::ty_infer {
-        let inferred = t_to_ty(cx, t, ty.span);
-        return create_ty(cx, t, inferred);
+      ty::ty_tup(ref elts) => {
+        let llty = type_of::type_of(cx, t);
+        create_fields(cx, ty_to_str(cx.tcx, t), llty, llty,
+                      tuple_fields(*elts), span)
       }
-
-      ast::ty_rec(fields) {
-        return create_record(cx, t, fields, ty.span);
+      ty::ty_enum(did, ref substs) => {
+        create_enum(cx, t, did, substs, span)
       }
-
-      ast::ty_vec(mt) {
-        let inner_t = ty::sequence_element_type(cx.tcx, t);
-        let inner_ast_t = t_to_ty(cx, inner_t, mt.ty.span);
-        let v = create_vec(cx, t, inner_t, ty.span, inner_ast_t);
-        return create_pointer_type(cx, t, ty.span, v);
+      ty::ty_bare_fn(_) | ty::ty_closure(_) => create_fn_pair(cx, t, span),
+      ty::ty_trait(_, _, vstore) => {
+        create_trait_object(cx, t, vstore, span)
       }
-
-      ast::ty_path(_, id) {
-        match cx.tcx.def_map.get(id) {
-          ast::def_prim_ty(pty) {
-            return create_basic_type(cx, t, pty, ty.span);
-          }
-          _ {}
-        }
+      ty::ty_param(*) | ty::ty_self | ty::ty_infer(*) | ty::ty_err => {
+        cx.sess.span_bug(span, fmt!("debug info for unsubstituted type \
+                                     %s", ty_to_str(cx.tcx, t)))
       }
-
-      _ {}
     };
-    */
+
+    pending.remove(&ty::type_id(t));
+    let mdval = @{node: llnode, data: {hash: ty::type_id(t)}};
+    update_cache(cache, tg, tydesc_metadata(mdval));
+    return mdval;
 }
 
 fn filename_from_span(cx: @crate_ctxt, sp: codemap::span) -> ~str {
@@ -654,101 +805,73 @@ fn create_var(type_tag: int, context: ValueRef, +name: ~str, file: ValueRef,
     return llmdnode(lldata);
 }
 
-pub fn create_local_var(bcx: block, local: @ast::local)
-    -> @metadata<local_var_md> {
-    unsafe {
-        let cx = bcx.ccx();
-        let cache = get_cache(cx);
-        let tg = AutoVariableTag;
-        match cached_metadata::<@metadata<local_var_md>>(
-            cache, tg, |md| md.data.id == local.node.id) {
-          option::Some(md) => return md,
-          option::None => ()
-        }
+fn declare_var(bcx: block, llptr: ValueRef, mdnode: ValueRef) {
+    let declargs = ~[llmdnode(~[llptr]), mdnode];
+    trans::build::Call(bcx, bcx.ccx().intrinsics.get(&~"llvm.dbg.declare"),
+                       declargs);
+}
 
-        let name = match local.node.pat.node {
-          ast::pat_ident(_, pth, _) => ast_util::path_to_ident(pth),
-          // FIXME this should be handled (#2533)
-          _ => fail!(~"no single variable name for local")
-        };
-        let loc = cx.sess.codemap.lookup_char_pos(local.span.lo);
-        let ty = node_id_type(bcx, local.node.id);
-        let tymd = create_ty(cx, ty, local.node.ty);
+/// Describes the variables that `local` binds to the debugger
+pub fn create_local_var(bcx: block, local: @ast::local) {
+    let cx = bcx.ccx();
+    let tg = AutoVariableTag;
+    let context = match bcx.parent {
+        None => create_function(bcx.fcx).node,
+        Some(_) => create_block(bcx).node
+    };
+    do pat_bindings(cx.tcx.def_map, local.node.pat) |_, id, sp, path| {
+        let loc = cx.sess.codemap.lookup_char_pos(sp.lo);
+        let ty = node_id_type(bcx, id);
+        let tymd = create_ty(cx, ty, sp);
         let filemd = create_file(cx, /*bad*/copy loc.file.name);
-        let context = match bcx.parent {
-            None => create_function(bcx.fcx).node,
-            Some(_) => create_block(bcx).node
-        };
-        let mdnode = create_var(tg, context, cx.sess.str_of(name),
+        let mdnode = create_var(tg, context,
+                                cx.sess.str_of(ast_util::path_to_ident(path)),
                                 filemd.node, loc.line as int, tymd.node);
-        let mdval = @{node: mdnode, data: {id: local.node.id}};
-        update_cache(cache, AutoVariableTag, local_var_metadata(mdval));
 
-        let llptr = match bcx.fcx.lllocals.find(&local.node.id) {
+        // A local bound by a single identifier lives where the local does
+        let llptr = match bcx.fcx.lllocals.find(&id) {
           option::Some(local_mem(v)) => v,
           option::Some(_) => {
-            bcx.tcx().sess.span_bug(local.span, ~"local is bound to \
+            bcx.tcx().sess.span_bug(sp, ~"local is bound to \
                     something weird");
           }
           option::None => {
-            match bcx.fcx.lllocals.get(&local.node.pat.id) {
-              local_imm(v) => v,
-              _ => bcx.tcx().sess.span_bug(local.span, ~"local is bound to \
-                                                         something weird")
+            match bcx.fcx.lllocals.get(&local.node.id) {
+              local_mem(v) | local_imm(v) => v
             }
           }
         };
-        let declargs = ~[llmdnode(~[llptr]), mdnode];
-        trans::build::Call(bcx, cx.intrinsics.get(&~"llvm.dbg.declare"),
-                           declargs);
-        return mdval;
+        declare_var(bcx, llptr, mdnode);
     }
 }
 
-pub fn create_arg(bcx: block, arg: ast::arg, sp: span)
-    -> Option<@metadata<argument_md>> {
-    unsafe {
-        let fcx = bcx.fcx, cx = fcx.ccx;
-        let cache = get_cache(cx);
-        let tg = ArgVariableTag;
-        match cached_metadata::<@metadata<argument_md>>(
-            cache, ArgVariableTag, |md| md.data.id == arg.id) {
-          option::Some(md) => return Some(md),
-          option::None => ()
-        }
-
-        let loc = cx.sess.codemap.lookup_char_pos(sp.lo);
-        let ty = node_id_type(bcx, arg.id);
-        let tymd = create_ty(cx, ty, arg.ty);
-        let filemd = create_file(cx, /*bad*/copy loc.file.name);
-        let context = create_function(bcx.fcx);
-
-        match arg.pat.node {
-            ast::pat_ident(_, path, _) => {
-                // XXX: This is wrong; it should work for multiple bindings.
-                let mdnode = create_var(tg,
-                                        context.node,
-                                        cx.sess.str_of(path.idents.last()),
-                                        filemd.node,
-                                        loc.line as int,
-                                        tymd.node);
-
-                let mdval = @{node: mdnode, data: {id: arg.id}};
-                update_cache(cache, tg, argument_metadata(mdval));
-
-                let llptr = match fcx.llargs.get(&arg.id) {
-                  local_mem(v) | local_imm(v) => v,
-                };
-                let declargs = ~[llmdnode(~[llptr]), mdnode];
-                trans::build::Call(bcx,
-                                   cx.intrinsics.get(&~"llvm.dbg.declare"),
-                                   declargs);
-                return Some(mdval);
-            }
-            _ => {
-                return None;
+/// Describes the variables that the argument `arg` binds to the debugger
+pub fn create_arg(bcx: block, arg: ast::arg, sp: span) {
+    let fcx = bcx.fcx, cx = fcx.ccx;
+    let tg = ArgVariableTag;
+    let loc = cx.sess.codemap.lookup_char_pos(sp.lo);
+    let filemd = create_file(cx, /*bad*/copy loc.file.name);
+    let context = create_function(bcx.fcx);
+
+    do pat_bindings(cx.tcx.def_map, arg.pat) |_, id, _, path| {
+        let ty = node_id_type(bcx, id);
+        let tymd = create_ty(cx, ty, sp);
+        let mdnode = create_var(tg,
+                                context.node,
+                                cx.sess.str_of(path.idents.last()),
+                                filemd.node,
+                                loc.line as int,
+                                tymd.node);
+
+        let llptr = match fcx.llargs.find(&id) {
+          option::Some(local_mem(v)) | option::Some(local_imm(v)) => v,
+          option::None => {
+            match fcx.llargs.get(&arg.id) {
+              local_mem(v) | local_imm(v) => v
             }
-        }
+          }
+        };
+        declare_var(bcx, llptr, mdnode);
     }
 }
 
@@ -779,26 +902,23 @@ pub fn create_function(fcx: fn_ctxt) -> @metadata<subprogram_md> {
     let sp = fcx.span.get();
     log(debug, cx.sess.codemap.span_to_str(sp));
 
-    let (ident, ret_ty, id) = match cx.tcx.items.get(&fcx.id) {
+    let (ident, id) = match cx.tcx.items.get(&fcx.id) {
       ast_map::node_item(item, _) => {
         match /*bad*/copy item.node {
-          ast::item_fn(decl, _, _, _) => {
-            (item.ident, decl.output, item.id)
+          ast::item_fn(*) => {
+            (item.ident, item.id)
           }
           _ => fcx.ccx.sess.span_bug(item.span, ~"create_function: item \
                                                   bound to non-function")
         }
       }
       ast_map::node_method(method, _, _) => {
-          (method.ident, method.decl.output, method.id)
+          (method.ident, method.id)
       }
       ast_map::node_expr(expr) => {
         match /*bad*/copy expr.node {
-          ast::expr_fn(_, decl, _, _) => {
-            ((dbg_cx.names)(~"fn"), decl.output, expr.id)
-          }
-          ast::expr_fn_block(decl, _) => {
-            ((dbg_cx.names)(~"fn"), decl.output, expr.id)
+          ast::expr_fn(*) | ast::expr_fn_block(*) => {
+            ((dbg_cx.names)(~"fn"), expr.id)
           }
           _ => fcx.ccx.sess.span_bug(expr.span,
                                      ~"create_function: \
@@ -806,7 +926,7 @@ pub fn create_function(fcx: fn_ctxt) -> @metadata<subprogram_md> {
         }
       }
       ast_map::node_dtor(_, _, did, _) => {
-        ((dbg_cx.names)(~"dtor"), ast_util::dtor_ty(), did.node)
+        ((dbg_cx.names)(~"dtor"), did.node)
       }
       _ => fcx.ccx.sess.bug(~"create_function: unexpected \
                               sort of node")
@@ -815,33 +935,47 @@ pub fn create_function(fcx: fn_ctxt) -> @metadata<subprogram_md> {
     log(debug, ident);
     log(debug, id);
 
+    // Each monomorphized instance of a fn is a subprogram of its own
     let cache = get_cache(cx);
     match cached_metadata::<@metadata<subprogram_md>>(
-        cache, SubprogramTag, |md| md.data.id == id) {
+        cache, SubprogramTag, |md| md.data.llfn == fcx.llfn) {
       option::Some(md) => return md,
       option::None => ()
     }
 
+    let name = match fcx.param_substs {
+      Some(ref substs) if !substs.tys.is_empty() => {
+        let tys = do substs.tys.map |t| { ty_to_str(cx.tcx, *t) };
+        fmt!("%s<%s>", cx.sess.str_of(ident), str::connect(tys, ", "))
+      }
+      _ => cx.sess.str_of(ident)
+    };
+
     let loc = cx.sess.codemap.lookup_char_pos(sp.lo);
     let file_node = create_file(cx, copy loc.file.name).node;
-    let ty_node = if cx.sess.opts.extra_debuginfo {
-        match ret_ty.node {
-          ast::ty_nil => llnull(),
-          _ => create_ty(cx, ty::node_id_to_type(cx.tcx, id), ret_ty).node
-        }
-    } else {
-        llnull()
+    let sub_node = match cx.tcx.items.get(&fcx.id) {
+      ast_map::node_dtor(*) => {
+        create_composite_type(SubroutineTag, ~"", file_node, 0, 0, 0, 0, 0,
+                              option::None, option::Some(~[llnull()]))
+      }
+      _ => {
+        let fn_ty = ty::node_id_to_type(cx.tcx, id);
+        let fn_ty = match fcx.param_substs {
+          Some(ref substs) => {
+            ty::subst_tps(cx.tcx, substs.tys, substs.self_ty, fn_ty)
+          }
+          None => fn_ty
+        };
+        create_subroutine_type(cx, fn_ty, sp)
+      }
     };
-    let sub_node = create_composite_type(SubroutineTag, ~"", file_node, 0, 0,
-                                         0, 0, option::None,
-                                         option::Some(~[ty_node]));
 
     let fn_metadata = ~[lltag(SubprogramTag),
                        llunused(),
                        file_node,
-                       llstr(cx.sess.str_of(ident)),
+                       llstr(copy name),
                         //XXX fully-qualified C++ name:
-                       llstr(cx.sess.str_of(ident)),
+                       llstr(copy name),
                        llstr(~""), //XXX MIPS name?????
                        file_node,
                        lli32(loc.line as int),
@@ -860,7 +994,7 @@ pub fn create_function(fcx: fn_ctxt) -> @metadata<subprogram_md> {
                       ];
     let val = llmdnode(fn_metadata);
     add_named_metadata(cx, ~"llvm.dbg.sp", val);
-    let mdval = @{node: val, data: {id: id}};
+    let mdval = @{node: val, data: {llfn: fcx.llfn}};
     update_cache(cache, SubprogramTag, subprogram_metadata(mdval));
 
     return mdval;
@@ -16,6 +16,7 @@ use middle::trans::type_of;
 use middle::ty::field;
 use middle::ty;
 
+use core::libc::c_uint;
 use syntax::parse::token::special_idents;
 
 // Creates a simpler, size-equivalent type. The resulting type is guaranteed
@@ -168,6 +169,16 @@ pub fn llalign_of_min(cx: @crate_ctxt, t: TypeRef) -> uint {
     }
 }
 
+// Returns the offset in bytes of the element with index `ix` of the struct
+// type `struct_ty`, including any padding before it.
+pub fn llelement_offset(cx: @crate_ctxt, struct_ty: TypeRef,
+                        ix: uint) -> uint {
+    unsafe {
+        return llvm::LLVMOffsetOfElement(cx.td.lltd, struct_ty,
+                                         ix as c_uint) as uint;
+    }
+}
+
 // Returns the "default" alignment of t, which is calculated by casting
 // null to a record containing a single-bit followed by a t value, then
 // doing gep(0,1) to get at the trailing (and presumably padded) t cell.
@@ -150,10 +150,12 @@ pub fn ast_path_to_substs_and_ty<AC: AstConv, RS: region_scope Copy Durable>(
 
     // Convert the type parameters supplied by the user.
     if !vec::same_length(*decl_bounds, path.types) {
-        self.tcx().sess.span_fatal(
+        self.tcx().sess.span_err_with_code(
             path.span,
             fmt!("wrong number of type arguments: expected %u but found %u",
-                 (*decl_bounds).len(), path.types.len()));
+                 (*decl_bounds).len(), path.types.len()),
+            "E0026");
+        self.tcx().sess.abort_if_errors();
     }
     let tps = path.types.map(|a_t| ast_ty_to_ty(self, rscope, *a_t));
 
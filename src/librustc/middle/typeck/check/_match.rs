@@ -228,10 +228,11 @@ pub fn check_struct_pat_fields(pcx: pat_ctxt,
             }
             None => {
                 let name = pprust::path_to_str(path, tcx.sess.intr());
-                tcx.sess.span_err(span,
-                                  fmt!("struct `%s` does not have a field
-                                        named `%s`", name,
-                                       tcx.sess.str_of(field.ident)));
+                tcx.sess.span_err_with_code(
+                    span,
+                    fmt!("struct `%s` does not have a field named `%s`",
+                         name, tcx.sess.str_of(field.ident)),
+                    "E0027");
             }
         }
     }
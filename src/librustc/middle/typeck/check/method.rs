@@ -1098,15 +1098,15 @@ pub impl LookupContext {
             if num_supplied_tps == 0u {
                 self.fcx.infcx().next_ty_vars(candidate.num_method_tps)
             } else if candidate.num_method_tps == 0u {
-                tcx.sess.span_err(
+                tcx.sess.span_err_with_code(
                     self.expr.span,
-                    ~"this method does not take type parameters");
+                    ~"this method does not take type parameters", "E0026");
                 self.fcx.infcx().next_ty_vars(candidate.num_method_tps)
             } else if num_supplied_tps != candidate.num_method_tps {
-                tcx.sess.span_err(
+                tcx.sess.span_err_with_code(
                     self.expr.span,
                     ~"incorrect number of type \
-                     parameters given for this method");
+                     parameters given for this method", "E0026");
                 self.fcx.infcx().next_ty_vars(candidate.num_method_tps)
             } else {
                 self.supplied_tps.to_vec()
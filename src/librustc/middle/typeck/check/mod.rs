@@ -124,6 +124,7 @@ use syntax::ast_util::{visibility_to_privacy};
 use syntax::ast_util;
 use syntax::codemap::{span, spanned, respan};
 use syntax::codemap;
+use syntax::diagnostic::SpanLabel;
 use syntax::parse::token::special_idents;
 use syntax::print::pprust;
 use syntax::visit;
@@ -506,11 +507,14 @@ pub fn check_no_duplicate_fields(tcx: ty::ctxt,
         let (id, sp) = *p;
         match field_names.find(&id) {
           Some(orig_sp) => {
-            tcx.sess.span_err(sp, fmt!("Duplicate field \
+            tcx.sess.span_err_labelled(sp, fmt!("Duplicate field \
                                    name %s in record type declaration",
-                                        tcx.sess.str_of(id)));
-            tcx.sess.span_note(orig_sp, ~"First declaration of \
-                                          this field occurred here");
+                                        tcx.sess.str_of(id)),
+                                       [SpanLabel {
+                                           span: orig_sp,
+                                           label: ~"first declaration of \
+                                                    this field"
+                                       }]);
             break;
           }
           None => {
@@ -890,6 +894,16 @@ pub impl FnCtxt {
         self.infcx().type_error_message(sp, mk_msg, actual_ty, err);
     }
 
+    fn type_error_message_with_code(@mut self,
+                                    sp: span,
+                                    mk_msg: &fn(~str) -> ~str,
+                                    actual_ty: ty::t,
+                                    err: Option<&ty::type_err>,
+                                    code: &str) {
+        self.infcx().type_error_message_with_code(sp, mk_msg, actual_ty, err,
+                                                  Some(code));
+    }
+
     fn report_mismatched_return_types(@mut self,
                                       sp: span,
                                       e: ty::t,
@@ -1196,7 +1210,7 @@ pub fn check_expr_with_unifier(fcx: @mut FnCtxt,
                                    else {"s were"},
                                    suffix);
 
-                    tcx.sess.span_err(sp, msg);
+                    tcx.sess.span_err_with_code(sp, msg, "E0025");
 
                     vec::from_fn(expected_arg_count, |_| ty::mk_err(tcx))
                 };
@@ -1365,7 +1379,7 @@ pub fn check_expr_with_unifier(fcx: @mut FnCtxt,
                 method_map.insert(expr.id, (*entry));
             }
             None => {
-                fcx.type_error_message(expr.span,
+                fcx.type_error_message_with_code(expr.span,
                   |actual| {
                       fmt!("type `%s` does not implement any method in scope \
                             named `%s`",
@@ -1373,7 +1387,8 @@ pub fn check_expr_with_unifier(fcx: @mut FnCtxt,
                            fcx.ccx.tcx.sess.str_of(method_name))
                   },
                   expr_t,
-                  None);
+                  None,
+                  "E0023");
 
                 // Add error type for the result
                 fcx.write_ty(expr.id, ty::mk_err(fcx.ccx.tcx));
@@ -1748,13 +1763,13 @@ pub fn check_expr_with_unifier(fcx: @mut FnCtxt,
                 }
             }
             None => {
-                fcx.type_error_message(expr.span,
+                fcx.type_error_message_with_code(expr.span,
                   |actual| {
                       fmt!("attempted access of field `%s` on type `%s`, but \
                             no field or method with that name was found",
                            tcx.sess.str_of(field), actual)
                   },
-                  expr_t, None);
+                  expr_t, None, "E0024");
                 // Add error type for the result
                 fcx.write_ty(expr.id, ty::mk_err(tcx));
             }
@@ -1787,16 +1802,18 @@ pub fn check_expr_with_unifier(fcx: @mut FnCtxt,
         for ast_fields.each |field| {
             match class_field_map.find(&field.node.ident) {
                 None => {
-                    tcx.sess.span_err(
+                    tcx.sess.span_err_with_code(
                         field.span,
                         fmt!("structure has no field named `%s`",
-                             tcx.sess.str_of(field.node.ident)));
+                             tcx.sess.str_of(field.node.ident)),
+                        "E0027");
                 }
                 Some((_, true)) => {
-                    tcx.sess.span_err(
+                    tcx.sess.span_err_with_code(
                         field.span,
                         fmt!("field `%s` specified more than once",
-                             tcx.sess.str_of(field.node.ident)));
+                             tcx.sess.str_of(field.node.ident)),
+                        "E0028");
                 }
                 Some((field_id, false)) => {
                     let expected_field_type =
@@ -1828,14 +1845,12 @@ pub fn check_expr_with_unifier(fcx: @mut FnCtxt,
                     }
                 }
 
-                tcx.sess.span_err(span,
-                                  fmt!("missing field%s: %s",
-                                       if missing_fields.len() == 1 {
-                                           ~""
-                                       } else {
-                                           ~"s"
-                                       },
-                                       str::connect(missing_fields, ~", ")));
+                tcx.sess.span_err_with_code(
+                    span,
+                    fmt!("missing field%s: %s",
+                         if missing_fields.len() == 1 { ~"" } else { ~"s" },
+                         str::connect(missing_fields, ~", ")),
+                    "E0029");
             }
         }
 
@@ -3020,16 +3035,18 @@ pub fn instantiate_path(fcx: @mut FnCtxt,
     let tps = if ty_substs_len == 0 {
         fcx.infcx().next_ty_vars(ty_param_count)
     } else if ty_param_count == 0 {
-        fcx.ccx.tcx.sess.span_err
-            (span, ~"this item does not take type parameters");
+        fcx.ccx.tcx.sess.span_err_with_code
+            (span, ~"this item does not take type parameters", "E0026");
         fcx.infcx().next_ty_vars(ty_param_count)
     } else if ty_substs_len > ty_param_count {
-        fcx.ccx.tcx.sess.span_err
-            (span, ~"too many type parameters provided for this item");
+        fcx.ccx.tcx.sess.span_err_with_code
+            (span, ~"too many type parameters provided for this item",
+             "E0026");
         fcx.infcx().next_ty_vars(ty_param_count)
     } else if ty_substs_len < ty_param_count {
-        fcx.ccx.tcx.sess.span_err
-            (span, ~"not enough type parameters provided for this item");
+        fcx.ccx.tcx.sess.span_err_with_code
+            (span, ~"not enough type parameters provided for this item",
+             "E0026");
         fcx.infcx().next_ty_vars(ty_param_count)
     } else {
         pth.types.map(|aty| fcx.to_ty(*aty))
@@ -712,6 +712,13 @@ impl @mut InferCtxt {
 
     fn type_error_message(sp: span, mk_msg: fn(~str) -> ~str,
                           actual_ty: ty::t, err: Option<&ty::type_err>) {
+        self.type_error_message_with_code(sp, mk_msg, actual_ty, err, None)
+    }
+
+    fn type_error_message_with_code(sp: span, mk_msg: fn(~str) -> ~str,
+                                    actual_ty: ty::t,
+                                    err: Option<&ty::type_err>,
+                                    code: Option<&str>) {
         let actual_ty = self.resolve_type_vars_if_possible(actual_ty);
 
         // Don't report an error if actual type is ty_err.
@@ -721,9 +728,12 @@ impl @mut InferCtxt {
         let error_str = err.map_default(~"", |t_err|
                          fmt!(" (%s)",
                               ty::type_err_to_str(self.tcx, *t_err)));
-        self.tcx.sess.span_err(sp,
-           fmt!("%s%s", mk_msg(self.ty_to_str(actual_ty)),
-                error_str));
+        let msg = fmt!("%s%s", mk_msg(self.ty_to_str(actual_ty)),
+                       error_str);
+        match code {
+            Some(code) => self.tcx.sess.span_err_with_code(sp, msg, code),
+            None => self.tcx.sess.span_err(sp, msg)
+        }
         err.iter(|err|
              ty::note_and_explain_type_err(self.tcx, *err));
     }
@@ -743,7 +753,8 @@ impl @mut InferCtxt {
                 }
             }
         };
-        self.type_error_message(sp, mk_msg, a, Some(err));
+        self.type_error_message_with_code(sp, mk_msg, a, Some(err),
+                                          Some("E0016"));
     }
 
     fn replace_bound_regions_with_fresh_regions(
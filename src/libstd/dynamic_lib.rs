@@ -0,0 +1,80 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Loading of dynamic libraries at runtime
+
+use core::libc::{c_char, c_void};
+use core::prelude::*;
+use core::ptr;
+use core::str;
+
+extern mod rustrt {
+    pub unsafe fn rust_dlopen(path: *c_char) -> *c_void;
+    pub unsafe fn rust_dlsym(lib: *c_void, name: *c_char) -> *c_void;
+    pub unsafe fn rust_dlclose(lib: *c_void);
+    pub unsafe fn rust_dlerror() -> *c_char;
+}
+
+/// A dynamic library, which is closed when this is dropped
+pub struct DynamicLibrary {
+    priv handle: *c_void
+}
+
+impl Drop for DynamicLibrary {
+    fn finalize(&self) {
+        unsafe {
+            rustrt::rust_dlclose(self.handle);
+        }
+    }
+}
+
+pub impl DynamicLibrary {
+    /// Loads the dynamic library at `path`
+    static fn open(path: &Path) -> Result<DynamicLibrary, ~str> {
+        unsafe {
+            let handle = do str::as_c_str(path.to_str()) |buf| {
+                rustrt::rust_dlopen(buf)
+            };
+            if ptr::is_null(handle) {
+                Err(str::raw::from_c_str(rustrt::rust_dlerror()))
+            } else {
+                Ok(DynamicLibrary { handle: handle })
+            }
+        }
+    }
+
+    /**
+     * The address of the symbol `name` in the library. It is only valid
+     * for as long as the library stays open.
+     */
+    unsafe fn symbol(&self, name: &str) -> Result<*c_void, ~str> {
+        let sym = do str::as_c_str(name) |buf| {
+            rustrt::rust_dlsym(self.handle, buf)
+        };
+        if ptr::is_null(sym) {
+            Err(str::raw::from_c_str(rustrt::rust_dlerror()))
+        } else {
+            Ok(sym)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dynamic_lib::DynamicLibrary;
+
+    #[test]
+    fn test_open_missing_library() {
+        match DynamicLibrary::open(&Path("no-such-library.so")) {
+            Ok(_) => fail!(),
+            Err(e) => assert !e.is_empty()
+        }
+    }
+}
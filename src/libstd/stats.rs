@@ -12,6 +12,7 @@ use core::vec;
 use core::f64;
 use core::cmp;
 use core::num;
+use core::uint;
 use sort;
 
 // NB: this can probably be rewritten in terms of num::Num
@@ -94,3 +95,98 @@ impl Stats for &[f64] {
         (self.median_abs_dev() / self.median()) * 100.0
     }
 }
+
+/// The outcome of a Mann-Whitney U test on two samples
+pub struct MannWhitney {
+    /// The U statistic of the first sample
+    u: f64,
+    /// U normalized against its distribution under the null hypothesis
+    z: f64,
+    /// Two-sided probability of a shift at least this large by chance
+    p_value: f64
+}
+
+/**
+ * Tests whether two samples are drawn from the same distribution without
+ * assuming anything about its shape, which suits timing data with its
+ * long tail of slow runs.
+ *
+ * Uses the normal approximation to the distribution of U with a
+ * correction for ties, so it is only meaningful for samples of more than
+ * a dozen or so values each.
+ */
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> MannWhitney {
+    assert a.len() != 0 && b.len() != 0;
+
+    pure fn le(x: &(f64, bool), y: &(f64, bool)) -> bool {
+        match (*x, *y) { ((x, _), (y, _)) => x <= y }
+    }
+
+    // Pool both samples, remembering which one each value came from
+    let mut pooled = ~[];
+    for a.each |x| { pooled.push((*x, true)); }
+    for b.each |x| { pooled.push((*x, false)); }
+    let pooled = sort::merge_sort(pooled, le);
+
+    let n = pooled.len();
+    let mut rank_sum = 0.0;
+    let mut ties = 0.0;
+    let mut i = 0;
+    while i < n {
+        let (v, _) = pooled[i];
+        let mut j = i;
+        while j + 1 < n {
+            let (w, _) = pooled[j + 1];
+            if w != v { break; }
+            j += 1;
+        }
+        // Tied values all get the mean of the ranks they span
+        let rank = ((i + j) as f64) / 2.0 + 1.0;
+        let t = (j - i + 1) as f64;
+        ties += t * t * t - t;
+        for uint::range(i, j + 1) |k| {
+            let (_, from_a) = pooled[k];
+            if from_a { rank_sum += rank; }
+        }
+        i = j + 1;
+    }
+
+    let n1 = a.len() as f64;
+    let n2 = b.len() as f64;
+    let nt = n1 + n2;
+    let u = rank_sum - n1 * (n1 + 1.0) / 2.0;
+    let mean = n1 * n2 / 2.0;
+    let var = n1 * n2 / 12.0 * ((nt + 1.0) - ties / (nt * (nt - 1.0)));
+    let z = if var > 0.0 { (u - mean) / f64::sqrt(var) } else { 0.0 };
+
+    MannWhitney {
+        u: u,
+        z: z,
+        p_value: f64::erfc(f64::abs(z) / f64::sqrt(2.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stats::mann_whitney_u;
+    use core::vec;
+
+    #[test]
+    fn test_mann_whitney_identical_samples() {
+        let a = vec::from_fn(30, |i| i as f64);
+        let r = mann_whitney_u(a, a);
+        assert r.u == 450.0;
+        assert r.z == 0.0;
+        assert r.p_value == 1.0;
+    }
+
+    #[test]
+    fn test_mann_whitney_shifted_samples() {
+        let a = vec::from_fn(30, |i| i as f64);
+        let b = vec::from_fn(30, |i| (i + 100) as f64);
+        let r = mann_whitney_u(a, b);
+        assert r.u == 0.0;
+        assert r.z < 0.0;
+        assert r.p_value < 0.001;
+    }
+}
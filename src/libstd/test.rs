@@ -16,24 +16,35 @@
 // while providing a base that other test frameworks may build off of.
 
 use getopts;
+use json;
 use sort;
 use term;
+use time::precise_time_ns;
+use timer;
+use uv;
 
 use core::cmp::Eq;
 
 use core::to_str::ToStr;
 use core::either::Either;
 use core::either;
+use core::f64;
+use core::hashmap::linear::LinearMap;
 use core::io::WriterUtil;
 use core::io;
+use core::libc;
 use core::libc::size_t;
+use core::logging;
 use core::pipes::{stream, Chan, Port, SharedChan};
 use core::option;
+use core::os;
 use core::prelude::*;
 use core::result;
 use core::str;
+use core::sys;
 use core::task::TaskBuilder;
 use core::task;
+use core::uint;
 use core::vec;
 
 #[abi = "cdecl"]
@@ -107,7 +118,10 @@ pub struct BenchHarness {
 pub struct TestDesc {
     name: TestName,
     ignore: bool,
-    should_fail: bool
+    should_fail: bool,
+    // Milliseconds after which the test is killed and reported as timed
+    // out. Falls back to the runner's --timeout when None.
+    timeout: Option<uint>
 }
 
 pub struct TestDescAndFn {
@@ -123,7 +137,14 @@ pub fn test_main(args: &[~str], tests: ~[TestDescAndFn]) {
           either::Left(move o) => o,
           either::Right(move m) => fail!(m)
         };
-    if !run_tests_console(&opts, tests) { fail!(~"Some tests failed"); }
+    let has_timeouts = vec::any(tests, |t| t.desc.timeout.is_some());
+    if !run_tests_console(&opts, tests) {
+        // A test that timed out may still be spinning without ever
+        // yielding, and such a task can't be killed, so exit directly
+        // rather than wait for it
+        if has_timeouts { unsafe { libc::exit(101); } }
+        fail!(~"Some tests failed");
+    }
 }
 
 // A variant optimized for invocation with a static test vector.
@@ -157,9 +178,30 @@ pub struct TestOpts {
     run_benchmarks: bool,
     save_results: Option<Path>,
     compare_results: Option<Path>,
-    logfile: Option<Path>
+    logfile: Option<Path>,
+    test_threads: Option<uint>,
+    format: OutputFormat,
+    test_timeout: Option<uint>,
+    // Changes in median ns/iter smaller than this percentage are treated
+    // as noise when comparing against a saved baseline.
+    noise_threshold: f64,
+    fail_on_regression: bool,
+    // Let tests print straight to the console instead of capturing their
+    // output and only showing it for failures
+    nocapture: bool
+}
+
+// How run_tests_console reports results on stdout: the human-readable
+// default, one JSON object per line, or a JUnit XML document.
+#[deriving_eq]
+pub enum OutputFormat {
+    PrettyFormat,
+    JsonFormat,
+    JunitFormat
 }
 
+pub const default_noise_threshold: f64 = 5.0;
+
 type OptRes = Either<TestOpts, ~str>;
 
 // Parses command line arguments into test options
@@ -170,7 +212,13 @@ pub fn parse_opts(args: &[~str]) -> OptRes {
                  getopts::optflag(~"bench"),
                  getopts::optopt(~"save"),
                  getopts::optopt(~"diff"),
-                 getopts::optopt(~"logfile")];
+                 getopts::optopt(~"logfile"),
+                 getopts::optopt(~"test-threads"),
+                 getopts::optopt(~"format"),
+                 getopts::optopt(~"timeout"),
+                 getopts::optopt(~"noise-threshold"),
+                 getopts::optflag(~"fail-on-regression"),
+                 getopts::optflag(~"nocapture")];
     let matches =
         match getopts::getopts(args_, opts) {
           Ok(move m) => m,
@@ -197,6 +245,53 @@ pub fn parse_opts(args: &[~str]) -> OptRes {
     let compare_results = getopts::opt_maybe_str(&matches, ~"diff");
     let compare_results = compare_results.map(|s| Path(*s));
 
+    let test_threads = match getopts::opt_maybe_str(&matches,
+                                                    ~"test-threads") {
+        Some(s) => match uint::from_str(s) {
+            Some(n) if n > 0 => Some(n),
+            _ => return either::Right(
+                fmt!("argument for --test-threads must be a number > 0 \
+                      (got `%s`)", s))
+        },
+        None => None
+    };
+
+    let format = match getopts::opt_maybe_str(&matches, ~"format") {
+        None => PrettyFormat,
+        Some(~"pretty") => PrettyFormat,
+        Some(~"json") => JsonFormat,
+        Some(~"junit") => JunitFormat,
+        Some(s) => return either::Right(
+            fmt!("argument for --format must be pretty, json or junit \
+                  (got `%s`)", s))
+    };
+
+    let test_timeout = match getopts::opt_maybe_str(&matches, ~"timeout") {
+        Some(s) => match uint::from_str(s) {
+            Some(ms) if ms > 0 => Some(ms),
+            _ => return either::Right(
+                fmt!("argument for --timeout must be a number of \
+                      milliseconds > 0 (got `%s`)", s))
+        },
+        None => None
+    };
+
+    let noise_threshold =
+        match getopts::opt_maybe_str(&matches, ~"noise-threshold") {
+        Some(s) => match f64::from_str(s) {
+            Some(pct) if pct >= 0.0 => pct,
+            _ => return either::Right(
+                fmt!("argument for --noise-threshold must be a \
+                      non-negative percentage (got `%s`)", s))
+        },
+        None => default_noise_threshold
+    };
+
+    let fail_on_regression =
+        getopts::opt_present(&matches, ~"fail-on-regression");
+
+    let nocapture = getopts::opt_present(&matches, ~"nocapture");
+
     let test_opts = TestOpts {
         filter: filter,
         run_ignored: run_ignored,
@@ -204,7 +299,13 @@ pub fn parse_opts(args: &[~str]) -> OptRes {
         run_benchmarks: run_benchmarks,
         save_results: save_results,
         compare_results: compare_results,
-        logfile: logfile
+        logfile: logfile,
+        test_threads: test_threads,
+        format: format,
+        test_timeout: test_timeout,
+        noise_threshold: noise_threshold,
+        fail_on_regression: fail_on_regression,
+        nocapture: nocapture
     };
 
     either::Left(test_opts)
@@ -217,18 +318,93 @@ pub struct BenchSamples {
 }
 
 #[deriving_eq]
-pub enum TestResult { TrOk, TrFailed, TrIgnored, TrBench(BenchSamples) }
+pub enum TestResult {
+    TrOk,
+    TrFailed,
+    TrIgnored,
+    TrTimedOut(uint),
+    TrBench(BenchSamples)
+}
+
+// Everything the structured output formats know about a finished test.
+pub struct TestRecord {
+    desc: TestDesc,
+    result: TestResult,
+    ns_elapsed: u64,
+    message: Option<~str>,
+    // Whatever the test printed to stdout or stderr or logged
+    output: ~str
+}
+
+impl TestRecord {
+    fn to_json(&self) -> json::Json {
+        let mut obj = LinearMap::new();
+        obj.insert(~"type", json::String(~"test"));
+        obj.insert(~"name", json::String(self.desc.name.to_str()));
+        obj.insert(~"outcome", json::String(outcome_str(&self.result)));
+        obj.insert(~"duration_ns", json::Number(self.ns_elapsed as float));
+        obj.insert(~"message", match self.message {
+            Some(ref m) => json::String(copy *m),
+            None => json::Null
+        });
+        obj.insert(~"output", json::String(copy self.output));
+        match self.result {
+            TrBench(ref bs) => {
+                use stats::Stats;
+                let median = bs.ns_iter_samples.median();
+                let mad = bs.ns_iter_samples.median_abs_dev();
+                obj.insert(~"ns_iter_median", json::Number(median as float));
+                obj.insert(~"ns_iter_mad", json::Number(mad as float));
+                obj.insert(~"mb_s", json::Number(bs.mb_s as float));
+            }
+            _ => ()
+        }
+        json::Object(~obj)
+    }
+}
+
+pure fn outcome_str(result: &TestResult) -> ~str {
+    match *result {
+        TrOk => ~"ok",
+        TrFailed => ~"failed",
+        TrIgnored => ~"ignored",
+        TrTimedOut(_) => ~"timeout",
+        TrBench(_) => ~"bench"
+    }
+}
+
+// The explanation attached to a failed test in structured output, given
+// the message the test failed with, if any.
+fn failure_message(desc: &TestDesc, result: &TestResult,
+                   failure: &Option<~str>) -> Option<~str> {
+    match *result {
+        TrFailed if desc.should_fail => {
+            Some(~"test did not fail as expected")
+        }
+        TrFailed => match *failure {
+            Some(ref msg) => Some(copy *msg),
+            None => Some(~"test task failed")
+        },
+        TrTimedOut(ms) => Some(fmt!("test timed out after %u ms", ms)),
+        _ => None
+    }
+}
 
 struct ConsoleTestState {
     out: io::Writer,
     log_out: Option<io::Writer>,
     use_color: bool,
+    format: OutputFormat,
     mut total: uint,
     mut passed: uint,
     mut failed: uint,
     mut ignored: uint,
     mut benchmarked: uint,
-    mut failures: ~[TestDesc]
+    mut failures: ~[TestDesc],
+    // The captured output of failed tests that printed anything
+    mut failure_output: ~[(~str, ~str)],
+    mut records: ~[TestRecord],
+    mut benchmarks: ~[(~str, BenchSamples)]
 }
 
 // A simple console test runner
@@ -237,41 +413,85 @@ pub fn run_tests_console(opts: &TestOpts,
 
     fn callback(event: &TestEvent, st: @ConsoleTestState) {
         debug!("callback(event=%?)", event);
+        let pretty = st.format == PrettyFormat;
         match *event {
           TeFiltered(ref filtered_tests) => {
             st.total = filtered_tests.len();
-            let noun = if st.total != 1 { ~"tests" } else { ~"test" };
-            st.out.write_line(fmt!("\nrunning %u %s", st.total, noun));
+            if pretty {
+                let noun = if st.total != 1 { ~"tests" } else { ~"test" };
+                st.out.write_line(fmt!("\nrunning %u %s", st.total, noun));
+            }
           }
-          TeWait(ref test) => st.out.write_str(
-              fmt!("test %s ... ", test.name.to_str())),
-          TeResult(copy test, result) => {
+          TeWait(ref test) => if pretty {
+              st.out.write_str(fmt!("test %s ... ", test.name.to_str()))
+          },
+          TeResult(copy test, result, ns_elapsed, ref output,
+                   ref failure) => {
             match st.log_out {
                 Some(f) => write_log(f, result, &test),
                 None => ()
             }
+            let record = TestRecord {
+                desc: copy test,
+                result: copy result,
+                ns_elapsed: ns_elapsed,
+                message: failure_message(&test, &result, failure),
+                output: copy *output
+            };
+            match st.format {
+              JsonFormat => {
+                st.out.write_line(json::to_str(&record.to_json()))
+              }
+              JunitFormat => st.records.push(move record),
+              PrettyFormat => ()
+            }
             match result {
               TrOk => {
                 st.passed += 1;
-                write_ok(st.out, st.use_color);
-                st.out.write_line(~"");
+                if pretty {
+                    write_ok(st.out, st.use_color);
+                    st.out.write_line(~"");
+                }
               }
               TrFailed => {
                 st.failed += 1;
-                write_failed(st.out, st.use_color);
-                st.out.write_line(~"");
+                if pretty {
+                    write_failed(st.out, st.use_color);
+                    st.out.write_line(~"");
+                }
+                if !output.is_empty() {
+                    st.failure_output.push((test.name.to_str(),
+                                            copy *output));
+                }
                 st.failures.push(move test);
               }
               TrIgnored => {
                 st.ignored += 1;
-                write_ignored(st.out, st.use_color);
-                st.out.write_line(~"");
+                if pretty {
+                    write_ignored(st.out, st.use_color);
+                    st.out.write_line(~"");
+                }
+              }
+              TrTimedOut(_) => {
+                st.failed += 1;
+                if pretty {
+                    write_timed_out(st.out, st.use_color);
+                    st.out.write_line(~"");
+                }
+                if !output.is_empty() {
+                    st.failure_output.push((test.name.to_str(),
+                                            copy *output));
+                }
+                st.failures.push(move test);
               }
               TrBench(bs) => {
                 st.benchmarked += 1u;
-                write_bench(st.out, st.use_color);
-                st.out.write_line(fmt!(": %s",
-                                       fmt_bench_samples(&bs)));
+                st.benchmarks.push((test.name.to_str(), copy bs));
+                if pretty {
+                    write_bench(st.out, st.use_color);
+                    st.out.write_line(fmt!(": %s",
+                                           fmt_bench_samples(&bs)));
+                }
               }
             }
           }
@@ -293,34 +513,78 @@ pub fn run_tests_console(opts: &TestOpts,
     let st = @ConsoleTestState {
         out: io::stdout(),
         log_out: log_out,
-        use_color: use_color(),
+        use_color: use_color(opts),
+        format: opts.format,
         mut total: 0u,
         mut passed: 0u,
         mut failed: 0u,
         mut ignored: 0u,
         mut benchmarked: 0u,
-        mut failures: ~[]
+        mut failures: ~[],
+        mut failure_output: ~[],
+        mut records: ~[],
+        mut benchmarks: ~[]
     };
 
     run_tests(opts, tests, |x| callback(&x, st));
 
     assert (st.passed + st.failed +
             st.ignored + st.benchmarked == st.total);
-    let success = st.failed == 0u;
+    let mut success = st.failed == 0u;
+
+    match opts.compare_results {
+      Some(ref path) => {
+        let baseline = match load_baseline(path) {
+            Ok(move b) => b,
+            Err(ref e) => {
+                fail!(fmt!("can't load benchmark baseline %s: %s",
+                           path.to_str(), *e))
+            }
+        };
+        let cmps = compare_to_baseline(&baseline, st.benchmarks,
+                                       opts.noise_threshold);
+        if st.format == PrettyFormat {
+            write_comparison(st.out, path, opts.noise_threshold, cmps,
+                             st.benchmarks);
+        }
+        let regressed = cmps.any(|c| c.change == BenchRegressed);
+        if regressed && opts.fail_on_regression {
+            success = false;
+        }
+      }
+      None => ()
+    }
 
-    if !success {
-        print_failures(st);
+    match opts.save_results {
+      Some(ref path) => match save_baseline(path, st.benchmarks) {
+        Ok(()) => (),
+        Err(ref e) => {
+            fail!(fmt!("can't save benchmark baseline %s: %s",
+                       path.to_str(), *e))
+        }
+      },
+      None => ()
     }
 
-    st.out.write_str(fmt!("\nresult: "));
-    if success {
-        // There's no parallelism at this point so it's safe to use color
-        write_ok(st.out, true);
-    } else {
-        write_failed(st.out, true);
+    match st.format {
+      JsonFormat => write_json_summary(st),
+      JunitFormat => write_junit(st),
+      PrettyFormat => {
+        if st.failed != 0 {
+            print_failures(st);
+        }
+
+        st.out.write_str(fmt!("\nresult: "));
+        if success {
+            // There's no parallelism at this point so it's safe to use color
+            write_ok(st.out, true);
+        } else {
+            write_failed(st.out, true);
+        }
+        st.out.write_str(fmt!(". %u passed; %u failed; %u ignored\n\n",
+                              st.passed, st.failed, st.ignored));
+      }
     }
-    st.out.write_str(fmt!(". %u passed; %u failed; %u ignored\n\n",
-                          st.passed, st.failed, st.ignored));
 
     return success;
 
@@ -344,6 +608,7 @@ pub fn run_tests_console(opts: &TestOpts,
                         TrOk => ~"ok",
                         TrFailed => ~"failed",
                         TrIgnored => ~"ignored",
+                        TrTimedOut(_) => ~"timeout",
                         TrBench(ref bs) => fmt_bench_samples(bs)
                     }, test.name.to_str()));
     }
@@ -356,6 +621,10 @@ pub fn run_tests_console(opts: &TestOpts,
         write_pretty(out, ~"FAILED", term::color_red, use_color);
     }
 
+    fn write_timed_out(out: io::Writer, use_color: bool) {
+        write_pretty(out, ~"TIMEOUT", term::color_red, use_color);
+    }
+
     fn write_ignored(out: io::Writer, use_color: bool) {
         write_pretty(out, ~"ignored", term::color_yellow, use_color);
     }
@@ -376,6 +645,15 @@ pub fn run_tests_console(opts: &TestOpts,
 }
 
 fn print_failures(st: @ConsoleTestState) {
+    for st.failure_output.each |f| {
+        match *f {
+            (ref name, ref output) => {
+                st.out.write_line(fmt!("\n---- %s output ----", *name));
+                st.out.write_str(*output);
+            }
+        }
+    }
+
     st.out.write_line(~"\nfailures:");
     let failures = vec::cast_to_mut(st.failures.map(|t| t.name.to_str()));
     sort::tim_sort(failures);
@@ -384,6 +662,364 @@ fn print_failures(st: @ConsoleTestState) {
     }
 }
 
+// The significance level below which a change in benchmark timings is
+// believed to be more than chance.
+const significance_level: f64 = 0.05;
+
+#[deriving_eq]
+pub enum BenchChange { BenchRegressed, BenchImproved, BenchUnchanged }
+
+// How a benchmark's timings moved relative to a saved baseline
+pub struct BenchComparison {
+    name: ~str,
+    old_median: f64,
+    new_median: f64,
+    new_mad: f64,
+    pct_change: f64,
+    p_value: f64,
+    change: BenchChange
+}
+
+/**
+ * Compares two runs of a benchmark. The change only counts if the median
+ * moved by more than `noise_threshold` percent and a Mann-Whitney test
+ * says the two sets of samples are unlikely to come from the same
+ * distribution.
+ */
+pub fn compare_bench(name: ~str, old: &BenchSamples, new: &BenchSamples,
+                     noise_threshold: f64) -> BenchComparison {
+    use stats::{Stats, mann_whitney_u};
+
+    let old_median = old.ns_iter_samples.median();
+    let new_median = new.ns_iter_samples.median();
+    let pct_change = if old_median == 0.0 { 0.0 }
+                     else { (new_median - old_median) / old_median * 100.0 };
+    let mw = mann_whitney_u(old.ns_iter_samples, new.ns_iter_samples);
+    let change = if mw.p_value >= significance_level ||
+                    f64::abs(pct_change) <= noise_threshold {
+        BenchUnchanged
+    } else if pct_change > 0.0 {
+        BenchRegressed
+    } else {
+        BenchImproved
+    };
+
+    BenchComparison {
+        name: name,
+        old_median: old_median,
+        new_median: new_median,
+        new_mad: new.ns_iter_samples.median_abs_dev(),
+        pct_change: pct_change,
+        p_value: mw.p_value,
+        change: change
+    }
+}
+
+// Compares every benchmark that also appears in the baseline
+pub fn compare_to_baseline(baseline: &LinearMap<~str, BenchSamples>,
+                           benchmarks: &[(~str, BenchSamples)],
+                           noise_threshold: f64) -> ~[BenchComparison] {
+    let mut cmps = ~[];
+    for benchmarks.each |b| {
+        match *b {
+            (ref name, ref new) => match baseline.find(name) {
+                Some(old) => {
+                    cmps.push(compare_bench(copy *name, old, new,
+                                            noise_threshold));
+                }
+                None => ()
+            }
+        }
+    }
+    cmps
+}
+
+// Baselines are a JSON object mapping each benchmark name to its samples
+pub fn save_baseline(path: &Path,
+                     benchmarks: &[(~str, BenchSamples)])
+                  -> Result<(), ~str> {
+    use stats::Stats;
+
+    let mut obj = LinearMap::new();
+    for benchmarks.each |b| {
+        match *b {
+            (ref name, ref bs) => {
+                let mut entry = LinearMap::new();
+                let samples = do bs.ns_iter_samples.map |s| {
+                    json::Number(*s as float)
+                };
+                let median = bs.ns_iter_samples.median();
+                let mad = bs.ns_iter_samples.median_abs_dev();
+                entry.insert(~"samples", json::List(samples));
+                entry.insert(~"median", json::Number(median as float));
+                entry.insert(~"mad", json::Number(mad as float));
+                entry.insert(~"mb_s", json::Number(bs.mb_s as float));
+                obj.insert(copy *name, json::Object(~entry));
+            }
+        }
+    }
+
+    match io::file_writer(path, ~[io::Create, io::Truncate]) {
+        Ok(w) => {
+            json::to_pretty_writer(w, &json::Object(~obj));
+            Ok(())
+        }
+        Err(move e) => Err(e)
+    }
+}
+
+pub fn load_baseline(path: &Path)
+                  -> Result<LinearMap<~str, BenchSamples>, ~str> {
+    fn bench_from_json(j: &json::Json) -> Option<BenchSamples> {
+        let entry = match *j {
+            json::Object(ref o) => o,
+            _ => return None
+        };
+        let samples = match entry.find(&~"samples") {
+            Some(&json::List(ref l)) => {
+                let mut samples = ~[];
+                for l.each |s| {
+                    match *s {
+                        json::Number(n) => samples.push(n as f64),
+                        _ => return None
+                    }
+                }
+                samples
+            }
+            _ => return None
+        };
+        if samples.is_empty() {
+            return None;
+        }
+        let mb_s = match entry.find(&~"mb_s") {
+            Some(&json::Number(n)) => n as uint,
+            _ => 0
+        };
+        Some(BenchSamples { ns_iter_samples: samples, mb_s: mb_s })
+    }
+
+    let s = match io::read_whole_file_str(path) {
+        Ok(move s) => s,
+        Err(move e) => return Err(e)
+    };
+    let j = match json::from_str(s) {
+        Ok(move j) => j,
+        Err(ref e) => return Err(e.to_str())
+    };
+    let mut baseline = LinearMap::new();
+    match j {
+        json::Object(ref o) => {
+            for o.each |&(name, v)| {
+                match bench_from_json(v) {
+                    Some(move bs) => { baseline.insert(copy *name, bs); }
+                    None => {
+                        return Err(fmt!("malformed entry for benchmark %s",
+                                        *name));
+                    }
+                }
+            }
+        }
+        _ => return Err(~"baseline is not a JSON object")
+    }
+    Ok(baseline)
+}
+
+fn write_comparison(out: io::Writer, path: &Path, noise_threshold: f64,
+                    cmps: &[BenchComparison],
+                    benchmarks: &[(~str, BenchSamples)]) {
+    out.write_line(fmt!("\nbenchmarks compared to %s \
+                         (noise threshold %.1f%%):",
+                        path.to_str(), noise_threshold as float));
+    let mut regressed = 0u, improved = 0u, unchanged = 0u;
+    for cmps.each |c| {
+        let verdict = match c.change {
+            BenchRegressed => { regressed += 1; ~"REGRESSED" }
+            BenchImproved => { improved += 1; ~"improved" }
+            BenchUnchanged => { unchanged += 1; ~"no change" }
+        };
+        out.write_line(fmt!("    %s: %u -> %u ns/iter (+/- %u) \
+                             %s%.2f%%, p = %.4f: %s",
+                            c.name, c.old_median as uint,
+                            c.new_median as uint,
+                            3 * (c.new_mad as uint),
+                            if c.pct_change >= 0.0 { ~"+" } else { ~"" },
+                            c.pct_change as float, c.p_value as float,
+                            verdict));
+    }
+    for benchmarks.each |b| {
+        match *b {
+            (ref name, _) => if !cmps.any(|c| c.name == *name) {
+                out.write_line(fmt!("    %s: not in baseline", *name));
+            }
+        }
+    }
+    out.write_line(fmt!("%u regressed; %u improved; %u unchanged",
+                        regressed, improved, unchanged));
+}
+
+fn write_json_summary(st: @ConsoleTestState) {
+    let mut obj = LinearMap::new();
+    obj.insert(~"type", json::String(~"summary"));
+    obj.insert(~"total", json::Number(st.total as float));
+    obj.insert(~"passed", json::Number(st.passed as float));
+    obj.insert(~"failed", json::Number(st.failed as float));
+    obj.insert(~"ignored", json::Number(st.ignored as float));
+    obj.insert(~"benchmarked", json::Number(st.benchmarked as float));
+    st.out.write_line(json::to_str(&json::Object(~obj)));
+}
+
+fn write_junit(st: @ConsoleTestState) {
+    fn seconds(ns: u64) -> ~str {
+        fmt!("%u.%03u", (ns / 1_000_000_000) as uint,
+             ((ns / 1_000_000) % 1_000) as uint)
+    }
+
+    // JUnit wants a class and a method; use the module path for the former
+    let total_ns = vec::foldl(0, st.records, |n, r| n + r.ns_elapsed);
+    st.out.write_line(~"<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+    st.out.write_line(~"<testsuites>");
+    st.out.write_line(fmt!("  <testsuite name=\"rust\" tests=\"%u\" \
+                            failures=\"%u\" skipped=\"%u\" time=\"%s\">",
+                           st.total, st.failed, st.ignored,
+                           seconds(total_ns)));
+    for st.records.each |r| {
+        let name = r.desc.name.to_str();
+        let mut path = str::split_str(name, "::");
+        let leaf = path.pop();
+        let class = if path.is_empty() { ~"rust" }
+                    else { str::connect(path, "::") };
+        let open = fmt!("    <testcase classname=\"%s\" name=\"%s\" \
+                         time=\"%s\"",
+                        xml_escape(class), xml_escape(leaf),
+                        seconds(r.ns_elapsed));
+        match r.result {
+          TrFailed | TrTimedOut(_) => {
+            let msg = match r.message {
+                Some(ref m) => copy *m,
+                None => ~""
+            };
+            st.out.write_line(open + ~">");
+            st.out.write_line(fmt!("      <failure message=\"%s\"/>",
+                                   xml_escape(msg)));
+            write_system_out(st.out, r.output);
+            st.out.write_line(~"    </testcase>");
+          }
+          TrIgnored => {
+            st.out.write_line(open + ~">");
+            st.out.write_line(~"      <skipped/>");
+            st.out.write_line(~"    </testcase>");
+          }
+          TrOk | TrBench(_) => if r.output.is_empty() {
+            st.out.write_line(open + ~"/>")
+          } else {
+            st.out.write_line(open + ~">");
+            write_system_out(st.out, r.output);
+            st.out.write_line(~"    </testcase>");
+          }
+        }
+    }
+    st.out.write_line(~"  </testsuite>");
+    st.out.write_line(~"</testsuites>");
+}
+
+fn write_system_out(out: io::Writer, output: &str) {
+    if !output.is_empty() {
+        out.write_line(fmt!("      <system-out>%s</system-out>",
+                            xml_escape(output)));
+    }
+}
+
+fn xml_escape(s: &str) -> ~str {
+    let mut escaped = ~"";
+    for str::chars_each(s) |c| {
+        match c {
+          '&' => escaped += ~"&amp;",
+          '<' => escaped += ~"&lt;",
+          '>' => escaped += ~"&gt;",
+          '"' => escaped += ~"&quot;",
+          '\'' => escaped += ~"&apos;",
+          '\n' => escaped += ~"&#10;",
+          _ => str::push_char(&mut escaped, c)
+        }
+    }
+    escaped
+}
+
+#[test]
+fn junit_output_escapes_names_and_reports_failures() {
+    let s = do io::with_str_writer |wr| {
+        let ok = TestRecord {
+            desc: TestDesc {
+                name: StaticTestName("a::b<c>"),
+                ignore: false,
+                should_fail: false,
+                timeout: option::None
+            },
+            result: TrOk,
+            ns_elapsed: 1_500_000_000,
+            message: None,
+            output: ~""
+        };
+        let failed = TestRecord {
+            desc: TestDesc {
+                name: StaticTestName("d"),
+                ignore: false,
+                should_fail: true,
+                timeout: option::None
+            },
+            result: TrFailed,
+            ns_elapsed: 0,
+            message: Some(~"\"oops\""),
+            output: ~"<boom>"
+        };
+
+        let st = @ConsoleTestState {
+            out: wr,
+            log_out: option::None,
+            use_color: false,
+            format: JunitFormat,
+            mut total: 2u,
+            mut passed: 1u,
+            mut failed: 1u,
+            mut ignored: 0u,
+            mut benchmarked: 0u,
+            mut failures: ~[],
+            mut failure_output: ~[],
+            mut records: ~[move ok, move failed],
+            mut benchmarks: ~[]
+        };
+
+        write_junit(st);
+    };
+
+    assert str::contains(s, "tests=\"2\" failures=\"1\"");
+    assert str::contains(s, "classname=\"a\" name=\"b&lt;c&gt;\" \
+                             time=\"1.500\"/>");
+    assert str::contains(s, "<failure message=\"&quot;oops&quot;\"/>");
+    assert str::contains(s, "<system-out>&lt;boom&gt;</system-out>");
+}
+
+#[test]
+fn json_record_includes_outcome_and_message() {
+    let record = TestRecord {
+        desc: TestDesc {
+            name: StaticTestName("x"),
+            ignore: false,
+            should_fail: false,
+            timeout: option::None
+        },
+        result: TrFailed,
+        ns_elapsed: 42,
+        message: Some(~"test task failed"),
+        output: ~"hello\n"
+    };
+    let s = json::to_str(&record.to_json());
+    assert str::contains(s, "\"outcome\":\"failed\"");
+    assert str::contains(s, "\"message\":\"test task failed\"");
+    assert str::contains(s, "\"duration_ns\":42");
+    assert str::contains(s, "\"output\":\"hello\\n\"");
+}
+
 #[test]
 fn should_sort_failures_before_printing_them() {
     fn dummy() {}
@@ -392,25 +1028,31 @@ fn should_sort_failures_before_printing_them() {
         let test_a = TestDesc {
             name: StaticTestName("a"),
             ignore: false,
-            should_fail: false
+            should_fail: false,
+            timeout: option::None
         };
 
         let test_b = TestDesc {
             name: StaticTestName("b"),
             ignore: false,
-            should_fail: false
+            should_fail: false,
+            timeout: option::None
         };
 
         let st = @ConsoleTestState {
             out: wr,
             log_out: option::None,
             use_color: false,
+            format: PrettyFormat,
             mut total: 0u,
             mut passed: 0u,
             mut failed: 0u,
             mut ignored: 0u,
             mut benchmarked: 0u,
-            mut failures: ~[move test_b, move test_a]
+            mut failures: ~[move test_b, move test_a],
+            mut failure_output: ~[],
+            mut records: ~[],
+            mut benchmarks: ~[]
         };
 
         print_failures(st);
@@ -421,15 +1063,113 @@ fn should_sort_failures_before_printing_them() {
     assert apos < bpos;
 }
 
-fn use_color() -> bool { return get_concurrency() == 1; }
+#[test]
+fn parallel_results_are_reported_in_order() {
+    let opts = TestOpts {
+        filter: option::None,
+        run_ignored: false,
+        logfile: option::None,
+        run_tests: true,
+        run_benchmarks: false,
+        save_results: option::None,
+        compare_results: option::None,
+        test_threads: option::Some(4),
+        format: PrettyFormat,
+        test_timeout: option::None,
+        noise_threshold: default_noise_threshold,
+        fail_on_regression: false,
+        nocapture: false
+    };
+
+    let names = ~[~"d", ~"b", ~"e", ~"a", ~"c"];
+    let tests = do names.map |name| {
+        // Make the tests that sort first take the longest to finish
+        let delay = (5 - (name[0] - ('a' as u8)) as uint) * 10;
+        TestDescAndFn {
+            desc: TestDesc {
+                name: DynTestName(copy *name),
+                ignore: false,
+                should_fail: false,
+                timeout: option::None
+            },
+            testfn: DynTestFn(fn~() {
+                for uint::range(0, delay) |_| { task::yield(); }
+            })
+        }
+    };
+
+    let seen = @mut ~[];
+    run_tests(&opts, tests, |e| {
+        match e {
+            TeResult(desc, _, _, _, _) => seen.push(desc.name.to_str()),
+            _ => ()
+        }
+    });
+
+    assert *seen == ~[~"a", ~"b", ~"c", ~"d", ~"e"];
+}
+
+#[test]
+fn results_of_tests_with_the_same_name_stay_apart() {
+    let opts = TestOpts {
+        filter: option::None,
+        run_ignored: false,
+        logfile: option::None,
+        run_tests: true,
+        run_benchmarks: false,
+        save_results: option::None,
+        compare_results: option::None,
+        test_threads: option::Some(2),
+        format: PrettyFormat,
+        test_timeout: option::None,
+        noise_threshold: default_noise_threshold,
+        fail_on_regression: false,
+        nocapture: false
+    };
+
+    // The ignored test reports at once, while the other is still running
+    let tests = do ~[true, false].map |ignore| {
+        TestDescAndFn {
+            desc: TestDesc {
+                name: DynTestName(~"same"),
+                ignore: *ignore,
+                should_fail: false,
+                timeout: option::None
+            },
+            testfn: DynTestFn(fn~() {
+                for uint::range(0, 50) |_| { task::yield(); }
+            })
+        }
+    };
+
+    let seen = @mut 0u;
+    run_tests(&opts, tests, |e| {
+        match e {
+            TeResult(desc, result, _, _, _) => {
+                let expected = if desc.ignore { TrIgnored } else { TrOk };
+                assert result == expected;
+                *seen += 1;
+            }
+            _ => ()
+        }
+    });
+
+    assert *seen == 2u;
+}
+
+fn use_color(opts: &TestOpts) -> bool { return get_concurrency(opts) == 1; }
 
 enum TestEvent {
     TeFiltered(~[TestDesc]),
     TeWait(TestDesc),
-    TeResult(TestDesc, TestResult),
+    // The result of a test along with its wall-clock run time in ns, its
+    // captured output and the message it failed with
+    TeResult(TestDesc, TestResult, u64, ~str, Option<~str>),
 }
 
-type MonitorMsg = (TestDesc, TestResult);
+// A finished test with the position it was spawned at, its result,
+// anything it printed while running and the message it failed with
+type MonitorMsg = (uint, TestDesc, TestResult, ~str, Option<~str>);
 
 fn run_tests(opts: &TestOpts,
              tests: ~[TestDescAndFn],
@@ -450,43 +1190,84 @@ fn run_tests(opts: &TestOpts,
 
     // It's tempting to just spawn all the tests at once, but since we have
     // many tests that run in other processes we would be making a big mess.
-    let concurrency = get_concurrency();
+    let concurrency = get_concurrency(opts);
     debug!("using %u test tasks", concurrency);
 
+    let total = filtered_tests.len();
     let mut remaining = filtered_tests;
     vec::reverse(remaining);
     let mut pending = 0;
 
+    // Tests finish in whatever order the scheduler pleases, so results are
+    // parked here by their position in the sorted test list and reported
+    // in that order. Names can repeat, so each test reports its position
+    // back along with its result.
+    let mut finished: ~[Option<(TestResult, u64, ~str, Option<~str>)>] =
+        vec::from_elem(total, None);
+    let mut start_times = ~[];
+    let mut next_spawn = 0;
+    let mut next_report = 0;
+    let mut descs = ~[];
+
     let (p, ch) = stream();
     let ch = SharedChan(ch);
 
     while pending > 0 || !remaining.is_empty() {
         while pending < concurrency && !remaining.is_empty() {
-            let test = remaining.pop();
+            let test = with_default_timeout(remaining.pop(),
+                                            opts.test_timeout);
             if concurrency == 1 {
                 // We are doing one test at a time so we can print the name
                 // of the test before we run it. Useful for debugging tests
                 // that hang forever.
-                callback(TeWait(test.desc));
+                callback(TeWait(copy test.desc));
             }
-            run_test(!opts.run_tests, test, ch.clone());
+            start_times.push(precise_time_ns());
+            descs.push(copy test.desc);
+            run_test(!opts.run_tests, opts.nocapture, test, next_spawn,
+                     ch.clone());
+            next_spawn += 1;
             pending += 1;
         }
 
-        let (desc, result) = p.recv();
-        if concurrency != 1 {
-            callback(TeWait(desc));
-        }
-        callback(TeResult(desc, result));
+        let (idx, _, result, output, failure) = p.recv();
+        let ns_elapsed = precise_time_ns() - start_times[idx];
+        finished[idx] = Some((result, ns_elapsed, output, failure));
         pending -= 1;
+
+        while next_report < next_spawn && finished[next_report].is_some() {
+            let (result, ns_elapsed, output, failure) =
+                option::swap_unwrap(&mut finished[next_report]);
+            let desc = copy descs[next_report];
+            if concurrency != 1 {
+                callback(TeWait(copy desc));
+            }
+            callback(TeResult(desc, result, ns_elapsed, output, failure));
+            next_report += 1;
+        }
     }
 
     // All benchmarks run at the end, in serial.
     do vec::consume(filtered_benchs) |_, b| {
         callback(TeWait(copy b.desc));
-        run_test(!opts.run_benchmarks, b, ch.clone());
-        let (test, result) = p.recv();
-        callback(TeResult(move test, result));
+        let start = precise_time_ns();
+        run_test(!opts.run_benchmarks, opts.nocapture, b, 0, ch.clone());
+        let (_, test, result, output, failure) = p.recv();
+        let ns_elapsed = precise_time_ns() - start;
+        callback(TeResult(move test, result, ns_elapsed, output, failure));
+    }
+}
+
+// Tests without their own #[timeout] get the runner-wide one, if any.
+fn with_default_timeout(test: TestDescAndFn,
+                        timeout: Option<uint>) -> TestDescAndFn {
+    if test.desc.timeout.is_some() || timeout.is_none() {
+        return test;
+    }
+    let TestDescAndFn {desc, testfn} = test;
+    TestDescAndFn {
+        desc: TestDesc {timeout: timeout, ..desc},
+        testfn: testfn
     }
 }
 
@@ -497,7 +1278,19 @@ const sched_overcommit : uint = 1;
 #[cfg(unix)]
 const sched_overcommit : uint = 4u;
 
-fn get_concurrency() -> uint {
+fn get_concurrency(opts: &TestOpts) -> uint {
+    match opts.test_threads {
+        Some(n) => return n,
+        None => ()
+    }
+    match os::getenv(~"RUST_TEST_THREADS") {
+        Some(s) => match uint::from_str(s) {
+            Some(n) if n > 0 => return n,
+            _ => fail!(fmt!("RUST_TEST_THREADS is `%s`, should be a \
+                             positive integer", s))
+        },
+        None => ()
+    }
     unsafe {
         let threads = rustrt::rust_sched_threads() as uint;
         if threads == 1 { 1 }
@@ -564,46 +1357,154 @@ struct TestFuture {
     wait: fn@() -> TestResult,
 }
 
+/// Runs `test` and sends its result to `monitor_ch`, tagged with `id`
 pub fn run_test(force_ignore: bool,
+                nocapture: bool,
                 test: TestDescAndFn,
+                id: uint,
                 monitor_ch: SharedChan<MonitorMsg>) {
 
     let TestDescAndFn {desc, testfn} = test;
 
     if force_ignore || desc.ignore {
-        monitor_ch.send((desc, TrIgnored));
+        monitor_ch.send((id, desc, TrIgnored, ~"", None));
         return;
     }
 
     fn run_test_inner(desc: TestDesc,
+                      nocapture: bool,
+                      id: uint,
                       monitor_ch: SharedChan<MonitorMsg>,
                       testfn: ~fn()) {
         let testfn_cell = ::cell::Cell(testfn);
         do task::spawn {
-            let mut result_future = None; // task::future_result(builder);
-            task::task().unlinked().future_result(|+r| {
-                result_future = Some(move r);
-            }).spawn(testfn_cell.take());
-            let task_result = option::unwrap(move result_future).recv();
-            let test_result = calc_result(&desc,
-                                          task_result == task::Success);
-            monitor_ch.send((desc, test_result));
+            let (output_port, output_ch) = stream();
+            let testfn = testfn_cell.take();
+            let testfn_cell =
+                ::cell::Cell(capture_output(testfn, nocapture, output_ch));
+            let test_result = match desc.timeout {
+                None => {
+                    let mut result_future = None;
+                    task::task().unlinked().future_result(|+r| {
+                        result_future = Some(move r);
+                    }).spawn(testfn_cell.take());
+                    let task_result =
+                        option::unwrap(move result_future).recv();
+                    calc_result(&desc, task_result == task::Success)
+                }
+                Some(ms) => {
+                    // The test runs supervised by an unlinked watchdog
+                    // task. If the deadline passes the watchdog fails,
+                    // which takes the test task (and anything it linked
+                    // to) down with it.
+                    let (p, ch) = stream();
+                    let watched = copy desc;
+                    do task::spawn_unlinked {
+                        let mut result_future = None;
+                        task::task().supervised().future_result(|+r| {
+                            result_future = Some(move r);
+                        }).spawn(testfn_cell.take());
+                        let result_port = option::unwrap(move result_future);
+                        match timer::recv_timeout(&uv::global_loop::get(),
+                                                  ms, &result_port) {
+                            Some(task_result) => {
+                                ch.send(calc_result(
+                                    &watched, task_result == task::Success));
+                            }
+                            None => {
+                                ch.send(TrTimedOut(ms));
+                                fail!(fmt!("test %s timed out after %u ms",
+                                           watched.name.to_str(), ms));
+                            }
+                        }
+                    }
+                    p.recv()
+                }
+            };
+            // The capturing wrapper sends the output even when the test
+            // fails, but nothing arrives if the test never got to run. A
+            // test that timed out may still be running, so don't wait on
+            // it at all.
+            let (output, failure) = match test_result {
+                TrTimedOut(_) => (~"", None),
+                _ => match output_port.try_recv() {
+                    Some((move bytes, move failure)) => {
+                        let failure = if failure.is_empty() { None } else {
+                            Some(output_to_str(failure))
+                        };
+                        (output_to_str(bytes), failure)
+                    }
+                    None => (~"", None)
+                }
+            };
+            monitor_ch.send((id, desc, test_result, output, failure));
         }
     }
 
     match testfn {
         DynBenchFn(benchfn) => {
             let bs = ::test::bench::benchmark(benchfn);
-            monitor_ch.send((desc, TrBench(bs)));
+            monitor_ch.send((id, desc, TrBench(bs), ~"", None));
             return;
         }
         StaticBenchFn(benchfn) => {
             let bs = ::test::bench::benchmark(benchfn);
-            monitor_ch.send((desc, TrBench(bs)));
+            monitor_ch.send((id, desc, TrBench(bs), ~"", None));
             return;
         }
-        DynTestFn(f) => run_test_inner(desc, monitor_ch, f),
-        StaticTestFn(f) => run_test_inner(desc, monitor_ch, || f())
+        DynTestFn(f) => {
+            run_test_inner(desc, nocapture, id, monitor_ch, f)
+        }
+        StaticTestFn(f) => {
+            run_test_inner(desc, nocapture, id, monitor_ch, || f())
+        }
+    }
+}
+
+// Sends the buffered output of a test and the message it failed with down
+// its channel when the test task exits, whether or not it failed.
+struct OutputSender {
+    buf: @io::BytesWriter,
+    failure: @io::BytesWriter,
+    ch: Chan<(~[u8], ~[u8])>
+}
+
+impl Drop for OutputSender {
+    fn finalize(&self) {
+        self.ch.send((self.buf.bytes.get(), self.failure.bytes.get()));
+    }
+}
+
+// Wraps a test so that the message it fails with is recorded, and unless
+// `nocapture` is set, what it prints to stdout or stderr or logs is
+// collected instead of going to the console.
+fn capture_output(testfn: ~fn(), nocapture: bool,
+                  output_ch: Chan<(~[u8], ~[u8])>) -> ~fn() {
+    let testfn_cell = ::cell::Cell(testfn);
+    let output_cell = ::cell::Cell(output_ch);
+    fn~() {
+        let buf = @io::BytesWriter();
+        let failure = @io::BytesWriter();
+        let _sender = OutputSender {
+            buf: buf,
+            failure: failure,
+            ch: output_cell.take()
+        };
+        sys::set_task_failure_writer(failure as io::Writer);
+        if !nocapture {
+            io::set_task_stdout(buf as io::Writer);
+            io::set_task_stderr(buf as io::Writer);
+            logging::set_task_logger(buf as io::Writer);
+        }
+        (testfn_cell.take())();
+    }
+}
+
+fn output_to_str(bytes: ~[u8]) -> ~str {
+    if str::is_utf8(bytes) {
+        str::from_bytes(bytes)
+    } else {
+        ~"(output is not valid UTF-8)\n"
     }
 }
 
@@ -788,14 +1689,24 @@ pub mod bench {
 
 #[cfg(test)]
 mod tests {
-    use test::{TrFailed, TrIgnored, TrOk, filter_tests, parse_opts,
+    use test::{TrFailed, TrIgnored, TrOk, TrTimedOut, filter_tests,
+               parse_opts,
                TestDesc, TestDescAndFn,
                StaticTestName, DynTestName, DynTestFn};
-    use test::{TestOpts, run_test};
+    use test::{TestOpts, run_test, JunitFormat, PrettyFormat};
+    use test::{BenchSamples, BenchRegressed, BenchImproved, BenchUnchanged,
+               compare_bench, save_baseline, load_baseline,
+               default_noise_threshold};
+    use tempfile;
 
     use core::either;
+    use core::io::WriterUtil;
+    use core::io;
+    use core::os;
     use core::pipes::{stream, SharedChan};
     use core::option;
+    use core::str;
+    use core::task;
     use core::vec;
 
     #[test]
@@ -805,14 +1716,15 @@ mod tests {
             desc: TestDesc {
                 name: StaticTestName("whatever"),
                 ignore: true,
-                should_fail: false
+                should_fail: false,
+                timeout: option::None
             },
             testfn: DynTestFn(fn~() { f()}),
         };
         let (p, ch) = stream();
         let ch = SharedChan(ch);
-        run_test(false, desc, ch);
-        let (_, res) = p.recv();
+        run_test(false, false, desc, 0, ch);
+        let (_, _, res, _, _) = p.recv();
         assert res != TrOk;
     }
 
@@ -823,14 +1735,15 @@ mod tests {
             desc: TestDesc {
                 name: StaticTestName("whatever"),
                 ignore: true,
-                should_fail: false
+                should_fail: false,
+                timeout: option::None
             },
             testfn: DynTestFn(fn~() { f()}),
         };
         let (p, ch) = stream();
         let ch = SharedChan(ch);
-        run_test(false, desc, ch);
-        let (_, res) = p.recv();
+        run_test(false, false, desc, 0, ch);
+        let (_, _, res, _, _) = p.recv();
         assert res == TrIgnored;
     }
 
@@ -842,14 +1755,15 @@ mod tests {
             desc: TestDesc {
                 name: StaticTestName("whatever"),
                 ignore: false,
-                should_fail: true
+                should_fail: true,
+                timeout: option::None
             },
             testfn: DynTestFn(fn~() { f() }),
         };
         let (p, ch) = stream();
         let ch = SharedChan(ch);
-        run_test(false, desc, ch);
-        let (_, res) = p.recv();
+        run_test(false, false, desc, 0, ch);
+        let (_, _, res, _, _) = p.recv();
         assert res == TrOk;
     }
 
@@ -860,15 +1774,121 @@ mod tests {
             desc: TestDesc {
                 name: StaticTestName("whatever"),
                 ignore: false,
-                should_fail: true
+                should_fail: true,
+                timeout: option::None
+            },
+            testfn: DynTestFn(fn~() { f() }),
+        };
+        let (p, ch) = stream();
+        let ch = SharedChan(ch);
+        run_test(false, false, desc, 0, ch);
+        let (_, _, res, _, _) = p.recv();
+        assert res == TrFailed;
+    }
+
+    #[test]
+    #[ignore(cfg(windows))]
+    pub fn failing_test_output_is_captured() {
+        fn f() {
+            io::println("captured line");
+            io::stderr().write_line("captured error");
+            fail!();
+        }
+        let desc = TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName("whatever"),
+                ignore: false,
+                should_fail: false,
+                timeout: option::None
+            },
+            testfn: DynTestFn(fn~() { f() }),
+        };
+        let (p, ch) = stream();
+        let ch = SharedChan(ch);
+        run_test(false, false, desc, 0, ch);
+        let (_, _, res, output, _) = p.recv();
+        assert res == TrFailed;
+        assert output == ~"captured line\ncaptured error\n";
+    }
+
+    #[test]
+    #[ignore(cfg(windows))]
+    pub fn failure_message_is_reported() {
+        fn f() { fail!(~"out of cheese"); }
+        let desc = TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName("whatever"),
+                ignore: false,
+                should_fail: false,
+                timeout: option::None
             },
             testfn: DynTestFn(fn~() { f() }),
         };
         let (p, ch) = stream();
         let ch = SharedChan(ch);
-        run_test(false, desc, ch);
-        let (_, res) = p.recv();
+        run_test(false, false, desc, 0, ch);
+        let (_, _, res, _, failure) = p.recv();
         assert res == TrFailed;
+        let failure = option::unwrap(failure);
+        assert str::starts_with(failure, "out of cheese, ");
+    }
+
+    #[test]
+    pub fn parse_nocapture_flag() {
+        let args = ~[~"progname", ~"--nocapture"];
+        let opts = match parse_opts(args) {
+          either::Left(copy o) => o,
+          _ => fail!(~"Malformed arg in parse_nocapture_flag")
+        };
+        assert opts.nocapture;
+    }
+
+    #[test]
+    pub fn hung_test_times_out() {
+        fn f() { loop { task::yield(); } }
+        let desc = TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName("whatever"),
+                ignore: false,
+                should_fail: false,
+                timeout: option::Some(100)
+            },
+            testfn: DynTestFn(fn~() { f() }),
+        };
+        let (p, ch) = stream();
+        let ch = SharedChan(ch);
+        run_test(false, false, desc, 0, ch);
+        let (_, _, res, _, _) = p.recv();
+        assert res == TrTimedOut(100);
+    }
+
+    #[test]
+    pub fn quick_test_beats_its_timeout() {
+        fn f() { }
+        let desc = TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName("whatever"),
+                ignore: false,
+                should_fail: false,
+                timeout: option::Some(10000)
+            },
+            testfn: DynTestFn(fn~() { f() }),
+        };
+        let (p, ch) = stream();
+        let ch = SharedChan(ch);
+        run_test(false, false, desc, 0, ch);
+        let (_, _, res, _, _) = p.recv();
+        assert res == TrOk;
+    }
+
+    #[test]
+    pub fn parse_timeout_option() {
+        let args = ~[~"progname", ~"--timeout", ~"2500"];
+        let opts = match parse_opts(args) {
+          either::Left(copy o) => o,
+          _ => fail!(~"Malformed arg in parse_timeout_option")
+        };
+        assert opts.test_timeout == Some(2500);
     }
 
     #[test]
@@ -891,6 +1911,78 @@ mod tests {
         assert (opts.run_ignored);
     }
 
+    #[test]
+    pub fn parse_test_threads_option() {
+        let args = ~[~"progname", ~"--test-threads", ~"3"];
+        let opts = match parse_opts(args) {
+          either::Left(copy o) => o,
+          _ => fail!(~"Malformed arg in parse_test_threads_option")
+        };
+        assert opts.test_threads == Some(3);
+    }
+
+    #[test]
+    pub fn reject_zero_test_threads() {
+        let args = ~[~"progname", ~"--test-threads", ~"0"];
+        assert either::is_right(&parse_opts(args));
+    }
+
+    #[test]
+    pub fn parse_format_option() {
+        let args = ~[~"progname", ~"--format", ~"junit"];
+        let opts = match parse_opts(args) {
+          either::Left(copy o) => o,
+          _ => fail!(~"Malformed arg in parse_format_option")
+        };
+        assert opts.format == JunitFormat;
+
+        let args = ~[~"progname", ~"--format", ~"yaml"];
+        assert either::is_right(&parse_opts(args));
+    }
+
+    fn samples(base: f64) -> BenchSamples {
+        BenchSamples {
+            ns_iter_samples: vec::from_fn(50, |i| base + ((i % 7) as f64)),
+            mb_s: 0
+        }
+    }
+
+    #[test]
+    pub fn compare_bench_flags_regressions() {
+        let c = compare_bench(~"b", &samples(100.0), &samples(200.0), 5.0);
+        assert c.change == BenchRegressed;
+        assert c.pct_change > 90.0;
+
+        let c = compare_bench(~"b", &samples(200.0), &samples(100.0), 5.0);
+        assert c.change == BenchImproved;
+    }
+
+    #[test]
+    pub fn compare_bench_ignores_changes_within_noise() {
+        let c = compare_bench(~"b", &samples(100.0), &samples(102.0), 5.0);
+        assert c.change == BenchUnchanged;
+
+        let c = compare_bench(~"b", &samples(100.0), &samples(100.0), 0.0);
+        assert c.change == BenchUnchanged;
+    }
+
+    #[test]
+    pub fn baseline_round_trips() {
+        let dir = tempfile::mkdtemp(&os::tmpdir(), ~"baseline").get();
+        let path = dir.push(~"bench.json");
+        let benches = ~[(~"a::b", samples(10.0)), (~"c", samples(20.0))];
+        assert save_baseline(&path, benches).is_ok();
+        let loaded = match load_baseline(&path) {
+            Ok(move b) => b,
+            Err(ref e) => fail!(copy *e)
+        };
+        assert loaded.len() == 2;
+        assert *loaded.get(&~"a::b") == samples(10.0);
+        assert *loaded.get(&~"c") == samples(20.0);
+        os::remove_file(&path);
+        os::remove_dir(&dir);
+    }
+
     #[test]
     pub fn filter_for_ignored_option() {
         fn dummy() {}
@@ -905,7 +1997,13 @@ mod tests {
             run_tests: true,
             run_benchmarks: false,
             save_results: option::None,
-            compare_results: option::None
+            compare_results: option::None,
+            test_threads: option::None,
+            format: PrettyFormat,
+            test_timeout: option::None,
+            noise_threshold: default_noise_threshold,
+            fail_on_regression: false,
+            nocapture: false
         };
 
         let tests = ~[
@@ -914,6 +2012,7 @@ mod tests {
                     name: StaticTestName("1"),
                     ignore: true,
                     should_fail: false,
+                    timeout: option::None
                 },
                 testfn: DynTestFn(fn~() { }),
             },
@@ -921,7 +2020,8 @@ mod tests {
                 desc: TestDesc {
                     name: StaticTestName("2"),
                     ignore: false,
-                    should_fail: false
+                    should_fail: false,
+                    timeout: option::None
                 },
                 testfn: DynTestFn(fn~() { }),
             },
@@ -942,7 +2042,13 @@ mod tests {
             run_tests: true,
             run_benchmarks: false,
             save_results: option::None,
-            compare_results: option::None
+            compare_results: option::None,
+            test_threads: option::None,
+            format: PrettyFormat,
+            test_timeout: option::None,
+            noise_threshold: default_noise_threshold,
+            fail_on_regression: false,
+            nocapture: false
         };
 
         let names =
@@ -961,7 +2067,8 @@ mod tests {
                     desc: TestDesc {
                         name: DynTestName(*name),
                         ignore: false,
-                        should_fail: false
+                        should_fail: false,
+                        timeout: option::None
                     },
                     testfn: DynTestFn(copy testfn),
                 };
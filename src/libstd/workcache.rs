@@ -14,16 +14,23 @@ use json;
 use sha1;
 use serialize::{Encoder, Encodable, Decoder, Decodable};
 use sort;
+use time;
+use timer;
+use uv;
 
-use core::cmp;
 use core::dvec;
 use core::either::{Either, Left, Right};
+use core::i64;
 use core::io;
+use core::io::WriterUtil;
+use core::libc;
 use core::option;
+use core::os;
 use core::pipes::{recv, oneshot, PortOne, send_one};
 use core::prelude::*;
 use core::result;
 use core::run;
+use core::str;
 use core::hashmap::linear::LinearMap;
 use core::task;
 use core::to_bytes;
@@ -97,6 +104,7 @@ use core::mutable::Mut;
 */
 
 #[deriving_eq]
+#[deriving_ord]
 #[auto_encode]
 #[auto_decode]
 struct WorkKey {
@@ -114,23 +122,6 @@ impl to_bytes::IterBytes for WorkKey {
     }
 }
 
-impl cmp::Ord for WorkKey {
-    pure fn lt(&self, other: &WorkKey) -> bool {
-        self.kind < other.kind ||
-            (self.kind == other.kind &&
-             self.name < other.name)
-    }
-    pure fn le(&self, other: &WorkKey) -> bool {
-        self.lt(other) || self.eq(other)
-    }
-    pure fn ge(&self, other: &WorkKey) -> bool {
-        self.gt(other) || self.eq(other)
-    }
-    pure fn gt(&self, other: &WorkKey) -> bool {
-        ! self.le(other)
-    }
-}
-
 impl WorkKey {
     static fn new(kind: &str, name: &str) -> WorkKey {
     WorkKey { kind: kind.to_owned(), name: name.to_owned() }
@@ -168,7 +159,199 @@ struct Database {
     mut db_dirty: bool
 }
 
+// How long to wait for another process to release the database lock. A
+// lock file older than this was left behind by a process that died.
+const lock_timeout_ms: uint = 10000;
+const lock_retry_ms: uint = 50;
+
+/**
+ * Exclusive ownership of a database's lock file, which is created with
+ * O_EXCL so that only one process at a time can hold it. The file holds
+ * the pid of its owner and is removed again when the lock goes out of
+ * scope.
+ */
+struct DbLock {
+    lock_path: Path
+}
+
+impl Drop for DbLock {
+    fn finalize(&self) {
+        os::remove_file(&self.lock_path);
+    }
+}
+
+impl DbLock {
+    static fn acquire(db_filename: &Path) -> Result<DbLock, ~str> {
+        DbLock::acquire_within(db_filename, lock_timeout_ms)
+    }
+
+    // Waits up to `timeout_ms` for the lock, and takes it over if the lock
+    // file is older than that and its holder hasn't changed meanwhile
+    static fn acquire_within(db_filename: &Path,
+                             timeout_ms: uint) -> Result<DbLock, ~str> {
+        let lock_path = db_filename.with_filetype(~"lock");
+        let flags = (libc::O_WRONLY | libc::O_CREAT | libc::O_EXCL)
+            as libc::c_int;
+        let mode = (libc::S_IRUSR | libc::S_IWUSR) as libc::c_int;
+        let mut waited = 0;
+        loop {
+            let fd = unsafe {
+                do os::as_c_charp(lock_path.to_str()) |buf| {
+                    libc::open(buf, flags, mode)
+                }
+            };
+            if fd >= 0 {
+                let w = io::fd_writer(fd, true);
+                w.write_line(fmt!("%d", unsafe { libc::getpid() } as int));
+                return Ok(DbLock { lock_path: lock_path });
+            }
+            if lock_is_stale(&lock_path, timeout_ms) {
+                let broken = match io::read_whole_file_str(&lock_path) {
+                    Ok(ref holder) => break_stale_lock(&lock_path, *holder),
+                    Err(_) => false
+                };
+                if broken { loop; }
+            }
+            if waited >= timeout_ms {
+                return Err(fmt!("timed out waiting for lock file %s%s; \
+                                 delete it if no other process is using \
+                                 the database",
+                                lock_path.to_str(), lock_holder(&lock_path)));
+            }
+            timer::sleep(&uv::global_loop::get(), lock_retry_ms);
+            waited += lock_retry_ms;
+        }
+    }
+}
+
+// Whether the lock file at `path` has been there for longer than anyone
+// waits for it, so that its owner must have died without removing it
+fn lock_is_stale(path: &Path, timeout_ms: uint) -> bool {
+    match modified_time(path) {
+        Some((secs, _)) => {
+            (time::get_time().sec - secs) * 1000 >= timeout_ms as i64
+        }
+        None => false
+    }
+}
+
+// Removes the stale lock file at `path`, provided it still holds
+// `holder`. Another process may have broken the lock and taken it in the
+// meantime, so the file is moved out of the way before it is checked,
+// and put back if it turns out to be that process's lock.
+fn break_stale_lock(path: &Path, holder: &str) -> bool {
+    let aside = path.with_filetype(
+        fmt!("stale-%d", unsafe { libc::getpid() } as int));
+    if !os::rename_file(path, &aside) {
+        return false;
+    }
+    match io::read_whole_file_str(&aside) {
+        Ok(ref s) if str::eq_slice(*s, holder) => {
+            os::remove_file(&aside);
+            true
+        }
+        _ => {
+            os::rename_file(&aside, path);
+            false
+        }
+    }
+}
+
+// Who holds the lock file at `path`, for error messages
+fn lock_holder(path: &Path) -> ~str {
+    match io::read_whole_file_str(path) {
+        Ok(ref s) if !str::trim(*s).is_empty() => {
+            fmt!(" (held by process %s)", str::trim(*s))
+        }
+        _ => ~""
+    }
+}
+
 impl Database {
+    /**
+     * Opens the database stored at `p`, which need not exist yet. A
+     * database that can't be read is treated as empty, and is replaced
+     * the next time the cache is saved.
+     */
+    static fn new(p: Path) -> Database {
+        let mut db = Database { db_filename: p,
+                                db_cache: LinearMap::new(),
+                                db_dirty: false };
+        match db.load() {
+            Ok(()) => (),
+            Err(ref e) => {
+                error!("workcache: %s; starting from an empty cache", *e)
+            }
+        }
+        db
+    }
+
+    // Reads the entries stored on disk into the cache. Entries already in
+    // the cache win, so that this can merge in the work of other writers
+    // without losing our own.
+    fn load(&mut self) -> Result<(), ~str> {
+        if !os::path_exists(&self.db_filename) {
+            return Ok(());
+        }
+        let s = match io::read_whole_file_str(&self.db_filename) {
+            Ok(move s) => s,
+            Err(move e) => return Err(e)
+        };
+        let j = match json::from_str(s) {
+            Ok(move j) => j,
+            Err(ref e) => {
+                return Err(fmt!("can't parse %s: %s",
+                                self.db_filename.to_str(), e.to_str()))
+            }
+        };
+        let entries: ~[(~str, ~str)] =
+            Decodable::decode(&json::Decoder(move j));
+        for entries.each |&(k, v)| {
+            if !self.db_cache.contains_key(&k) {
+                self.db_cache.insert(copy k, copy v);
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * Writes the cache back to disk if anything was added to it. Holds
+     * the lock file while it merges in whatever other processes saved in
+     * the meantime, then replaces the database file atomically.
+     */
+    fn save(&mut self) -> Result<(), ~str> {
+        if !self.db_dirty {
+            return Ok(());
+        }
+        let _lock = match DbLock::acquire(&self.db_filename) {
+            Ok(move l) => l,
+            Err(move e) => return Err(e)
+        };
+        match self.load() {
+            Ok(()) => (),
+            Err(ref e) => error!("workcache: %s; overwriting it", *e)
+        }
+
+        let mut entries = ~[];
+        for self.db_cache.each |&(k, v)| {
+            entries.push((copy *k, copy *v));
+        }
+        sort::tim_sort(entries);
+
+        let tmp_path = self.db_filename.with_filetype(~"tmp");
+        match io::file_writer(&tmp_path, [io::Create, io::Truncate]) {
+            Ok(w) => entries.encode(&json::Encoder(w)),
+            Err(move e) => return Err(e)
+        }
+        if !os::rename_file(&tmp_path, &self.db_filename) {
+            return Err(fmt!("can't replace %s: %s",
+                            self.db_filename.to_str(),
+                            os::last_os_error()));
+        }
+        self.db_dirty = false;
+        Ok(())
+    }
+
     fn prepare(&mut self, fn_name: &str,
                declared_inputs: &WorkMap) -> Option<(WorkMap, WorkMap, ~str)>
     {
@@ -194,14 +377,69 @@ impl Database {
     }
 }
 
+/**
+ * Records the decisions the workcache makes, most importantly why each
+ * prep was considered fresh or had to be re-run. Messages are kept in
+ * order and optionally echoed to a writer as they arrive.
+ */
 struct Logger {
-    // FIXME #4432: Fill in
-    a: ()
+    out: Option<io::Writer>,
+    mut entries: ~[~str]
 }
 
 impl Logger {
-    fn info(i: &str) {
-        io::println(~"workcache: " + i.to_owned());
+    /// A logger that only records messages
+    static fn new() -> Logger {
+        Logger { out: None, entries: ~[] }
+    }
+
+    /// A logger that also writes every message to `w`
+    static fn new_with_writer(w: io::Writer) -> Logger {
+        Logger { out: Some(w), entries: ~[] }
+    }
+
+    fn info(&self, i: &str) {
+        match self.out {
+            Some(w) => w.write_line(~"workcache: " + i.to_owned()),
+            None => ()
+        }
+        self.entries.push(i.to_owned());
+    }
+
+    /// Everything logged so far, oldest first
+    fn entries(&self) -> ~[~str] {
+        copy self.entries
+    }
+}
+
+/// Decides whether the work `name`, last seen with value `val`, is fresh
+type FreshnessFn = @fn(name: &str, val: &str) -> bool;
+
+/**
+ * Maps each kind of work (the `kind` of a `WorkKey`) to the function that
+ * checks works of that kind for freshness. Works of a kind nobody
+ * registered a check for are always considered stale.
+ */
+struct Freshness {
+    checks: LinearMap<~str, FreshnessFn>
+}
+
+impl Freshness {
+    static fn new() -> Freshness {
+        Freshness { checks: LinearMap::new() }
+    }
+
+    /// Registers `f` as the check for `kind`, replacing any previous one
+    fn register(&mut self, kind: &str, f: FreshnessFn) {
+        self.checks.insert(kind.to_owned(), f);
+    }
+
+    /// Runs the check for `kind`, or returns None if there is none
+    fn check(&self, kind: &str, name: &str, val: &str) -> Option<bool> {
+        match self.checks.find(&kind.to_owned()) {
+            Some(f) => Some((*f)(name, val)),
+            None => None
+        }
     }
 }
 
@@ -209,7 +447,7 @@ struct Context {
     db: @Mut<Database>,
     logger: @Mut<Logger>,
     cfg: @json::Object,
-    freshness: LinearMap<~str,@fn(&str,&str)->bool>
+    freshness: Freshness
 }
 
 struct Prep {
@@ -254,12 +492,87 @@ fn digest_file(path: &Path) -> ~str {
     sha.result_str()
 }
 
+// The modification time of `path` in seconds and nanoseconds, on the
+// platforms where Path can tell
+#[cfg(target_os = "freebsd")]
+#[cfg(target_os = "linux")]
+#[cfg(target_os = "macos")]
+#[cfg(target_os = "win32")]
+fn modified_time(path: &Path) -> Option<(i64, int)> {
+    path.get_mtime()
+}
+
+#[cfg(target_os = "android")]
+fn modified_time(_path: &Path) -> Option<(i64, int)> {
+    None
+}
+
+fn file_mtime(path: &Path) -> Option<~str> {
+    match modified_time(path) {
+        Some((secs, nsecs)) => Some(fmt!("%s.%d", i64::to_str(secs), nsecs)),
+        None => None
+    }
+}
+
+/**
+ * The value to declare for a `file` work: the file's modification time
+ * and a sha1 of its contents, as `mtime:sha1`. When checking freshness
+ * an unchanged mtime is trusted, and only a changed one costs a re-hash.
+ */
+fn file_value(path: &Path) -> ~str {
+    let mtime = match file_mtime(path) {
+        Some(move m) => m,
+        None => ~""
+    };
+    mtime + ~":" + digest_file(path)
+}
+
+// Freshness check for `file` works, whose values come from `file_value`.
+// A bare sha1 without the mtime part is accepted too.
+fn file_is_fresh(name: &str, val: &str) -> bool {
+    let path = Path(name);
+    if !os::path_exists(&path) {
+        return false;
+    }
+    let (mtime, sha) = match str::find_char(val, ':') {
+        Some(i) => (str::slice(val, 0, i), str::slice(val, i + 1, val.len())),
+        None => (~"", val.to_owned())
+    };
+    if !mtime.is_empty() && file_mtime(&path) == Some(mtime) {
+        return true;
+    }
+    digest_file(&path) == sha
+}
+
+// Freshness check for `cfg` works: the value is the JSON text of the
+// named configuration entry, so it is fresh while the entry is unchanged.
+fn cfg_is_fresh(cfg: &json::Object, name: &str, val: &str) -> bool {
+    match cfg.find(&name.to_owned()) {
+        Some(j) => json::to_str(j) == val.to_owned(),
+        None => false
+    }
+}
+
 impl Context {
 
+    /// A context that knows how to check `file` and `cfg` works
     static fn new(db: @Mut<Database>,
                   lg: @Mut<Logger>,
                   cfg: @json::Object) -> Context {
-        Context{db: db, logger: lg, cfg: cfg, freshness: LinearMap::new()}
+        let mut freshness = Freshness::new();
+        freshness.register("file", |name, val| file_is_fresh(name, val));
+        freshness.register("cfg", |name, val| cfg_is_fresh(cfg, name, val));
+        Context{db: db, logger: lg, cfg: cfg, freshness: freshness}
+    }
+
+    /// Adds or replaces the freshness check for works of kind `kind`
+    fn register_freshness(&mut self, kind: &str, f: FreshnessFn) {
+        self.freshness.register(kind, f);
+    }
+
+    /// The value to declare for the `cfg` work `name`, if there is one
+    fn cfg_value(&self, name: &str) -> Option<~str> {
+        self.cfg.find(&name.to_owned()).map(|j| json::to_str(*j))
     }
 
     fn prep<T:Owned
@@ -296,15 +609,24 @@ impl TPrep for @Mut<Prep> {
     fn is_fresh(&self, cat: &str, kind: &str,
                 name: &str, val: &str) -> bool {
         do self.borrow_imm |p| {
-            let k = kind.to_owned();
-            let f = (*p.ctxt.freshness.get(&k))(name, val);
+            let f = match p.ctxt.freshness.check(kind, name, val) {
+                Some(fresh) => fresh,
+                None => {
+                    do p.ctxt.logger.borrow_imm |lg| {
+                        lg.info(fmt!("%s: no freshness check for kind %s",
+                                     p.fn_name, kind));
+                    }
+                    false
+                }
+            };
             do p.ctxt.logger.borrow_imm |lg| {
                 if f {
-                    lg.info(fmt!("%s %s:%s is fresh",
-                                 cat, kind, name));
+                    lg.info(fmt!("%s: %s %s:%s is fresh",
+                                 p.fn_name, cat, kind, name));
                 } else {
-                    lg.info(fmt!("%s %s:%s is not fresh",
-                                 cat, kind, name))
+                    lg.info(fmt!("%s: %s %s:%s is not fresh \
+                                  (recorded as %s)",
+                                 p.fn_name, cat, kind, name, val))
                 }
             }
             f
@@ -332,16 +654,30 @@ impl TPrep for @Mut<Prep> {
                 db.prepare(p.fn_name, &p.declared_inputs)
             };
 
+            if cached.is_none() {
+                do p.ctxt.logger.borrow_imm |lg| {
+                    lg.info(fmt!("%s: stale, nothing cached for these \
+                                  declared inputs", p.fn_name));
+                }
+            }
+
             match move cached {
                 Some((ref disc_in, ref disc_out, ref res))
                 if self.all_fresh("declared input",
                                   &p.declared_inputs) &&
                 self.all_fresh("discovered input", disc_in) &&
                 self.all_fresh("discovered output", disc_out) => {
+                    do p.ctxt.logger.borrow_imm |lg| {
+                        lg.info(fmt!("%s: fresh, using cached result",
+                                     p.fn_name));
+                    }
                     Work::new(*self, move Left(json_decode(*res)))
                 }
 
                 _ => {
+                    do p.ctxt.logger.borrow_imm |lg| {
+                        lg.info(fmt!("%s: running", p.fn_name));
+                    }
                     let (chan, port) = oneshot::init();
                     let mut blk = None;
                     blk <-> bo;
@@ -399,6 +735,10 @@ fn unwrap<T:Owned
                              &exe.discovered_inputs,
                              &exe.discovered_outputs,
                              s);
+                    match db.save() {
+                        Ok(()) => (),
+                        Err(ref e) => fail!(fmt!("workcache: %s", *e))
+                    }
                 }
             }
             move v
@@ -410,26 +750,189 @@ fn unwrap<T:Owned
 fn test() {
     use io::WriterUtil;
 
-    let db = @Mut(Database { db_filename: Path("db.json"),
-                             db_cache: LinearMap::new(),
-                             db_dirty: false });
-    let lg = @Mut(Logger { a: () });
+    let dir = ::tempfile::mkdtemp(&os::tmpdir(), ~"workcache").get();
+    let db_path = dir.push(~"db.json");
+    let src = dir.push(~"foo.c");
+    let out = dir.push(~"foo.o");
+
+    let db = @Mut(Database::new(copy db_path));
+    let lg = @Mut(Logger::new_with_writer(io::stdout()));
     let cfg = @LinearMap::new();
     let cx = @Context::new(db, lg, cfg);
     let w:Work<~str> = do cx.prep("test1") |prep| {
-        let pth = Path("foo.c");
         {
-            let file = io::file_writer(&pth, [io::Create]).get();
+            let file = io::file_writer(&src, [io::Create]).get();
             file.write_str("int main() { return 0; }");
         }
 
-        prep.declare_input("file", pth.to_str(), digest_file(&pth));
+        prep.declare_input("file", src.to_str(), file_value(&src));
+        let src = src.to_str();
+        let out = out.to_str();
         do prep.exec |_exe| {
-            let out = Path("foo.o");
-            run::run_program("gcc", [~"foo.c", ~"-o", out.to_str()]);
-            move out.to_str()
+            run::run_program("gcc", [copy src, ~"-o", copy out]);
+            copy out
         }
     };
     let s = unwrap(move w);
     io::println(s);
+
+    os::remove_file(&src);
+    os::remove_file(&out);
+    os::remove_file(&db_path);
+    os::remove_dir(&dir);
+}
+
+#[test]
+fn test_db_save_and_load() {
+    let dir = ::tempfile::mkdtemp(&os::tmpdir(), ~"workcache").get();
+    let path = dir.push(~"db.json");
+    let mut inputs = LinearMap::new();
+    inputs.insert(WorkKey::new("file", "foo.c"), ~"abc123");
+    let none: WorkMap = LinearMap::new();
+
+    {
+        let mut db = Database::new(copy path);
+        db.cache("compile", &inputs, &none, &none, "\"foo.o\"");
+        assert db.save().is_ok();
+        assert !db.db_dirty;
+    }
+    assert !os::path_exists(&path.with_filetype(~"lock"));
+
+    let mut db = Database::new(copy path);
+    match db.prepare("compile", &inputs) {
+        Some((_, _, ref res)) => assert *res == ~"\"foo.o\"",
+        None => fail!(~"entry was not saved")
+    }
+    assert db.prepare("link", &inputs).is_none();
+
+    os::remove_file(&path);
+    os::remove_dir(&dir);
+}
+
+#[test]
+fn test_stale_lock_is_broken() {
+    let dir = ::tempfile::mkdtemp(&os::tmpdir(), ~"workcache").get();
+    let path = dir.push(~"db.json");
+    let lock_path = path.with_filetype(~"lock");
+    {
+        let file = io::file_writer(&lock_path, [io::Create]).get();
+        file.write_str("12345\n");
+    }
+
+    // Any existing lock file is too old when nobody waits for it
+    {
+        let _lock = result::unwrap(DbLock::acquire_within(&path, 0));
+        let holder = io::read_whole_file_str(&lock_path).get();
+        assert holder != ~"12345\n";
+    }
+    assert !os::path_exists(&lock_path);
+
+    os::remove_dir(&dir);
+}
+
+#[test]
+fn test_changed_lock_is_not_broken() {
+    let dir = ::tempfile::mkdtemp(&os::tmpdir(), ~"workcache").get();
+    let lock_path = dir.push(~"db.lock");
+    {
+        let file = io::file_writer(&lock_path, [io::Create]).get();
+        file.write_str("54321\n");
+    }
+
+    // Someone else took the lock over since it was seen holding 12345
+    assert !break_stale_lock(&lock_path, "12345\n");
+    assert io::read_whole_file_str(&lock_path).get() == ~"54321\n";
+
+    assert break_stale_lock(&lock_path, "54321\n");
+    assert !os::path_exists(&lock_path);
+
+    os::remove_dir(&dir);
+}
+
+#[test]
+fn test_corrupt_db_is_replaced() {
+    let dir = ::tempfile::mkdtemp(&os::tmpdir(), ~"workcache").get();
+    let path = dir.push(~"db.json");
+    {
+        let file = io::file_writer(&path, [io::Create]).get();
+        file.write_str("{ not json");
+    }
+    let none: WorkMap = LinearMap::new();
+
+    let mut db = Database::new(copy path);
+    assert db.db_cache.is_empty();
+    db.cache("a", &none, &none, &none, "1");
+    assert db.save().is_ok();
+
+    let mut db = Database::new(copy path);
+    assert db.prepare("a", &none).is_some();
+
+    os::remove_file(&path);
+    os::remove_dir(&dir);
+}
+
+#[test]
+fn test_db_save_merges_other_writers() {
+    let dir = ::tempfile::mkdtemp(&os::tmpdir(), ~"workcache").get();
+    let path = dir.push(~"db.json");
+    let none: WorkMap = LinearMap::new();
+
+    let mut a = Database::new(copy path);
+    let mut b = Database::new(copy path);
+    a.cache("a", &none, &none, &none, "1");
+    b.cache("b", &none, &none, &none, "2");
+    assert a.save().is_ok();
+    assert b.save().is_ok();
+
+    let mut db = Database::new(copy path);
+    assert db.prepare("a", &none).is_some();
+    assert db.prepare("b", &none).is_some();
+
+    os::remove_file(&path);
+    os::remove_dir(&dir);
+}
+
+#[test]
+fn test_file_freshness() {
+    let dir = ::tempfile::mkdtemp(&os::tmpdir(), ~"workcache").get();
+    let path = dir.push(~"input.txt");
+    {
+        let file = io::file_writer(&path, [io::Create]).get();
+        file.write_str("one");
+    }
+    let val = file_value(&path);
+    assert file_is_fresh(path.to_str(), val);
+    assert file_is_fresh(path.to_str(), digest_file(&path));
+
+    {
+        let file = io::file_writer(&path, [io::Truncate]).get();
+        file.write_str("two");
+    }
+    // A stale mtime falls back to comparing contents
+    assert !file_is_fresh(path.to_str(), ~"0.0:" + digest_file(&path) + ~"x");
+    assert file_is_fresh(path.to_str(), ~"0.0:" + digest_file(&path));
+
+    os::remove_file(&path);
+    assert !file_is_fresh(path.to_str(), val);
+    os::remove_dir(&dir);
+}
+
+#[test]
+fn test_cfg_freshness_and_registry() {
+    let mut cfg = LinearMap::new();
+    cfg.insert(~"os", json::String(~"linux"));
+    let db = @Mut(Database { db_filename: Path("unused.json"),
+                             db_cache: LinearMap::new(),
+                             db_dirty: false });
+    let lg = @Mut(Logger::new());
+    let mut cx = Context::new(db, lg, @cfg);
+
+    let os_val = cx.cfg_value("os").get();
+    assert cx.freshness.check("cfg", "os", os_val) == Some(true);
+    assert cx.freshness.check("cfg", "os", "\"macos\"") == Some(false);
+    assert cx.freshness.check("cfg", "arch", os_val) == Some(false);
+    assert cx.freshness.check("url", "x", "etag").is_none();
+
+    cx.register_freshness("url", |_name, val| val == "etag");
+    assert cx.freshness.check("url", "x", "etag") == Some(true);
 }
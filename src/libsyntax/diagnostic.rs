@@ -20,20 +20,49 @@ use core::option;
 use core::str;
 use core::vec;
 use core::dvec::DVec;
+use core::hashmap::linear::LinearMap;
 
+use std::json;
+use std::sort;
 use std::term;
 
+/// A secondary span of a diagnostic, labelled with what happens there,
+/// e.g. "first borrow here"
+pub struct SpanLabel {
+    span: span,
+    label: ~str
+}
+
+/**
+ * A mechanical fix for a diagnostic: replace the source text of `span`
+ * with `replacement`, which is empty to delete it. Only suggestions that
+ * are `machine_applicable` are known to be right in every case, and only
+ * those are applied by `rustc --apply-fixes`.
+ */
+pub struct Suggestion {
+    span: span,
+    replacement: ~str,
+    machine_applicable: bool
+}
+
+/// Prints a diagnostic. `code` is its error code, e.g. `E0001`, if it has
+/// one; `labels` are its secondary spans.
 pub type Emitter = fn@(cmsp: Option<(@codemap::CodeMap, span)>,
-                   msg: &str, lvl: level);
+                   msg: &str, lvl: level, code: Option<~str>,
+                   labels: &[SpanLabel]);
 
 
 pub trait span_handler {
     fn span_fatal(@mut self, sp: span, msg: &str) -> !;
     fn span_err(@mut self, sp: span, msg: &str);
+    fn span_err_with_code(@mut self, sp: span, msg: &str, code: &str);
+    fn span_err_labelled(@mut self, sp: span, msg: &str,
+                         code: Option<~str>, labels: &[SpanLabel]);
     fn span_warn(@mut self, sp: span, msg: &str);
     fn span_note(@mut self, sp: span, msg: &str);
     fn span_bug(@mut self, sp: span, msg: &str) -> !;
     fn span_unimpl(@mut self, sp: span, msg: &str) -> !;
+    fn span_suggest(@mut self, suggestion: Suggestion);
     fn handler(@mut self) -> handler;
 }
 
@@ -51,11 +80,21 @@ pub trait handler {
             cmsp: Option<(@codemap::CodeMap, span)>,
             msg: &str,
             lvl: level);
+    fn emit_labelled(@mut self,
+                     cmsp: Option<(@codemap::CodeMap, span)>,
+                     msg: &str,
+                     lvl: level,
+                     code: Option<~str>,
+                     labels: &[SpanLabel]);
+    // The fixes suggested so far, for --apply-fixes
+    fn add_suggestion(@mut self, suggestion: Suggestion);
+    fn suggestions(@mut self) -> ~[Suggestion];
 }
 
 struct HandlerT {
     err_count: uint,
     emit: Emitter,
+    suggestions: ~[Suggestion]
 }
 
 struct CodemapT {
@@ -72,6 +111,15 @@ impl span_handler for CodemapT {
         self.handler.emit(Some((self.cm, sp)), msg, error);
         self.handler.bump_err_count();
     }
+    fn span_err_with_code(@mut self, sp: span, msg: &str, code: &str) {
+        self.span_err_labelled(sp, msg, Some(code.to_owned()), []);
+    }
+    fn span_err_labelled(@mut self, sp: span, msg: &str,
+                         code: Option<~str>, labels: &[SpanLabel]) {
+        self.handler.emit_labelled(Some((self.cm, sp)), msg, error, code,
+                                   labels);
+        self.handler.bump_err_count();
+    }
     fn span_warn(@mut self, sp: span, msg: &str) {
         self.handler.emit(Some((self.cm, sp)), msg, warning);
     }
@@ -84,6 +132,15 @@ impl span_handler for CodemapT {
     fn span_unimpl(@mut self, sp: span, msg: &str) -> ! {
         self.span_bug(sp, ~"unimplemented " + msg);
     }
+    fn span_suggest(@mut self, suggestion: Suggestion) {
+        let msg = if suggestion.replacement.is_empty() {
+            ~"suggestion: remove this"
+        } else {
+            fmt!("suggestion: replace with `%s`", suggestion.replacement)
+        };
+        self.handler.emit(Some((self.cm, suggestion.span)), msg, note);
+        self.handler.add_suggestion(suggestion);
+    }
     fn handler(@mut self) -> handler {
         self.handler
     }
@@ -91,11 +148,11 @@ impl span_handler for CodemapT {
 
 impl handler for HandlerT {
     fn fatal(@mut self, msg: &str) -> ! {
-        (self.emit)(None, msg, fatal);
+        (self.emit)(None, msg, fatal, None, []);
         fail!();
     }
     fn err(@mut self, msg: &str) {
-        (self.emit)(None, msg, error);
+        (self.emit)(None, msg, error, None, []);
         self.bump_err_count();
     }
     fn bump_err_count(@mut self) {
@@ -115,10 +172,10 @@ impl handler for HandlerT {
         self.fatal(s);
     }
     fn warn(@mut self, msg: &str) {
-        (self.emit)(None, msg, warning);
+        (self.emit)(None, msg, warning, None, []);
     }
     fn note(@mut self, msg: &str) {
-        (self.emit)(None, msg, note);
+        (self.emit)(None, msg, note, None, []);
     }
     fn bug(@mut self, msg: &str) -> ! {
         self.fatal(ice_msg(msg));
@@ -130,7 +187,21 @@ impl handler for HandlerT {
             cmsp: Option<(@codemap::CodeMap, span)>,
             msg: &str,
             lvl: level) {
-        (self.emit)(cmsp, msg, lvl);
+        (self.emit)(cmsp, msg, lvl, None, []);
+    }
+    fn emit_labelled(@mut self,
+                     cmsp: Option<(@codemap::CodeMap, span)>,
+                     msg: &str,
+                     lvl: level,
+                     code: Option<~str>,
+                     labels: &[SpanLabel]) {
+        (self.emit)(cmsp, msg, lvl, code, labels);
+    }
+    fn add_suggestion(@mut self, suggestion: Suggestion) {
+        self.suggestions.push(suggestion);
+    }
+    fn suggestions(@mut self) -> ~[Suggestion] {
+        copy self.suggestions
     }
 }
 
@@ -147,12 +218,15 @@ pub fn mk_handler(emitter: Option<Emitter>) -> @handler {
     let emit: Emitter = match emitter {
         Some(e) => e,
         None => {
-            let emit: Emitter = |cmsp, msg, t| emit(cmsp, msg, t);
+            let emit: Emitter = |cmsp, msg, t, code, labels| {
+                emit(cmsp, msg, t, code, labels)
+            };
             emit
         }
     };
 
-    @mut HandlerT { mut err_count: 0, emit: emit } as @handler
+    @mut HandlerT { mut err_count: 0, emit: emit, mut suggestions: ~[] }
+        as @handler
 }
 
 #[deriving_eq]
@@ -197,22 +271,63 @@ fn print_diagnostic(topic: ~str, lvl: level, msg: &str) {
     io::stderr().write_str(fmt!(" %s\n", msg));
 }
 
+/**
+ * Applies replacements, each given as (start byte, end byte, text), to
+ * `src`, and returns the result with the number of replacements made. A
+ * replacement that overlaps an earlier one is skipped.
+ */
+pub fn apply_replacements(src: &str, edits: &[(uint, uint, ~str)])
+                       -> (~str, uint) {
+    pure fn le(a: &(uint, uint, ~str), b: &(uint, uint, ~str)) -> bool {
+        let (alo, ahi, _) = *a;
+        let (blo, bhi, _) = *b;
+        alo < blo || alo == blo && ahi <= bhi
+    }
+    let sorted = sort::merge_sort(edits, le);
+    let mut out = ~"";
+    let mut pos = 0u;
+    let mut applied = 0u;
+    for sorted.each |edit| {
+        let (lo, hi, ref text) = *edit;
+        if lo < pos || hi < lo || hi > src.len() { loop; }
+        out += str::slice(src, pos, lo);
+        out += *text;
+        pos = hi;
+        applied += 1u;
+    }
+    out += str::slice(src, pos, src.len());
+    (out, applied)
+}
+
 pub fn collect(messages: @DVec<~str>)
-    -> fn@(Option<(@codemap::CodeMap, span)>, &str, level)
+    -> fn@(Option<(@codemap::CodeMap, span)>, &str, level, Option<~str>,
+           &[SpanLabel])
 {
-    let f: @fn(Option<(@codemap::CodeMap, span)>, &str, level) =
-        |_o, msg: &str, _l| { messages.push(msg.to_str()); };
+    let f: @fn(Option<(@codemap::CodeMap, span)>, &str, level,
+               Option<~str>, &[SpanLabel]) =
+        |_o, msg: &str, _l, _code, _labels| {
+            messages.push(msg.to_str());
+        };
     f
 }
 
-pub fn emit(cmsp: Option<(@codemap::CodeMap, span)>, msg: &str, lvl: level) {
+pub fn emit(cmsp: Option<(@codemap::CodeMap, span)>, msg: &str, lvl: level,
+            code: Option<~str>, labels: &[SpanLabel]) {
+    // e.g. `unreachable pattern [E0001]`
+    let msg = match code {
+        Some(c) => fmt!("%s [%s]", msg, c),
+        None => msg.to_owned()
+    };
     match cmsp {
       Some((cm, sp)) => {
         let sp = cm.adjust_span(sp);
         let ss = cm.span_to_str(sp);
-        let lines = cm.span_to_lines(sp);
         print_diagnostic(ss, lvl, msg);
-        highlight_lines(cm, sp, lines);
+        if labels.is_empty() {
+            highlight_lines(cm, sp, cm.span_to_lines(sp));
+        } else {
+            io::stderr().write_str(highlight_labelled(cm, sp, labels));
+        }
         print_macro_backtrace(cm, sp);
       }
       None => {
@@ -221,6 +336,139 @@ pub fn emit(cmsp: Option<(@codemap::CodeMap, span)>, msg: &str, lvl: level) {
     }
 }
 
+// Line numbers are 1-based and columns 0-based, as in the text output;
+// byte offsets are relative to the start of the file
+fn span_to_json(cm: @codemap::CodeMap, sp: span) -> json::Json {
+    if cm.files.len() == 0 {
+        return json::Null;
+    }
+    let lo = cm.lookup_char_pos(sp.lo);
+    let hi = cm.lookup_char_pos(sp.hi);
+    let mut o = ~LinearMap::new();
+    o.insert(~"file", json::String(copy lo.file.name));
+    o.insert(~"byte_start",
+             json::Number((sp.lo - lo.file.start_pos).to_uint() as float));
+    o.insert(~"byte_end",
+             json::Number((sp.hi - lo.file.start_pos).to_uint() as float));
+    o.insert(~"line_start", json::Number(lo.line as float));
+    o.insert(~"col_start", json::Number(lo.col.to_uint() as float));
+    o.insert(~"line_end", json::Number(hi.line as float));
+    o.insert(~"col_end", json::Number(hi.col.to_uint() as float));
+    json::Object(o)
+}
+
+// The macro backtrace of a span, innermost expansion first
+fn expansion_to_json(cm: @codemap::CodeMap, sp: span) -> json::Json {
+    let mut frames = ~[];
+    let mut sp = sp;
+    loop {
+        match sp.expn_info {
+            Some(ei) => {
+                let mut o = ~LinearMap::new();
+                o.insert(~"macro", json::String(copy ei.callie.name));
+                o.insert(~"def_site", match ei.callie.span {
+                    Some(def) => span_to_json(cm, def),
+                    None => json::Null
+                });
+                o.insert(~"call_site", span_to_json(cm, ei.call_site));
+                frames.push(json::Object(o));
+                sp = ei.call_site;
+            }
+            None => break
+        }
+    }
+    json::List(frames)
+}
+
+struct JsonEmitter {
+    out: io::Writer,
+    // The last error or warning, held back until we know which notes
+    // belong to it
+    mut pending: Option<~json::Object>,
+    mut notes: ~[json::Json]
+}
+
+impl JsonEmitter {
+    fn emit(&self, cmsp: Option<(@codemap::CodeMap, span)>,
+            msg: &str, lvl: level, code: Option<~str>,
+            labels: &[SpanLabel]) {
+        let mut d = ~LinearMap::new();
+        d.insert(~"level", json::String(diagnosticstr(lvl)));
+        d.insert(~"message", json::String(msg.to_owned()));
+        d.insert(~"code", match code {
+            Some(c) => json::String(c),
+            None => json::Null
+        });
+        match cmsp {
+            Some((cm, sp)) => {
+                let sp = cm.adjust_span(sp);
+                d.insert(~"span", span_to_json(cm, sp));
+                d.insert(~"expansion", expansion_to_json(cm, sp));
+                d.insert(~"labels", json::List(labels.map(|l| {
+                    let mut o = ~LinearMap::new();
+                    let lsp = cm.adjust_span(l.span);
+                    o.insert(~"span", span_to_json(cm, lsp));
+                    o.insert(~"label", json::String(copy l.label));
+                    json::Object(o)
+                })));
+            }
+            None => {
+                d.insert(~"span", json::Null);
+                d.insert(~"expansion", json::List(~[]));
+                d.insert(~"labels", json::List(~[]));
+            }
+        }
+
+        if lvl == note && self.pending.is_some() {
+            self.notes.push(json::Object(d));
+            return;
+        }
+        self.flush();
+        self.pending = Some(d);
+        // Nothing follows a fatal error, the compiler stops right there
+        if lvl == fatal {
+            self.flush();
+        }
+    }
+
+    // Writes out the diagnostic being held back, with its notes
+    fn flush(&self) {
+        let mut pending = None;
+        pending <-> self.pending;
+        match pending {
+            Some(d) => {
+                let mut d = d;
+                let mut notes = ~[];
+                notes <-> self.notes;
+                d.insert(~"notes", json::List(notes));
+                json::to_writer(self.out, &json::Object(d));
+                self.out.write_char('\n');
+            }
+            None => ()
+        }
+    }
+}
+
+/**
+ * An emitter that writes each diagnostic to `out` as a line of JSON: an
+ * object with its `level`, `message`, error `code` (or null), primary
+ * `span` (or null), secondary `labels` (each a `span` and a `label`),
+ * macro `expansion` backtrace, and the `notes` that were emitted after
+ * it.
+ *
+ * Each diagnostic is held back until the next one shows it has no more
+ * notes, so the second function returned must be called once compilation
+ * is over to write out the last one.
+ */
+pub fn mk_json_emitter(out: io::Writer) -> (Emitter, fn@()) {
+    let st = @JsonEmitter { out: out, mut pending: None, mut notes: ~[] };
+    let emit: Emitter = |cmsp, msg, lvl, code, labels| {
+        st.emit(cmsp, msg, lvl, code, labels)
+    };
+    let flush: fn@() = || st.flush();
+    (emit, flush)
+}
+
 fn highlight_lines(cm: @codemap::CodeMap,
                    sp: span,
                    lines: @codemap::FileLines) {
@@ -288,6 +536,89 @@ fn highlight_lines(cm: @codemap::CodeMap,
     }
 }
 
+// A span underlined in a labelled snippet: `^~~~` for the primary span,
+// `----` followed by the label for the others
+struct Marker {
+    file: @codemap::FileMap,
+    // 0-based, as FileMap::get_line wants it
+    line: uint,
+    lo: uint,
+    hi: uint,
+    primary: bool,
+    label: ~str
+}
+
+fn mk_marker(cm: @codemap::CodeMap, sp: span, primary: bool,
+             label: ~str) -> Marker {
+    let lo = cm.lookup_char_pos(sp.lo);
+    let hi = cm.lookup_char_pos(sp.hi);
+    // Spans running over several lines are underlined up to the end of
+    // their first line
+    let hi_col = if hi.line == lo.line {
+        hi.col.to_uint()
+    } else {
+        str::char_len(lo.file.get_line((lo.line - 1u) as int))
+    };
+    Marker { file: lo.file, line: lo.line - 1u, lo: lo.col.to_uint(),
+             hi: cmp::max(hi_col, lo.col.to_uint() + 1u),
+             primary: primary, label: label }
+}
+
+// Renders the lines a diagnostic and its labels point at in source order,
+// each followed by the underlines of the spans on it
+fn highlight_labelled(cm: @codemap::CodeMap, sp: span,
+                      labels: &[SpanLabel]) -> ~str {
+    let mut markers = ~[mk_marker(cm, sp, true, ~"")];
+    for labels.each |l| {
+        markers.push(mk_marker(cm, cm.adjust_span(l.span), false,
+                               copy l.label));
+    }
+    pure fn le(a: &Marker, b: &Marker) -> bool {
+        if a.file.name != b.file.name {
+            a.file.name <= b.file.name
+        } else if a.line != b.line {
+            a.line < b.line
+        } else {
+            a.lo <= b.lo
+        }
+    }
+    let markers = sort::merge_sort(markers, le);
+
+    let mut out = ~"";
+    let mut i = 0u;
+    while i < markers.len() {
+        let file = markers[i].file, line = markers[i].line;
+        let prefix = fmt!("%s:%u ", file.name, line + 1u);
+        let src = file.get_line(line as int);
+        out += prefix + src + ~"\n";
+        while i < markers.len() && markers[i].line == line &&
+                markers[i].file.name == file.name {
+            let m = &markers[i];
+            let mut s = str::repeat(" ", prefix.len());
+            // Copy tabs from the source line so that the underline lines
+            // up with it
+            let mut col = 0u;
+            for str::each_char(src) |c| {
+                if col == m.lo { break; }
+                str::push_char(&mut s, if c == '\t' { '\t' } else { ' ' });
+                col += 1u;
+            }
+            if m.primary {
+                s += ~"^";
+                for (m.hi - m.lo - 1u).times() { s += ~"~"; }
+            } else {
+                for (m.hi - m.lo).times() { s += ~"-"; }
+            }
+            if !m.label.is_empty() {
+                s += ~" " + m.label;
+            }
+            out += s + ~"\n";
+            i += 1u;
+        }
+    }
+    out
+}
+
 fn print_macro_backtrace(cm: @codemap::CodeMap, sp: span) {
     do option::iter(&sp.expn_info) |ei| {
         let ss = option::map_default(&ei.callie.span, @~"",
@@ -308,3 +639,117 @@ pub fn expect<T: Copy>(diag: span_handler,
        None => diag.handler().bug(msg())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use core::prelude::*;
+
+    use codemap::{BytePos, CodeMap, mk_sp};
+    use diagnostic::*;
+    use diagnostic::highlight_labelled;
+
+    use core::io;
+    use core::str;
+    use std::json;
+
+    #[test]
+    fn test_json_emitter() {
+        let out = do io::with_str_writer |wr| {
+            let cm = @CodeMap::new();
+            let fm = cm.new_filemap(~"test.rs",
+                                    @~"fn main() {\n    foo();\n}");
+            fm.next_line(BytePos(0));
+            fm.next_line(BytePos(12));
+            let (emit, flush) = mk_json_emitter(wr);
+            emit(Some((cm, mk_sp(BytePos(16), BytePos(19)))),
+                 "unresolved name: foo", error, Some(~"E0012"),
+                 [SpanLabel { span: mk_sp(BytePos(3), BytePos(7)),
+                              label: ~"in this function" }]);
+            emit(None, "did you mean bar?", note, None, []);
+            emit(None, "unused variable", warning, None, []);
+            flush();
+        };
+
+        let lines = str::lines(str::trim(out));
+        assert lines.len() == 2u;
+        match json::from_str(lines[0]) {
+            Ok(json::Object(ref d)) => {
+                assert *d.get(&~"level") == json::String(~"error");
+                assert *d.get(&~"message") ==
+                    json::String(~"unresolved name: foo");
+                assert *d.get(&~"code") == json::String(~"E0012");
+                match *d.get(&~"span") {
+                    json::Object(ref sp) => {
+                        assert *sp.get(&~"file") == json::String(~"test.rs");
+                        assert *sp.get(&~"byte_start") == json::Number(16f);
+                        assert *sp.get(&~"line_start") == json::Number(2f);
+                        assert *sp.get(&~"col_start") == json::Number(4f);
+                    }
+                    _ => fail!()
+                }
+                match *d.get(&~"notes") {
+                    json::List(ref notes) => assert notes.len() == 1u,
+                    _ => fail!()
+                }
+                match *d.get(&~"labels") {
+                    json::List(ref labels) => {
+                        assert labels.len() == 1u;
+                        match labels[0] {
+                            json::Object(ref l) => {
+                                assert *l.get(&~"label") ==
+                                    json::String(~"in this function");
+                            }
+                            _ => fail!()
+                        }
+                    }
+                    _ => fail!()
+                }
+            }
+            _ => fail!()
+        }
+        match json::from_str(lines[1]) {
+            Ok(json::Object(ref d)) => {
+                assert *d.get(&~"span") == json::Null;
+                assert *d.get(&~"code") == json::Null;
+                assert *d.get(&~"notes") == json::List(~[]);
+            }
+            _ => fail!()
+        }
+    }
+
+    #[test]
+    fn test_highlight_labelled() {
+        let cm = @CodeMap::new();
+        let fm = cm.new_filemap(~"t.rs", @(~"fn main() {\n" +
+                                           ~"    let a = &x;\n" +
+                                           ~"    let b = &mut x;\n}"));
+        fm.next_line(BytePos(0));
+        fm.next_line(BytePos(12));
+        fm.next_line(BytePos(28));
+        fm.next_line(BytePos(48));
+        let s = highlight_labelled(cm, mk_sp(BytePos(40), BytePos(46)),
+                                   [SpanLabel {
+                                       span: mk_sp(BytePos(24), BytePos(26)),
+                                       label: ~"first borrow here"
+                                   }]);
+        assert s == ~"t.rs:2     let a = &x;\n" +
+            ~"                   -- first borrow here\n" +
+            ~"t.rs:3     let b = &mut x;\n" +
+            ~"                   ^~~~~~\n";
+    }
+
+    #[test]
+    fn test_apply_replacements() {
+        let src = "while true { foo(+x); }";
+        let (out, n) = apply_replacements(src, [(17u, 18u, ~""),
+                                                (0u, 10u, ~"loop")]);
+        assert out == ~"loop { foo(x); }";
+        assert n == 2u;
+
+        // The second edit overlaps the first and is left out
+        let (out, n) = apply_replacements(src, [(0u, 5u, ~"loop"),
+                                                (3u, 10u, ~"")]);
+        assert out == ~"loop true { foo(+x); }";
+        assert n == 1u;
+    }
+}
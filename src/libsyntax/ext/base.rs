@@ -75,6 +75,19 @@ pub enum SyntaxExtension {
     ItemTT(SyntaxExpanderTTItem),
 }
 
+/**
+ * The registration function of a syntax extension plugin. A plugin
+ * library named by a `#[syntax_plugin = "name"]` crate attribute exports
+ * one, unmangled, as `rust_syntax_plugin_registrar`; the compiler calls
+ * it before expansion with an empty table, and adds the `NormalTT` and
+ * `ItemTT` expanders it inserts to the built-in ones.
+ */
+pub type PluginRegistrarFn = fn(HashMap<~str, SyntaxExtension>);
+
+/// The symbol under which a plugin exports its `PluginRegistrarFn`
+pub const plugin_registrar_symbol: &str =
+    "rust_syntax_plugin_registrar";
+
 // A temporary hard-coded map of methods for expanding syntax extension
 // AST nodes into full ASTs
 pub fn syntax_expander_table() -> HashMap<~str, SyntaxExtension> {
@@ -112,6 +125,15 @@ pub fn syntax_expander_table() -> HashMap<~str, SyntaxExtension> {
     syntax_expanders.insert(~"deriving_iter_bytes",
                             ItemDecorator(
                                 ext::deriving::expand_deriving_iter_bytes));
+    syntax_expanders.insert(~"deriving_clone",
+                            ItemDecorator(
+                                ext::deriving::expand_deriving_clone));
+    syntax_expanders.insert(~"deriving_ord",
+                            ItemDecorator(
+                                ext::deriving::expand_deriving_ord));
+    syntax_expanders.insert(~"deriving_to_str",
+                            ItemDecorator(
+                                ext::deriving::expand_deriving_to_str));
 
     // Quasi-quoting expanders
     syntax_expanders.insert(~"quote_tokens",
@@ -8,19 +8,21 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-/// The compiler code necessary to implement the #[deriving_eq] and
-/// #[deriving_iter_bytes] extensions.
+/// The compiler code necessary to implement the #[deriving_eq],
+/// #[deriving_iter_bytes], #[deriving_clone], #[deriving_ord] and
+/// #[deriving_to_str] extensions.
 
 use core::prelude::*;
 
 use ast;
-use ast::{TraitTyParamBound, Ty, and, bind_by_ref, binop, deref, enum_def};
-use ast::{enum_variant_kind, expr, expr_match, ident, item, item_};
-use ast::{item_enum, item_impl, item_struct, m_imm, meta_item, method};
-use ast::{named_field, or, pat, pat_ident, pat_wild, public, pure_fn};
-use ast::{re_anon, stmt, struct_def, struct_variant_kind};
-use ast::{sty_by_ref, sty_region, tuple_variant_kind, ty_nil, ty_param};
-use ast::{ty_param_bound, ty_path, ty_rptr, unnamed_field, variant};
+use ast::{TraitTyParamBound, Ty, add, and, bind_by_ref, binop, deref};
+use ast::{enum_def, enum_variant_kind, eq, expr, expr_if, expr_match, gt};
+use ast::{ident, impure_fn, item, item_, item_enum, item_impl, item_struct};
+use ast::{lt, m_imm, meta_item, method, named_field, not, or, pat, pat_enum};
+use ast::{pat_ident, pat_struct, pat_wild, public, pure_fn, purity, re_anon};
+use ast::{stmt, struct_def, struct_variant_kind, sty_by_ref, sty_region};
+use ast::{tuple_variant_kind, ty_nil, ty_param, ty_param_bound, ty_path};
+use ast::{ty_rptr, ty_uniq, unnamed_field, variant};
 use ext::base::ext_ctxt;
 use ext::build;
 use codemap::{span, spanned};
@@ -28,6 +30,7 @@ use parse::token::special_idents::clownshoes_extensions;
 
 use core::dvec;
 use core::uint;
+use core::vec;
 
 enum Junction {
     Conjunction,
@@ -80,6 +83,42 @@ pub fn expand_deriving_iter_bytes(cx: ext_ctxt,
                     expand_deriving_iter_bytes_enum_def)
 }
 
+pub fn expand_deriving_clone(cx: ext_ctxt,
+                             span: span,
+                             _mitem: meta_item,
+                             in_items: ~[@item])
+                          -> ~[@item] {
+    expand_deriving(cx,
+                    span,
+                    in_items,
+                    expand_deriving_clone_struct_def,
+                    expand_deriving_clone_enum_def)
+}
+
+pub fn expand_deriving_ord(cx: ext_ctxt,
+                           span: span,
+                           _mitem: meta_item,
+                           in_items: ~[@item])
+                        -> ~[@item] {
+    expand_deriving(cx,
+                    span,
+                    in_items,
+                    expand_deriving_ord_struct_def,
+                    expand_deriving_ord_enum_def)
+}
+
+pub fn expand_deriving_to_str(cx: ext_ctxt,
+                              span: span,
+                              _mitem: meta_item,
+                              in_items: ~[@item])
+                           -> ~[@item] {
+    expand_deriving(cx,
+                    span,
+                    in_items,
+                    expand_deriving_to_str_struct_def,
+                    expand_deriving_to_str_enum_def)
+}
+
 fn expand_deriving(cx: ext_ctxt,
                    span: span,
                    in_items: ~[@item],
@@ -122,7 +161,7 @@ fn create_impl_item(cx: ext_ctxt, span: span, +item: item_) -> @item {
 }
 
 /// Creates a method from the given expression, the signature of which
-/// conforms to the `eq` or `ne` method.
+/// conforms to the `eq` or `ne` method, or to the methods of `Ord`.
 fn create_eq_method(cx: ext_ctxt,
                     span: span,
                     method_ident: ident,
@@ -871,3 +910,663 @@ fn expand_deriving_iter_bytes_enum_method(cx: ext_ctxt,
     create_iter_bytes_method(cx, span, ~[ self_match_stmt ])
 }
 
+
+// The names of the fields of a struct, or `None` for each field of a
+// tuple-like struct.
+fn struct_field_names(struct_def: &struct_def) -> ~[Option<ident>] {
+    do struct_def.fields.map |struct_field| {
+        match struct_field.node.kind {
+            named_field(ident, _, _) => Some(ident),
+            unnamed_field => None
+        }
+    }
+}
+
+// The names of the fields of an enum variant, as for `struct_field_names`.
+fn variant_field_names(cx: ext_ctxt,
+                       span: span,
+                       variant: &variant)
+                    -> ~[Option<ident>] {
+    match variant.node.kind {
+        tuple_variant_kind(ref args) => args.map(|_| None),
+        struct_variant_kind(struct_def) => struct_field_names(struct_def),
+        enum_variant_kind(*) => {
+            cx.span_unimpl(span, ~"enum variants for `deriving`");
+        }
+    }
+}
+
+// Creates a pattern that matches any value of a struct and binds its
+// fields by reference to `prefix0`, `prefix1` and so on.
+fn create_struct_pattern(cx: ext_ctxt,
+                         span: span,
+                         type_ident: ident,
+                         struct_def: &struct_def,
+                         prefix: ~str)
+                      -> @pat {
+    let names = struct_field_names(struct_def);
+    if names.len() == 0 {
+        return build::mk_pat(cx, span, pat_wild);
+    }
+
+    let matching_path = build::mk_raw_path(span, ~[ type_ident ]);
+    let subpats = create_subpatterns(cx, span, prefix, names.len());
+    match names[0] {
+        Some(_) => {
+            let field_pats = do names.mapi |i, name| {
+                ast::field_pat { ident: name.get(), pat: subpats[i] }
+            };
+            build::mk_pat_struct(cx, span, matching_path, field_pats)
+        }
+        None => build::mk_pat_enum(cx, span, matching_path, subpats)
+    }
+}
+
+// Creates a pattern that matches a variant of an enum without binding
+// any of its fields.
+fn create_enum_variant_wild_pattern(cx: ext_ctxt,
+                                    span: span,
+                                    variant: &variant)
+                                 -> @pat {
+    let variant_ident = variant.node.name;
+    let matching_path = build::mk_raw_path(span, ~[ variant_ident ]);
+    match variant.node.kind {
+        tuple_variant_kind(ref variant_args) => {
+            if variant_args.len() == 0 {
+                build::mk_pat_ident_with_binding_mode(
+                    cx, span, variant_ident, ast::bind_infer)
+            } else {
+                build::mk_pat(cx, span, pat_enum(matching_path, None))
+            }
+        }
+        struct_variant_kind(*) => {
+            build::mk_pat(cx, span, pat_struct(matching_path, ~[], true))
+        }
+        enum_variant_kind(*) => {
+            cx.span_unimpl(span, ~"enum variants for `deriving`");
+        }
+    }
+}
+
+// Creates the expressions `prefix0`, `prefix1`, ..., referring to the
+// bindings of `create_subpatterns`.
+fn create_subpattern_paths(cx: ext_ctxt,
+                           span: span,
+                           prefix: ~str,
+                           n: uint)
+                        -> ~[@expr] {
+    do vec::from_fn(n) |i| {
+        let ident = cx.ident_of(prefix + i.to_str());
+        build::mk_path(cx, span, ~[ ident ])
+    }
+}
+
+// Calls the method named `method_name` on `receiver` with `args`.
+fn call_method(cx: ext_ctxt,
+               span: span,
+               receiver: @expr,
+               method_name: ~str,
+               +args: ~[@expr])
+            -> @expr {
+    let method_ident = cx.ident_of(method_name);
+    let method = build::mk_access_(cx, span, receiver, method_ident);
+    build::mk_call_(cx, span, method, args)
+}
+
+// Creates a value of a struct or enum variant from the values of its
+// fields.
+fn create_construction_expr(cx: ext_ctxt,
+                            span: span,
+                            ctor_ident: ident,
+                            names: &[Option<ident>],
+                            +values: ~[@expr])
+                         -> @expr {
+    if names.len() == 0 {
+        return build::mk_path(cx, span, ~[ ctor_ident ]);
+    }
+    match names[0] {
+        Some(_) => {
+            let fields = do names.mapi |i, name| {
+                let field = { ident: name.get(), ex: values[i] };
+                field
+            };
+            build::mk_struct_e(cx, span, ~[ ctor_ident ], fields)
+        }
+        None => build::mk_call(cx, span, ~[ ctor_ident ], values)
+    }
+}
+
+// Creates a method taking nothing but `&self`.
+fn create_nullary_method(cx: ext_ctxt,
+                         span: span,
+                         method_ident: ident,
+                         purity: purity,
+                         output_type: @Ty,
+                         body: @expr)
+                      -> @method {
+    let fn_decl = build::mk_fn_decl(~[], output_type);
+    let body_block = build::mk_simple_block(cx, span, body);
+    let self_ty = spanned { node: sty_region(m_imm), span: span };
+    @ast::method {
+        ident: method_ident,
+        attrs: ~[],
+        tps: ~[],
+        self_ty: self_ty,
+        purity: purity,
+        decl: move fn_decl,
+        body: move body_block,
+        id: cx.next_id(),
+        span: span,
+        self_id: cx.next_id(),
+        vis: public
+    }
+}
+
+fn create_derived_clone_impl(cx: ext_ctxt,
+                             span: span,
+                             type_ident: ident,
+                             +ty_params: ~[ty_param],
+                             body: @expr)
+                          -> @item {
+    let self_type = create_self_type_with_params(cx,
+                                                 span,
+                                                 type_ident,
+                                                 ty_params);
+    let method = create_nullary_method(cx,
+                                       span,
+                                       cx.ident_of(~"clone"),
+                                       impure_fn,
+                                       self_type,
+                                       body);
+    let trait_path = [
+        cx.ident_of(~"core"),
+        cx.ident_of(~"clone"),
+        cx.ident_of(~"Clone")
+    ];
+    create_derived_impl(cx, span, type_ident, ty_params, [ method ],
+                        trait_path)
+}
+
+fn expand_deriving_clone_struct_def(cx: ext_ctxt,
+                                    span: span,
+                                    struct_def: &struct_def,
+                                    type_ident: ident,
+                                    +ty_params: ~[ty_param])
+                                 -> @item {
+    // Clone each field of `self` into a new value of the struct.
+    let names = struct_field_names(struct_def);
+    let self_pat = create_struct_pattern(cx,
+                                         span,
+                                         type_ident,
+                                         struct_def,
+                                         ~"__self");
+    let fields = create_subpattern_paths(cx, span, ~"__self", names.len());
+    let clones = fields.map(|f| call_method(cx, span, *f, ~"clone", ~[]));
+    let clone_expr = create_construction_expr(cx,
+                                              span,
+                                              type_ident,
+                                              names,
+                                              clones);
+
+    let self_arm = ast::arm {
+        pats: ~[ self_pat ],
+        guard: None,
+        body: build::mk_simple_block(cx, span, clone_expr)
+    };
+    let self_expr = build::mk_path(cx, span, ~[ cx.ident_of(~"self") ]);
+    let self_expr = build::mk_unary(cx, span, deref, self_expr);
+    let body = build::mk_expr(cx, span, expr_match(self_expr, ~[ self_arm ]));
+
+    create_derived_clone_impl(cx, span, type_ident, move ty_params, body)
+}
+
+fn expand_deriving_clone_enum_def(cx: ext_ctxt,
+                                  span: span,
+                                  enum_definition: &enum_def,
+                                  type_ident: ident,
+                                  +ty_params: ~[ty_param])
+                               -> @item {
+    // Clone each field of the variant of `self` into a new value of the
+    // same variant.
+    let arms = do enum_definition.variants.map |variant| {
+        let names = variant_field_names(cx, span, variant);
+        let pat = create_enum_variant_pattern(cx, span, variant, ~"__self");
+        let fields = create_subpattern_paths(cx,
+                                             span,
+                                             ~"__self",
+                                             names.len());
+        let clones = fields.map(|f| call_method(cx, span, *f, ~"clone",
+                                                ~[]));
+        let clone_expr = create_construction_expr(cx,
+                                                  span,
+                                                  variant.node.name,
+                                                  names,
+                                                  clones);
+        ast::arm {
+            pats: ~[ pat ],
+            guard: None,
+            body: build::mk_simple_block(cx, span, clone_expr)
+        }
+    };
+
+    let self_expr = build::mk_path(cx, span, ~[ cx.ident_of(~"self") ]);
+    let self_expr = build::mk_unary(cx, span, deref, self_expr);
+    let body = build::mk_expr(cx, span, expr_match(self_expr, arms));
+
+    create_derived_clone_impl(cx, span, type_ident, move ty_params, body)
+}
+
+// The four methods of `Ord`, each as its name, whether it holds when its
+// operands are equal and whether it asks if `self` is the greater one.
+fn ord_methods() -> ~[(~str, bool, bool)] {
+    ~[
+        (~"lt", false, false),
+        (~"le", true, false),
+        (~"ge", true, true),
+        (~"gt", false, true)
+    ]
+}
+
+// Compares the fields `lhs_prefix0`, `lhs_prefix1`, ... with the fields
+// `rhs_prefix0`, ... in order, and builds
+//
+//     lhs0.lt(rhs0) || !rhs0.lt(lhs0) && (lhs1.lt(rhs1) || ... || base)
+//
+// which only needs `lt` of every field.
+fn create_lexical_ord_expr(cx: ext_ctxt,
+                           span: span,
+                           lhs_prefix: ~str,
+                           rhs_prefix: ~str,
+                           n: uint,
+                           base: bool)
+                        -> @expr {
+    let mut expr = build::mk_bool(cx, span, base);
+    let mut i = n;
+    while i > 0 {
+        i -= 1;
+        let lhs_ident = cx.ident_of(lhs_prefix + i.to_str());
+        let rhs_ident = cx.ident_of(rhs_prefix + i.to_str());
+
+        let less = call_method(cx,
+                               span,
+                               build::mk_path(cx, span, ~[ lhs_ident ]),
+                               ~"lt",
+                               ~[ build::mk_path(cx, span, ~[ rhs_ident ]) ]);
+        let greater = call_method(cx,
+                                  span,
+                                  build::mk_path(cx, span, ~[ rhs_ident ]),
+                                  ~"lt",
+                                  ~[ build::mk_path(cx,
+                                                    span,
+                                                    ~[ lhs_ident ]) ]);
+        let not_greater = build::mk_unary(cx, span, not, greater);
+        let rest = build::mk_binary(cx, span, and, not_greater, expr);
+        expr = build::mk_binary(cx, span, or, less, rest);
+    }
+    expr
+}
+
+// The order of the arguments of `create_lexical_ord_expr` for a method
+// of `Ord`.
+fn ord_operand_prefixes(self_is_greater: bool) -> (~str, ~str) {
+    if self_is_greater {
+        (~"__other", ~"__self")
+    } else {
+        (~"__self", ~"__other")
+    }
+}
+
+fn create_derived_ord_impl(cx: ext_ctxt,
+                           span: span,
+                           type_ident: ident,
+                           +ty_params: ~[ty_param],
+                           methods: &[@method])
+                        -> @item {
+    let trait_path = [
+        cx.ident_of(~"core"),
+        cx.ident_of(~"cmp"),
+        cx.ident_of(~"Ord")
+    ];
+    create_derived_impl(cx, span, type_ident, ty_params, methods, trait_path)
+}
+
+fn expand_deriving_ord_struct_def(cx: ext_ctxt,
+                                  span: span,
+                                  struct_def: &struct_def,
+                                  type_ident: ident,
+                                  +ty_params: ~[ty_param])
+                               -> @item {
+    let n = struct_def.fields.len();
+    let methods = do ord_methods().map |m| {
+        let (name, or_equal, self_is_greater) = copy *m;
+        let (lhs, rhs) = ord_operand_prefixes(self_is_greater);
+        let compare_expr = create_lexical_ord_expr(cx,
+                                                   span,
+                                                   lhs,
+                                                   rhs,
+                                                   n,
+                                                   or_equal);
+
+        // Match `self` and `other` against the struct to get at their
+        // fields.
+        let other_arm = ast::arm {
+            pats: ~[ create_struct_pattern(cx,
+                                           span,
+                                           type_ident,
+                                           struct_def,
+                                           ~"__other") ],
+            guard: None,
+            body: build::mk_simple_block(cx, span, compare_expr)
+        };
+        let other_expr = build::mk_path(cx,
+                                        span,
+                                        ~[ cx.ident_of(~"__other") ]);
+        let other_expr = build::mk_unary(cx, span, deref, other_expr);
+        let other_match_expr = build::mk_expr(cx,
+                                              span,
+                                              expr_match(other_expr,
+                                                         ~[ other_arm ]));
+        let self_arm = ast::arm {
+            pats: ~[ create_struct_pattern(cx,
+                                           span,
+                                           type_ident,
+                                           struct_def,
+                                           ~"__self") ],
+            guard: None,
+            body: build::mk_simple_block(cx, span, other_match_expr)
+        };
+        let self_expr = build::mk_path(cx, span, ~[ cx.ident_of(~"self") ]);
+        let self_expr = build::mk_unary(cx, span, deref, self_expr);
+        let body = build::mk_expr(cx,
+                                  span,
+                                  expr_match(self_expr, ~[ self_arm ]));
+
+        create_eq_method(cx,
+                         span,
+                         cx.ident_of(name),
+                         type_ident,
+                         ty_params,
+                         body)
+    };
+
+    create_derived_ord_impl(cx, span, type_ident, move ty_params, methods)
+}
+
+// Creates an expression giving the index of the variant of `*scrutinee`
+// among the variants of the enum.
+fn create_variant_index_expr(cx: ext_ctxt,
+                             span: span,
+                             enum_definition: &enum_def,
+                             scrutinee: ~str)
+                          -> @expr {
+    let arms = do enum_definition.variants.mapi |i, variant| {
+        ast::arm {
+            pats: ~[ create_enum_variant_wild_pattern(cx, span, variant) ],
+            guard: None,
+            body: build::mk_simple_block(cx,
+                                         span,
+                                         build::mk_uint(cx, span, i))
+        }
+    };
+    let expr = build::mk_path(cx, span, ~[ cx.ident_of(scrutinee) ]);
+    let expr = build::mk_unary(cx, span, deref, expr);
+    build::mk_expr(cx, span, expr_match(expr, arms))
+}
+
+fn expand_deriving_ord_enum_def(cx: ext_ctxt,
+                                span: span,
+                                enum_definition: &enum_def,
+                                type_ident: ident,
+                                +ty_params: ~[ty_param])
+                             -> @item {
+    // Values of different variants are ordered like the variants are
+    // declared; values of the same variant by their fields.
+    let self_idx_ident = cx.ident_of(~"__self_idx");
+    let other_idx_ident = cx.ident_of(~"__other_idx");
+    let methods = do ord_methods().map |m| {
+        let (name, or_equal, self_is_greater) = copy *m;
+        let (lhs, rhs) = ord_operand_prefixes(self_is_greater);
+
+        // Compare the fields when both are the same variant.
+        let self_arms = do enum_definition.variants.map |variant| {
+            let n = variant_arg_count(cx, span, variant);
+            let compare_expr = create_lexical_ord_expr(cx,
+                                                       span,
+                                                       copy lhs,
+                                                       copy rhs,
+                                                       n,
+                                                       or_equal);
+            let mut other_arms = ~[ ast::arm {
+                pats: ~[ create_enum_variant_pattern(cx,
+                                                     span,
+                                                     variant,
+                                                     ~"__other") ],
+                guard: None,
+                body: build::mk_simple_block(cx, span, compare_expr)
+            } ];
+            // Never reached, since the indices of the variants are equal.
+            if enum_definition.variants.len() > 1 {
+                other_arms.push(ast::arm {
+                    pats: ~[ build::mk_pat(cx, span, pat_wild) ],
+                    guard: None,
+                    body: build::mk_simple_block(cx,
+                                                 span,
+                                                 build::mk_bool(cx,
+                                                                span,
+                                                                false))
+                });
+            }
+            let other_expr = build::mk_path(cx,
+                                            span,
+                                            ~[ cx.ident_of(~"__other") ]);
+            let other_expr = build::mk_unary(cx, span, deref, other_expr);
+            let other_match_expr = build::mk_expr(cx,
+                                                  span,
+                                                  expr_match(other_expr,
+                                                             other_arms));
+            ast::arm {
+                pats: ~[ create_enum_variant_pattern(cx,
+                                                     span,
+                                                     variant,
+                                                     ~"__self") ],
+                guard: None,
+                body: build::mk_simple_block(cx, span, other_match_expr)
+            }
+        };
+        let self_expr = build::mk_path(cx, span, ~[ cx.ident_of(~"self") ]);
+        let self_expr = build::mk_unary(cx, span, deref, self_expr);
+        let same_variant_expr = build::mk_expr(cx,
+                                               span,
+                                               expr_match(self_expr,
+                                                          self_arms));
+
+        // Otherwise compare the indices of the variants.
+        let self_idx = build::mk_path(cx, span, ~[ self_idx_ident ]);
+        let other_idx = build::mk_path(cx, span, ~[ other_idx_ident ]);
+        let op = if self_is_greater { gt } else { lt };
+        let different_variant_expr = build::mk_binary(cx,
+                                                      span,
+                                                      op,
+                                                      self_idx,
+                                                      other_idx);
+
+        let idx_eq_expr = build::mk_binary(
+            cx,
+            span,
+            eq,
+            build::mk_path(cx, span, ~[ self_idx_ident ]),
+            build::mk_path(cx, span, ~[ other_idx_ident ]));
+        let if_expr = build::mk_expr(
+            cx,
+            span,
+            expr_if(idx_eq_expr,
+                    build::mk_simple_block(cx, span, same_variant_expr),
+                    Some(build::mk_block(cx,
+                                         span,
+                                         ~[],
+                                         ~[],
+                                         Some(different_variant_expr)))));
+
+        let self_idx_stmt = build::mk_local(
+            cx,
+            span,
+            false,
+            self_idx_ident,
+            create_variant_index_expr(cx, span, enum_definition, ~"self"));
+        let other_idx_stmt = build::mk_local(
+            cx,
+            span,
+            false,
+            other_idx_ident,
+            create_variant_index_expr(cx,
+                                      span,
+                                      enum_definition,
+                                      ~"__other"));
+        let body = build::mk_block(cx,
+                                   span,
+                                   ~[],
+                                   ~[ self_idx_stmt, other_idx_stmt ],
+                                   Some(if_expr));
+
+        create_eq_method(cx,
+                         span,
+                         cx.ident_of(name),
+                         type_ident,
+                         ty_params,
+                         body)
+    };
+
+    create_derived_ord_impl(cx, span, type_ident, move ty_params, methods)
+}
+
+// Creates an expression that shows a struct or enum variant named `name`
+// with the given fields, like `Name { a: 1, b: 2 }` or `Name(1, 2)`.
+fn create_to_str_expr(cx: ext_ctxt,
+                      span: span,
+                      name: ~str,
+                      names: &[Option<ident>],
+                      fields: &[@expr])
+                   -> @expr {
+    if names.len() == 0 {
+        return build::mk_uniq_str(cx, span, name);
+    }
+
+    let named = names[0].is_some();
+    let mut pieces = ~[];
+    let mut text = if named { name + ~" { " } else { name + ~"(" };
+    for names.eachi |i, field_name| {
+        if i > 0 {
+            text += ~", ";
+        }
+        match *field_name {
+            Some(ident) => {
+                text += cx.str_of(ident);
+                text += ~": ";
+            }
+            None => ()
+        }
+        pieces.push(build::mk_uniq_str(cx, span, text));
+        pieces.push(call_method(cx, span, fields[i], ~"to_str", ~[]));
+        text = ~"";
+    }
+    if named {
+        text += ~" }";
+    } else {
+        text += ~")";
+    }
+    pieces.push(build::mk_uniq_str(cx, span, text));
+
+    let mut expr = pieces[0];
+    for uint::range(1, pieces.len()) |i| {
+        expr = build::mk_binary(cx, span, add, expr, pieces[i]);
+    }
+    expr
+}
+
+fn create_derived_to_str_impl(cx: ext_ctxt,
+                              span: span,
+                              type_ident: ident,
+                              +ty_params: ~[ty_param],
+                              +arms: ~[ast::arm])
+                           -> @item {
+    let self_expr = build::mk_path(cx, span, ~[ cx.ident_of(~"self") ]);
+    let self_expr = build::mk_unary(cx, span, deref, self_expr);
+    let body = build::mk_expr(cx, span, expr_match(self_expr, arms));
+
+    // Create the type of the return value, `~str`.
+    let str_type = build::mk_simple_ty_path(cx, span, cx.ident_of(~"str"));
+    let output_type = @ast::Ty {
+        id: cx.next_id(),
+        node: ty_uniq(ast::mt { ty: str_type, mutbl: m_imm }),
+        span: span,
+    };
+
+    let method = create_nullary_method(cx,
+                                       span,
+                                       cx.ident_of(~"to_str"),
+                                       pure_fn,
+                                       output_type,
+                                       body);
+    let trait_path = [
+        cx.ident_of(~"core"),
+        cx.ident_of(~"to_str"),
+        cx.ident_of(~"ToStr")
+    ];
+    create_derived_impl(cx, span, type_ident, ty_params, [ method ],
+                        trait_path)
+}
+
+fn expand_deriving_to_str_struct_def(cx: ext_ctxt,
+                                     span: span,
+                                     struct_def: &struct_def,
+                                     type_ident: ident,
+                                     +ty_params: ~[ty_param])
+                                  -> @item {
+    let names = struct_field_names(struct_def);
+    let fields = create_subpattern_paths(cx, span, ~"__self", names.len());
+    let to_str_expr = create_to_str_expr(cx,
+                                         span,
+                                         cx.str_of(type_ident),
+                                         names,
+                                         fields);
+    let arm = ast::arm {
+        pats: ~[ create_struct_pattern(cx,
+                                       span,
+                                       type_ident,
+                                       struct_def,
+                                       ~"__self") ],
+        guard: None,
+        body: build::mk_simple_block(cx, span, to_str_expr)
+    };
+    create_derived_to_str_impl(cx, span, type_ident, move ty_params,
+                               ~[ arm ])
+}
+
+fn expand_deriving_to_str_enum_def(cx: ext_ctxt,
+                                   span: span,
+                                   enum_definition: &enum_def,
+                                   type_ident: ident,
+                                   +ty_params: ~[ty_param])
+                                -> @item {
+    let arms = do enum_definition.variants.map |variant| {
+        let names = variant_field_names(cx, span, variant);
+        let fields = create_subpattern_paths(cx,
+                                             span,
+                                             ~"__self",
+                                             names.len());
+        let to_str_expr = create_to_str_expr(cx,
+                                             span,
+                                             cx.str_of(variant.node.name),
+                                             names,
+                                             fields);
+        ast::arm {
+            pats: ~[ create_enum_variant_pattern(cx,
+                                                 span,
+                                                 variant,
+                                                 ~"__self") ],
+            guard: None,
+            body: build::mk_simple_block(cx, span, to_str_expr)
+        }
+    };
+    create_derived_to_str_impl(cx, span, type_ident, move ty_params, arms)
+}
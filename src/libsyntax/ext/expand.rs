@@ -334,7 +334,15 @@ pub fn core_macros() -> ~str {
 
 pub fn expand_crate(parse_sess: parse::parse_sess,
                     cfg: ast::crate_cfg, c: @crate) -> @crate {
-    let exts = syntax_expander_table();
+    expand_crate_with_exts(parse_sess, cfg, c, syntax_expander_table())
+}
+
+/// Expands `c` using the extensions in `exts`, which should include the
+/// built-in ones from `syntax_expander_table`
+pub fn expand_crate_with_exts(parse_sess: parse::parse_sess,
+                              cfg: ast::crate_cfg, c: @crate,
+                              exts: HashMap<~str, SyntaxExtension>)
+                           -> @crate {
     let afp = default_ast_fold();
     let cx: ext_ctxt = mk_ctxt(parse_sess, cfg);
     let f_pre = @AstFoldFns {
@@ -25,6 +25,10 @@ use ext::base;
 use ext::build::*;
 use extfmt::ct::*;
 
+use core::option;
+use core::uint;
+use core::vec;
+
 pub fn expand_syntax_ext(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
     -> base::MacResult {
     let args = get_exprs_from_tts(cx, copy tts);
@@ -47,6 +51,163 @@ pub fn expand_syntax_ext(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
     MRExpr(pieces_to_expr(cx, sp, pieces, args))
 }
 
+// The arguments after the format string. Those given as `name = expr` are
+// only their expressions here, with the name kept in `names`.
+struct Args {
+    exprs: ~[@ast::expr],
+    names: ~[Option<~str>],
+    // The number of conversions that refer to each argument
+    uses: ~[uint],
+    // The argument for the next conversion without a parameter
+    next: uint
+}
+
+fn split_args(cx: ext_ctxt, args: &[@ast::expr]) -> Args {
+    let mut exprs = ~[];
+    let mut names = ~[];
+    for args.each |arg| {
+        let name = match arg.node {
+          ast::expr_assign(lhs, _) => match lhs.node {
+            ast::expr_path(path) if !path.global &&
+                    path.idents.len() == 1u && path.types.is_empty() => {
+                Some(cx.str_of(path.idents[0]))
+            }
+            _ => None
+          },
+          _ => None
+        };
+        match name {
+          Some(ref n) => {
+            if names.contains(&Some(copy *n)) {
+                cx.span_err(arg.span,
+                            fmt!("duplicate argument named `%s` to fmt!",
+                                 *n));
+            }
+          }
+          None => {
+            if vec::any(names, |n| n.is_some()) {
+                cx.span_err(arg.span, ~"positional arguments to fmt! \
+                                        must come before named ones");
+            }
+          }
+        }
+        let expr = match arg.node {
+          ast::expr_assign(_, rhs) if name.is_some() => rhs,
+          _ => *arg
+        };
+        exprs.push(expr);
+        names.push(name);
+    }
+    let nargs = exprs.len();
+    Args { exprs: exprs, names: names, uses: vec::from_elem(nargs, 0u),
+           next: 0u }
+}
+
+// The index of the argument that `param` refers to, or of the next
+// argument if there is no parameter
+fn arg_index(cx: ext_ctxt, sp: span, args: &mut Args,
+             param: &Option<Param>) -> uint {
+    let i = match *param {
+      Some(ParamIndex(n)) => {
+        if n == 0u || n > args.exprs.len() {
+            cx.span_fatal(sp, fmt!("fmt! has no argument %u", n));
+        }
+        n - 1u
+      }
+      Some(ParamName(ref name)) => {
+        match vec::position(args.names, |n| *n == Some(copy *name)) {
+          Some(i) => i,
+          None => {
+            cx.span_fatal(sp, fmt!("fmt! has no argument named `%s`",
+                                   *name))
+          }
+        }
+      }
+      None => {
+        if args.next >= args.exprs.len() {
+            cx.span_fatal(sp, ~"not enough arguments to fmt! " +
+                              ~"for the given format string");
+        }
+        args.next += 1u;
+        args.next - 1u
+      }
+    };
+    args.uses[i] += 1u;
+    i
+}
+
+// The argument a `*` width or precision is taken from
+fn count_arg(cx: ext_ctxt, sp: span, args: &mut Args,
+             cnt: &Count) -> Option<uint> {
+    match *cnt {
+      CountIsParam(ref param) => {
+        Some(arg_index(cx, sp, args, &Some(copy *param)))
+      }
+      CountIsNextParam => Some(arg_index(cx, sp, args, &None)),
+      CountIs(_) | CountImplied => None
+    }
+}
+
+// The type of a literal, for checking it against the conversion it is
+// given to. Integer literals without a suffix are just "integer".
+fn literal_kind(e: @ast::expr) -> Option<~str> {
+    match e.node {
+      ast::expr_lit(lit) => match lit.node {
+        ast::lit_str(_) => Some(~"str"),
+        ast::lit_int(_, ast::ty_char) => Some(~"char"),
+        ast::lit_int(*) => Some(~"int"),
+        ast::lit_uint(*) => Some(~"uint"),
+        ast::lit_int_unsuffixed(_) => Some(~"integer"),
+        ast::lit_float(*) | ast::lit_float_unsuffixed(_) => Some(~"float"),
+        ast::lit_bool(_) => Some(~"bool"),
+        ast::lit_nil => Some(~"()")
+      },
+      ast::expr_vstore(e, _) => literal_kind(e),
+      _ => None
+    }
+}
+
+// Reports a literal argument of the wrong type for `what`, which expects
+// a value of type `expected`. Returns false if there was one.
+fn check_literal_arg(cx: ext_ctxt, what: &str, expected: &str,
+                     arg: @ast::expr) -> bool {
+    match literal_kind(arg) {
+      Some(ref kind) if *kind == ~"integer" => {
+        if expected != "int" && expected != "uint" {
+            cx.span_err(arg.span,
+                        fmt!("mismatched types: %s expects `%s`, found \
+                              an integer literal", what, expected));
+            return false;
+        }
+      }
+      Some(ref kind) if *kind != expected.to_owned() => {
+        cx.span_err(arg.span,
+                    fmt!("mismatched types: %s expects `%s`, found a `%s` \
+                          literal", what, expected, *kind));
+        return false;
+      }
+      _ => ()
+    }
+    true
+}
+
+// The type of argument a conversion expects, and how it is written
+fn conv_expects(t: Ty) -> Option<(~str, ~str)> {
+    match t {
+      TyBool => Some((~"bool", ~"%b")),
+      TyStr => Some((~"str", ~"%s")),
+      TyChar => Some((~"char", ~"%c")),
+      TyInt(Signed) => Some((~"int", ~"%d")),
+      TyInt(Unsigned) => Some((~"uint", ~"%u")),
+      TyBits => Some((~"uint", ~"%t")),
+      TyHex(CaseLower) => Some((~"uint", ~"%x")),
+      TyHex(CaseUpper) => Some((~"uint", ~"%X")),
+      TyOctal => Some((~"uint", ~"%o")),
+      TyFloat => Some((~"float", ~"%f")),
+      TyPoly => None
+    }
+}
+
 // FIXME (#2249): A lot of these functions for producing expressions can
 // probably be factored out in common with other code that builds
 // expressions.  Also: Cleanup the naming of these functions.
@@ -66,7 +227,9 @@ fn pieces_to_expr(cx: ext_ctxt, sp: span,
     // Produces an AST expression that represents a RT::conv record,
     // which tells the RT::conv* functions how to perform the conversion
 
-    fn make_rt_conv_expr(cx: ext_ctxt, sp: span, cnv: Conv) -> @ast::expr {
+    fn make_rt_conv_expr(cx: ext_ctxt, sp: span, cnv: Conv,
+                         width: Option<@ast::expr>,
+                         precision: Option<@ast::expr>) -> @ast::expr {
         fn make_flags(cx: ext_ctxt, sp: span, flags: ~[Flag]) -> @ast::expr {
             let mut tmp_expr = make_rt_path_expr(cx, sp, @~"flag_none");
             for flags.each |f| {
@@ -82,8 +245,10 @@ fn pieces_to_expr(cx: ext_ctxt, sp: span,
             }
             return tmp_expr;
         }
-        fn make_count(cx: ext_ctxt, sp: span, cnt: Count) -> @ast::expr {
-            match cnt {
+        // `arg` is the argument a `*` count is taken from
+        fn make_count(cx: ext_ctxt, sp: span, cnt: &Count,
+                      arg: Option<@ast::expr>) -> @ast::expr {
+            match *cnt {
               CountImplied => {
                 return make_rt_path_expr(cx, sp, @~"CountImplied");
               }
@@ -93,7 +258,11 @@ fn pieces_to_expr(cx: ext_ctxt, sp: span,
                 let count_is_args = ~[count_lit];
                 return mk_call_global(cx, sp, count_is_path, count_is_args);
               }
-              _ => cx.span_unimpl(sp, ~"unimplemented fmt! conversion")
+              CountIsParam(_) | CountIsNextParam => {
+                let arg = arg.get();
+                let count_is_path = make_path_vec(cx, @~"CountIs");
+                return mk_call_global(cx, arg.span, count_is_path, ~[arg]);
+              }
             }
         }
         fn make_ty(cx: ext_ctxt, sp: span, t: Ty) -> @ast::expr {
@@ -126,23 +295,28 @@ fn pieces_to_expr(cx: ext_ctxt, sp: span,
             )
         }
         let rt_conv_flags = make_flags(cx, sp, cnv.flags);
-        let rt_conv_width = make_count(cx, sp, cnv.width);
-        let rt_conv_precision = make_count(cx, sp, cnv.precision);
+        let rt_conv_width = make_count(cx, sp, &cnv.width, width);
+        let rt_conv_precision = make_count(cx, sp, &cnv.precision,
+                                           precision);
         let rt_conv_ty = make_ty(cx, sp, cnv.ty);
         make_conv_struct(cx, sp, rt_conv_flags, rt_conv_width,
                          rt_conv_precision, rt_conv_ty)
     }
     fn make_conv_call(cx: ext_ctxt, sp: span, conv_type: ~str, cnv: Conv,
+                      width: Option<@ast::expr>,
+                      precision: Option<@ast::expr>,
                       arg: @ast::expr) -> @ast::expr {
         let fname = ~"conv_" + conv_type;
         let path = make_path_vec(cx, @fname);
-        let cnv_expr = make_rt_conv_expr(cx, sp, cnv);
+        let cnv_expr = make_rt_conv_expr(cx, sp, cnv, width, precision);
         let args = ~[cnv_expr, arg];
         return mk_call_global(cx, arg.span, path, args);
     }
 
-    fn make_new_conv(cx: ext_ctxt, sp: span, cnv: Conv, arg: @ast::expr) ->
-       @ast::expr {
+    fn make_new_conv(cx: ext_ctxt, sp: span, cnv: Conv,
+                     width: Option<@ast::expr>,
+                     precision: Option<@ast::expr>,
+                     arg: @ast::expr) -> @ast::expr {
         // FIXME: Move validation code into core::extfmt (Issue #2249)
 
         fn is_signed_type(cnv: Conv) -> bool {
@@ -156,10 +330,6 @@ fn pieces_to_expr(cx: ext_ctxt, sp: span,
             }
         }
         let unsupported = ~"conversion not supported in fmt! string";
-        match cnv.param {
-          option::None => (),
-          _ => cx.span_unimpl(sp, unsupported)
-        }
         for cnv.flags.each |f| {
             match *f {
               FlagLeftJustify => (),
@@ -181,42 +351,25 @@ fn pieces_to_expr(cx: ext_ctxt, sp: span,
               _ => cx.span_unimpl(sp, unsupported)
             }
         }
-        match cnv.width {
-          CountImplied => (),
-          CountIs(_) => (),
-          _ => cx.span_unimpl(sp, unsupported)
-        }
-        match cnv.precision {
-          CountImplied => (),
-          CountIs(_) => (),
-          _ => cx.span_unimpl(sp, unsupported)
-        }
-        match cnv.ty {
-          TyStr => return make_conv_call(cx, arg.span, ~"str", cnv, arg),
-          TyInt(sign) => match sign {
-            Signed => return make_conv_call(cx, arg.span, ~"int", cnv, arg),
-            Unsigned => {
-                return make_conv_call(cx, arg.span, ~"uint", cnv, arg)
-            }
-          },
-          TyBool => return make_conv_call(cx, arg.span, ~"bool", cnv, arg),
-          TyChar => return make_conv_call(cx, arg.span, ~"char", cnv, arg),
-          TyHex(_) => {
-            return make_conv_call(cx, arg.span, ~"uint", cnv, arg);
-          }
-          TyBits => return make_conv_call(cx, arg.span, ~"uint", cnv, arg),
-          TyOctal => return make_conv_call(cx, arg.span, ~"uint", cnv, arg),
-          TyFloat => {
-            return make_conv_call(cx, arg.span, ~"float", cnv, arg);
+        let conv_type = match cnv.ty {
+          TyStr => ~"str",
+          TyInt(Signed) => ~"int",
+          TyInt(Unsigned) | TyHex(_) | TyBits | TyOctal => ~"uint",
+          TyBool => ~"bool",
+          TyChar => ~"char",
+          TyFloat => ~"float",
+          TyPoly => {
+            return make_conv_call(cx, arg.span, ~"poly", cnv, width,
+                                  precision, mk_addr_of(cx, sp, arg));
           }
-          TyPoly => return make_conv_call(cx, arg.span, ~"poly", cnv,
-                       mk_addr_of(cx, sp, arg))
-        }
+        };
+        make_conv_call(cx, arg.span, conv_type, cnv, width, precision, arg)
     }
     fn log_conv(c: Conv) {
         match c.param {
-          Some(p) => { log(debug, ~"param: " + p.to_str()); }
-          _ => debug!("param: none")
+          Some(ParamIndex(p)) => { log(debug, ~"param: " + p.to_str()); }
+          Some(ParamName(ref name)) => debug!("param: named %s", *name),
+          None => debug!("param: none")
         }
         for c.flags.each |f| {
             match *f {
@@ -230,16 +383,14 @@ fn pieces_to_expr(cx: ext_ctxt, sp: span,
         match c.width {
           CountIs(i) => log(
               debug, ~"width: count is " + i.to_str()),
-          CountIsParam(i) => log(
-              debug, ~"width: count is param " + i.to_str()),
+          CountIsParam(ref p) => debug!("width: count is param %?", *p),
           CountIsNextParam => debug!("width: count is next param"),
           CountImplied => debug!("width: count is implied")
         }
         match c.precision {
           CountIs(i) => log(
               debug, ~"prec: count is " + i.to_str()),
-          CountIsParam(i) => log(
-              debug, ~"prec: count is param " + i.to_str()),
+          CountIsParam(ref p) => debug!("prec: count is param %?", *p),
           CountIsNextParam => debug!("prec: count is next param"),
           CountImplied => debug!("prec: count is implied")
         }
@@ -262,43 +413,119 @@ fn pieces_to_expr(cx: ext_ctxt, sp: span,
         }
     }
     let fmt_sp = args[0].span;
-    let mut n = 0u;
+    let mut args = split_args(cx, args.tail());
+
+    // Work out which argument each conversion, and each `*` width and
+    // precision, takes its value from. The width comes before the
+    // precision and both before the value, as in printf.
+    let mut convs = ~[];
+    let mut all_sequential = true;
+    for pieces.each |pc| {
+        match *pc {
+          PieceString(_) => (),
+          PieceConv(ref conv) => {
+            debug!("Building conversion:");
+            log_conv(copy *conv);
+            let width = count_arg(cx, sp, &mut args, &conv.width);
+            let precision = count_arg(cx, sp, &mut args, &conv.precision);
+            let value = arg_index(cx, sp, &mut args, &conv.param);
+            match conv.width { CountIsParam(_) => all_sequential = false,
+                               _ => () }
+            match conv.precision { CountIsParam(_) => all_sequential = false,
+                                   _ => () }
+            if conv.param.is_some() { all_sequential = false; }
+            convs.push((width, precision, value));
+          }
+        }
+    }
+
+    let nargs = args.exprs.len();
+    if all_sequential && args.next < nargs {
+        cx.span_fatal
+            (sp, fmt!("too many arguments to fmt!. found %u, expected %u",
+                      nargs + 1u, args.next + 1u));
+    }
+    for uint::range(0u, nargs) |i| {
+        if args.uses[i] == 0u {
+            cx.span_err(args.exprs[i].span,
+                        ~"too many arguments to fmt!: this argument is \
+                          never used");
+        }
+    }
+
+    // An argument used more than once is evaluated once, into a temporary
+    // that each use dereferences
+    let mut stmts = ~[];
+    let mut arg_exprs = ~[];
+    for uint::range(0u, nargs) |i| {
+        let e = args.exprs[i];
+        if args.uses[i] > 1u {
+            let tmp = cx.parse_sess().interner.gensym(@~"__fmt_arg");
+            stmts.push(mk_local(cx, e.span, false, tmp,
+                                mk_addr_of(cx, e.span, e)));
+            arg_exprs.push(mk_unary(cx, e.span, ast::deref,
+                                    mk_path(cx, e.span, ~[tmp])));
+        } else {
+            arg_exprs.push(e);
+        }
+    }
+
     let mut piece_exprs = ~[];
-    let nargs = args.len();
+    let mut n = 0u;
+    let mut mismatched = false;
     for pieces.each |pc| {
         match *pc {
           PieceString(ref s) => {
             piece_exprs.push(mk_uniq_str(cx, fmt_sp, (*s)))
           }
-          PieceConv(conv) => {
+          PieceConv(ref conv) => {
+            let (width, precision, value) = convs[n];
             n += 1u;
-            if n >= nargs {
-                cx.span_fatal(sp,
-                              ~"not enough arguments to fmt! " +
-                                  ~"for the given format string");
+            let count_expr = |i: Option<uint>| {
+                do option::map(&i) |i| {
+                    if !check_literal_arg(cx, "a `*` width or precision",
+                                          "uint", args.exprs[*i]) {
+                        mismatched = true;
+                    }
+                    arg_exprs[*i]
+                }
+            };
+            match conv_expects(conv.ty) {
+              Some((expected, spec)) => {
+                if !check_literal_arg(cx, fmt!("`%s`", spec), expected,
+                                      args.exprs[value]) {
+                    mismatched = true;
+                }
+              }
+              None => ()
             }
-            debug!("Building conversion:");
-            log_conv(conv);
-            let arg_expr = args[n];
-            let c_expr = make_new_conv(cx, fmt_sp, conv, arg_expr);
+            let c_expr = make_new_conv(cx, fmt_sp, copy *conv,
+                                       count_expr(width),
+                                       count_expr(precision),
+                                       arg_exprs[value]);
             piece_exprs.push(c_expr);
           }
         }
     }
-    let expected_nargs = n + 1u; // n conversions + the fmt string
 
-    if expected_nargs < nargs {
-        cx.span_fatal
-            (sp, fmt!("too many arguments to fmt!. found %u, expected %u",
-                           nargs, expected_nargs));
+    // The conversions for mismatched literals would only fail to typecheck
+    // again, so leave them out once they have been reported
+    if mismatched {
+        return mk_uniq_str(cx, fmt_sp, ~"");
     }
 
     let arg_vec = mk_fixed_vec_e(cx, fmt_sp, piece_exprs);
-    return mk_call_global(cx,
-                          fmt_sp,
-                          ~[cx.parse_sess().interner.intern(@~"str"),
-                            cx.parse_sess().interner.intern(@~"concat")],
-                          ~[arg_vec]);
+    let concat = mk_call_global(cx,
+                                fmt_sp,
+                                ~[cx.parse_sess().interner.intern(@~"str"),
+                                  cx.parse_sess().interner.intern(
+                                      @~"concat")],
+                                ~[arg_vec]);
+    if stmts.is_empty() {
+        concat
+    } else {
+        mk_block(cx, fmt_sp, ~[], stmts, Some(concat))
+    }
 }
 //
 // Local Variables:
@@ -95,9 +95,10 @@ pub fn expand_include(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
 pub fn expand_include_str(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
     -> base::MacResult {
     let file = get_single_str_from_tts(cx, sp, tts, "include_str!");
-    let res = io::read_whole_file_str(&res_rel_file(cx, sp, &Path(file)));
+    let path = res_rel_file(cx, sp, &Path(file));
+    let res = io::read_whole_file_str(&path);
     match res {
-      result::Ok(_) => { /* Continue. */ }
+      result::Ok(ref src) => record_included_file(cx, &path, copy *src),
       result::Err(ref e) => {
         cx.parse_sess().span_diagnostic.handler().fatal((*e));
       }
@@ -109,8 +110,11 @@ pub fn expand_include_str(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
 pub fn expand_include_bin(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
     -> base::MacResult {
     let file = get_single_str_from_tts(cx, sp, tts, "include_bin!");
-    match io::read_whole_file(&res_rel_file(cx, sp, &Path(file))) {
+    let path = res_rel_file(cx, sp, &Path(file));
+    match io::read_whole_file(&path) {
       result::Ok(src) => {
+        // The contents needn't be text, so the FileMap is left empty
+        record_included_file(cx, &path, ~"");
         let u8_exprs = vec::map(src, |char| {
             mk_u8(cx, sp, *char)
         });
@@ -122,6 +126,14 @@ pub fn expand_include_bin(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
     }
 }
 
+// Files pulled in by include_str! and include_bin! aren't parsed, but get
+// a FileMap anyway so that everything the crate was built from is listed
+// in the CodeMap
+fn record_included_file(cx: ext_ctxt, path: &Path, src: ~str) {
+    let fm = cx.codemap().new_filemap(path.to_str(), @src);
+    fm.next_line(fm.start_pos);
+}
+
 fn res_rel_file(cx: ext_ctxt, sp: codemap::span, arg: &Path) -> Path {
     // NB: relative paths are resolved relative to the compilation unit
     if !arg.is_absolute {
@@ -0,0 +1,203 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * Hygiene for `macro_rules!` expansions.
+ *
+ * The transcriber gives every identifier that the macro body contains
+ * literally a mark: a gensym with the same name, fresh for each
+ * expansion (see `new_marking_tt_reader`). Once the expansion has been
+ * parsed, the marks are kept only where they matter, on the local
+ * variables that the expansion itself binds in a pattern (in a `let`, a
+ * closure or fn argument, or a match arm) and the plain paths that refer
+ * to them. Everything else, such as items, fields, methods and types,
+ * gets its original identifier back.
+ *
+ * A marked binding is a different identifier from any at the call site,
+ * so the macro's temporaries can't capture the caller's variables passed
+ * in through syntax variables, nor the other way around. Resolve looks a
+ * marked path up under its plain name if nothing binds the mark.
+ */
+
+use core::prelude::*;
+
+use ast::{expr, expr_, expr_mac, expr_path, ident, item, item_, item_mac};
+use ast::{mac, mac_invoc_tt, pat_, pat_ident, path, stmt, stmt_, stmt_mac};
+use ast::{token_tree, tt_delim, tt_seq, tt_tok};
+use codemap::{span, spanned};
+use fold::{ast_fold, default_ast_fold, make_fold, AstFoldFns};
+use parse::token::{IDENT, ident_interner};
+use visit;
+
+use core::option;
+use std::oldmap::HashMap;
+
+/// Applies the marks of one expansion, recorded in `marks`, to an
+/// expression parsed from it
+pub fn hygienic_expr(intr: @ident_interner, marks: HashMap<ident, ident>,
+                     e: @expr) -> @expr {
+    let marked = marked_idents(marks);
+    let bound = marked_bindings(marked, |v| visit::visit_expr(e, (), v));
+    mk_fold(intr, marked, bound).fold_expr(e)
+}
+
+/// Applies the marks of one expansion to an item parsed from it
+pub fn hygienic_item(intr: @ident_interner, marks: HashMap<ident, ident>,
+                     i: @item) -> Option<@item> {
+    let marked = marked_idents(marks);
+    let bound = marked_bindings(marked, |v| visit::visit_item(i, (), v));
+    mk_fold(intr, marked, bound).fold_item(i)
+}
+
+/// Applies the marks of one expansion to a statement parsed from it
+pub fn hygienic_stmt(intr: @ident_interner, marks: HashMap<ident, ident>,
+                     s: @stmt) -> @stmt {
+    let marked = marked_idents(marks);
+    let bound = marked_bindings(marked, |v| visit::visit_stmt(s, (), v));
+    mk_fold(intr, marked, bound).fold_stmt(s)
+}
+
+// The set of marked identifiers, from the map of plain to marked ones
+fn marked_idents(marks: HashMap<ident, ident>) -> HashMap<ident, ()> {
+    let marked = HashMap();
+    for marks.each_value |id| {
+        marked.insert(*id, ());
+    }
+    marked
+}
+
+// The marked identifiers that a fragment binds in its patterns
+fn marked_bindings(marked: HashMap<ident, ()>, walk: fn(visit::vt<()>))
+                -> HashMap<ident, ()> {
+    let bound = HashMap();
+    let v = visit::mk_simple_visitor(@visit::SimpleVisitor {
+        visit_pat: |p| {
+            match p.node {
+              pat_ident(_, path, _) if binds_local(marked, path) => {
+                bound.insert(path.idents[0], ());
+              }
+              _ => ()
+            }
+        },
+        .. *visit::default_simple_visitor()
+    });
+    walk(v);
+    bound
+}
+
+// Whether `path` is a single identifier in `idents`, which is what a
+// local variable looks like
+fn binds_local(idents: HashMap<ident, ()>, path: @path) -> bool {
+    !path.global && path.idents.len() == 1u && path.types.is_empty() &&
+        idents.contains_key(&path.idents[0])
+}
+
+fn mk_fold(intr: @ident_interner, marked: HashMap<ident, ()>,
+           bound: HashMap<ident, ()>) -> ast_fold {
+    let afp = default_ast_fold();
+    let f_pre = @AstFoldFns {
+        fold_ident: |id, _fld| unmark(intr, marked, id),
+        fold_pat: |p, sp, fld| fold_pat(bound, p, sp, fld, afp.fold_pat),
+        fold_expr: |e, sp, fld| {
+            fold_expr(intr, marked, bound, e, sp, fld, afp.fold_expr)
+        },
+        fold_stmt: |s, sp, fld| {
+            fold_stmt(intr, marked, bound, s, sp, fld, afp.fold_stmt)
+        },
+        fold_item_underscore: |i, fld| {
+            fold_item_underscore(intr, marked, bound, i, fld,
+                                 afp.fold_item_underscore)
+        },
+        .. *afp
+    };
+    make_fold(f_pre)
+}
+
+fn unmark(intr: @ident_interner, marked: HashMap<ident, ()>,
+          id: ident) -> ident {
+    if marked.contains_key(&id) { intr.unmark(id) } else { id }
+}
+
+fn fold_pat(bound: HashMap<ident, ()>, p: pat_, sp: span, fld: ast_fold,
+            orig: fn@(pat_, span, ast_fold) -> (pat_, span))
+         -> (pat_, span) {
+    match p {
+      pat_ident(mode, path, sub) if binds_local(bound, path) => {
+        (pat_ident(mode, path, option::map(&sub, |x| fld.fold_pat(*x))), sp)
+      }
+      _ => orig(p, sp, fld)
+    }
+}
+
+fn fold_expr(intr: @ident_interner, marked: HashMap<ident, ()>,
+             bound: HashMap<ident, ()>, e: expr_, sp: span, fld: ast_fold,
+             orig: fn@(expr_, span, ast_fold) -> (expr_, span))
+          -> (expr_, span) {
+    match e {
+      expr_path(path) if binds_local(bound, path) => (expr_path(path), sp),
+      expr_mac(ref m) => (expr_mac(fold_mac(intr, marked, bound, m)), sp),
+      _ => orig(e, sp, fld)
+    }
+}
+
+fn fold_stmt(intr: @ident_interner, marked: HashMap<ident, ()>,
+             bound: HashMap<ident, ()>, s: stmt_, sp: span, fld: ast_fold,
+             orig: fn@(stmt_, span, ast_fold) -> (stmt_, span))
+          -> (stmt_, span) {
+    match s {
+      stmt_mac(ref m, semi) => {
+        (stmt_mac(fold_mac(intr, marked, bound, m), semi), sp)
+      }
+      _ => orig(s, sp, fld)
+    }
+}
+
+fn fold_item_underscore(intr: @ident_interner, marked: HashMap<ident, ()>,
+                        bound: HashMap<ident, ()>, i: item_, fld: ast_fold,
+                        orig: fn@(item_, ast_fold) -> item_) -> item_ {
+    match i {
+      item_mac(ref m) => item_mac(fold_mac(intr, marked, bound, m)),
+      _ => orig(i, fld)
+    }
+}
+
+// Macro invocations in the expansion are expanded later, so the marks in
+// their arguments are resolved now, the same way as in the parsed AST
+fn fold_mac(intr: @ident_interner, marked: HashMap<ident, ()>,
+            bound: HashMap<ident, ()>, m: &mac) -> mac {
+    match m.node {
+      mac_invoc_tt(path, ref tts) => {
+        spanned {
+            node: mac_invoc_tt(path, fold_tts(intr, marked, bound, *tts)),
+            span: m.span
+        }
+      }
+    }
+}
+
+fn fold_tts(intr: @ident_interner, marked: HashMap<ident, ()>,
+            bound: HashMap<ident, ()>, tts: &[token_tree])
+         -> ~[token_tree] {
+    do tts.map |tt| {
+        match *tt {
+          tt_tok(sp, IDENT(id, is_mod_name)) if !bound.contains_key(&id) => {
+            tt_tok(sp, IDENT(unmark(intr, marked, id), is_mod_name))
+          }
+          tt_delim(ref tts) => {
+            tt_delim(fold_tts(intr, marked, bound, *tts))
+          }
+          tt_seq(sp, ref tts, ref sep, zerok) => {
+            tt_seq(sp, fold_tts(intr, marked, bound, *tts), copy *sep,
+                   zerok)
+          }
+          _ => copy *tt
+        }
+    }
+}
@@ -16,9 +16,11 @@ use ast;
 use codemap::{span, spanned, dummy_sp};
 use ext::base::{ext_ctxt, MacResult, MRAny, MRDef, MacroDef, NormalTT};
 use ext::base;
+use ext::tt::hygiene::{hygienic_expr, hygienic_item, hygienic_stmt};
 use ext::tt::macro_parser::{error};
 use ext::tt::macro_parser::{named_match, matched_seq, matched_nonterminal};
 use ext::tt::macro_parser::{parse, parse_or_else, success, failure};
+use ext::tt::transcribe::new_marking_tt_reader;
 use parse::lexer::{new_tt_reader, reader};
 use parse::parser::Parser;
 use parse::token::special_idents;
@@ -26,6 +28,7 @@ use parse::token::{FAT_ARROW, SEMI, LBRACE, RBRACE, nt_matchers, nt_tt};
 use print;
 
 use core::io;
+use core::option;
 use std::oldmap::HashMap;
 
 pub fn add_new_extension(cx: ext_ctxt, sp: span, name: ident,
@@ -111,16 +114,21 @@ pub fn add_new_extension(cx: ext_ctxt, sp: span, name: ident,
                         _ => cx.span_bug(sp, ~"bad thing in rhs")
                     };
                     // rhs has holes ( `$id` and `$(...)` that need filled)
-                    let trncbr = new_tt_reader(s_d, itr, Some(named_matches),
-                                               rhs);
+                    let marks = HashMap();
+                    let trncbr = new_marking_tt_reader(s_d, itr,
+                                                       named_matches, rhs,
+                                                       marks);
                     let p = @Parser(cx.parse_sess(), cx.cfg(),
                                     trncbr as reader);
 
                     // Let the context choose how to interpret the result.
                     // Weird, but useful for X-macros.
-                    return MRAny(|| p.parse_expr(),
-                                  || p.parse_item(~[/* no attrs*/]),
-                                  || p.parse_stmt(~[/* no attrs*/]));
+                    return MRAny(
+                        || hygienic_expr(itr, marks, p.parse_expr()),
+                        || option::chain(p.parse_item(~[/* no attrs*/]),
+                                         |i| hygienic_item(itr, marks, i)),
+                        || hygienic_stmt(itr, marks,
+                                         p.parse_stmt(~[/* no attrs*/])));
                   }
                   failure(sp, ref msg) => if sp.lo >= best_fail_spot.lo {
                     best_fail_spot = sp;
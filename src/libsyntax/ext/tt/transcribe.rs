@@ -17,6 +17,7 @@ use codemap::{span, dummy_sp};
 use diagnostic::span_handler;
 use ext::tt::macro_parser::{named_match, matched_seq, matched_nonterminal};
 use parse::token::{EOF, INTERPOLATED, IDENT, Token, nt_ident, ident_interner};
+use parse::token::is_special_ident;
 use parse::lexer::TokenAndSpan;
 
 use core::option;
@@ -43,6 +44,8 @@ pub struct TtReader {
     interpolations: std::oldmap::HashMap<ident, @named_match>,
     repeat_idx: ~[uint],
     repeat_len: ~[uint],
+    /* for hygiene: the marked version of each identifier of `src` */
+    marks: Option<std::oldmap::HashMap<ident, ident>>,
     /* cached: */
     cur_tok: Token,
     cur_span: span
@@ -56,6 +59,28 @@ pub fn new_tt_reader(sp_diag: span_handler,
                      interp: Option<std::oldmap::HashMap<ident,@named_match>>,
                      src: ~[ast::token_tree])
                   -> @mut TtReader {
+    new_tt_reader_(sp_diag, itr, interp, src, None)
+}
+
+/** Like `new_tt_reader`, but for hygienic transcription: identifiers
+ *  that appear literally in `src`, rather than being substituted for a
+ *  syntax variable, are replaced by a fresh gensym of the same name, once
+ *  per name. `marks` records the replacements. */
+pub fn new_marking_tt_reader(sp_diag: span_handler,
+                             itr: @ident_interner,
+                             interp: std::oldmap::HashMap<ident,@named_match>,
+                             src: ~[ast::token_tree],
+                             marks: std::oldmap::HashMap<ident, ident>)
+                          -> @mut TtReader {
+    new_tt_reader_(sp_diag, itr, Some(interp), src, Some(marks))
+}
+
+fn new_tt_reader_(sp_diag: span_handler,
+                  itr: @ident_interner,
+                  interp: Option<std::oldmap::HashMap<ident,@named_match>>,
+                  src: ~[ast::token_tree],
+                  marks: Option<std::oldmap::HashMap<ident, ident>>)
+               -> @mut TtReader {
     let r = @mut TtReader {
         sp_diag: sp_diag,
         interner: itr,
@@ -72,6 +97,7 @@ pub fn new_tt_reader(sp_diag: span_handler,
         },
         repeat_idx: ~[],
         repeat_len: ~[],
+        marks: marks,
         /* dummy values, never read: */
         cur_tok: EOF,
         cur_span: dummy_sp()
@@ -101,6 +127,7 @@ pub pure fn dup_tt_reader(r: @mut TtReader) -> @mut TtReader {
         interpolations: r.interpolations,
         repeat_idx: copy r.repeat_idx,
         repeat_len: copy r.repeat_len,
+        marks: r.marks,
         cur_tok: r.cur_tok,
         cur_span: r.cur_span
     }
@@ -124,6 +151,27 @@ pure fn lookup_cur_matched_by_matched(r: @mut TtReader,
 fn lookup_cur_matched(r: @mut TtReader, name: ident) -> @named_match {
     lookup_cur_matched_by_matched(r, r.interpolations.get(&name))
 }
+
+// Marks an identifier token of the macro body, if this reader marks them
+fn mark_token(r: @mut TtReader, tok: Token) -> Token {
+    let marks = match r.marks {
+      Some(marks) => marks,
+      None => return tok
+    };
+    match tok {
+      IDENT(id, is_mod_name) if !is_special_ident(id) => {
+        match marks.find(&id) {
+          Some(marked) => IDENT(marked, is_mod_name),
+          None => {
+            let marked = r.interner.gensym(r.interner.get(id));
+            marks.insert(id, marked);
+            IDENT(marked, is_mod_name)
+          }
+        }
+      }
+      _ => tok
+    }
+}
 enum lis {
     lis_unconstrained, lis_constraint(uint, ident), lis_contradiction(~str)
 }
@@ -210,7 +258,7 @@ pub fn tt_next_token(r: @mut TtReader) -> TokenAndSpan {
             // if this could be 0-length, we'd need to potentially recur here
           }
           tt_tok(sp, copy tok) => {
-            r.cur_span = sp; r.cur_tok = tok;
+            r.cur_span = sp; r.cur_tok = mark_token(r, tok);
             r.cur.idx += 1u;
             return ret_val;
           }
@@ -138,7 +138,7 @@ fn fold_ty_param_bound(tpb: ty_param_bound, fld: ast_fold) -> ty_param_bound {
 }
 
 pub fn fold_ty_param(tp: ty_param, fld: ast_fold) -> ty_param {
-    ast::ty_param { ident: /* FIXME (#2543) */ copy tp.ident,
+    ast::ty_param { ident: fld.fold_ident(tp.ident),
                     id: fld.new_id(tp.id),
                     bounds: @tp.bounds.map(|x| fold_ty_param_bound(*x, fld) )}
 }
@@ -158,8 +158,39 @@ pub fn noop_fold_crate(c: crate_, fld: ast_fold) -> crate_ {
     }
 }
 
-fn noop_fold_view_item(vi: view_item_, _fld: ast_fold) -> view_item_ {
-    return /* FIXME (#2543) */ copy vi;
+fn noop_fold_view_item(vi: view_item_, fld: ast_fold) -> view_item_ {
+    // Node ids are left alone, as they always have been
+    fn fold_view_path(vp: @view_path, fld: ast_fold) -> @view_path {
+        let node = match vp.node {
+          view_path_simple(id, pth, ns, node_id) => {
+            view_path_simple(fld.fold_ident(id), fld.fold_path(pth), ns,
+                             node_id)
+          }
+          view_path_glob(pth, node_id) => {
+            view_path_glob(fld.fold_path(pth), node_id)
+          }
+          view_path_list(pth, ref ids, node_id) => {
+            let ids = do ids.map |id| {
+                spanned { node: ast::path_list_ident_ {
+                              name: fld.fold_ident(id.node.name),
+                              .. id.node },
+                          span: id.span }
+            };
+            view_path_list(fld.fold_path(pth), ids, node_id)
+          }
+        };
+        @spanned { node: node, span: vp.span }
+    }
+
+    match vi {
+      view_item_use(id, ref metas, node_id) => {
+        view_item_use(fld.fold_ident(id), /* FIXME (#2543) */ copy *metas,
+                      node_id)
+      }
+      view_item_import(ref vps) => {
+        view_item_import(vps.map(|vp| fold_view_path(*vp, fld)))
+      }
+    }
 }
 
 
@@ -206,9 +237,10 @@ pub fn noop_fold_item(&&i: @item, fld: ast_fold) -> Option<@item> {
 
 fn noop_fold_struct_field(&&sf: @struct_field, fld: ast_fold)
                        -> @struct_field {
-    @spanned { node: ast::struct_field_ { kind: copy sf.node.kind,
-                                          id: sf.node.id,
-                                          ty: fld.fold_ty(sf.node.ty) },
+    @spanned { node: ast::struct_field_ {
+                   kind: fold_struct_field_kind(sf.node.kind, fld),
+                   id: sf.node.id,
+                   ty: fld.fold_ty(sf.node.ty) },
                span: sf.span }
 }
 
@@ -246,7 +278,12 @@ pub fn noop_fold_item_underscore(i: item_, fld: ast_fold) -> item_ {
           item_trait(tps, traits, ref methods) => {
               let methods = do (*methods).map |method| {
                   match *method {
-                      required(*) => copy *method,
+                      required(ref m) => {
+                          required(ast::ty_method {
+                              ident: fld.fold_ident(m.ident),
+                              .. copy *m
+                          })
+                      }
                       provided(method) => provided(fld.fold_method(method))
                   }
               };
@@ -285,10 +322,21 @@ fn fold_trait_ref(&&p: @trait_ref, fld: ast_fold) -> @trait_ref {
     }
 }
 
+fn fold_struct_field_kind(kind: struct_field_kind,
+                          fld: ast_fold) -> struct_field_kind {
+    match kind {
+      named_field(id, mutbl, vis) => {
+        named_field(fld.fold_ident(id), mutbl, vis)
+      }
+      unnamed_field => unnamed_field
+    }
+}
+
 fn fold_struct_field(&&f: @struct_field, fld: ast_fold) -> @struct_field {
-    @spanned { node: ast::struct_field_ { kind: copy f.node.kind,
-                                          id: fld.new_id(f.node.id),
-                                          ty: fld.fold_ty(f.node.ty) },
+    @spanned { node: ast::struct_field_ {
+                   kind: fold_struct_field_kind(f.node.kind, fld),
+                   id: fld.new_id(f.node.id),
+                   ty: fld.fold_ty(f.node.ty) },
                span: fld.new_span(f.span) }
 }
 
@@ -353,7 +401,7 @@ pub fn noop_fold_pat(p: pat_, fld: ast_fold) -> pat_ {
           pat_rec(fields, etc) => {
             let fs = do fields.map |f| {
                 ast::field_pat {
-                    ident: /* FIXME (#2543) */ copy f.ident,
+                    ident: fld.fold_ident(f.ident),
                     pat: fld.fold_pat(f.pat),
                 }
             };
@@ -363,7 +411,7 @@ pub fn noop_fold_pat(p: pat_, fld: ast_fold) -> pat_ {
             let pth_ = fld.fold_path(pth);
             let fs = do fields.map |f| {
                 ast::field_pat {
-                    ident: /* FIXME (#2543) */ copy f.ident,
+                    ident: fld.fold_ident(f.ident),
                     pat: fld.fold_pat(f.pat)
                 }
             };
@@ -625,7 +673,7 @@ fn noop_fold_variant(v: variant_, fld: ast_fold) -> variant_ {
       None => None
     };
     ast::variant_ {
-        name: /* FIXME (#2543) */ copy v.name,
+        name: fld.fold_ident(v.name),
         attrs: attrs,
         kind: kind,
         id: fld.new_id(v.id),
@@ -718,7 +766,7 @@ pub impl ast_fold_fns: ast_fold {
     fn fold_struct_field(&&sf: @struct_field) -> @struct_field {
         @spanned {
             node: ast::struct_field_ {
-                kind: copy sf.node.kind,
+                kind: fold_struct_field_kind(sf.node.kind, self as ast_fold),
                 id: sf.node.id,
                 ty: (self as ast_fold).fold_ty(sf.node.ty),
             },
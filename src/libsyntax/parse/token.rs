@@ -352,6 +352,12 @@ pub mod special_idents {
     pub const type_self: ident = ident { repr: 36 };    // `Self`
 }
 
+/// Whether `id` is one of the `special_idents`, which some passes
+/// compare by index rather than by name
+pub pure fn is_special_ident(id: ast::ident) -> bool {
+    id.repr <= special_idents::type_self.repr
+}
+
 pub struct ident_interner {
     priv interner: Interner<@~str>,
 }
@@ -363,6 +369,11 @@ pub impl ident_interner {
     fn gensym(val: @~str) -> ast::ident {
         ast::ident { repr: self.interner.gensym(val) }
     }
+    /// The ident with the same name as `idx` but without the hygiene mark
+    /// (a gensym of the name) that macro expansion may have given it
+    fn unmark(idx: ast::ident) -> ast::ident {
+        self.intern(self.get(idx))
+    }
     pure fn get(idx: ast::ident) -> @~str {
         self.interner.get(idx.repr)
     }
@@ -17,6 +17,22 @@
 #include <execinfo.h>
 #endif
 
+// The size the system allocator actually reserved for a block, so that
+// heap usage can be tracked without storing sizes in the header
+#if defined(__APPLE__)
+#include <malloc/malloc.h>
+#  define USABLE_SIZE(p) malloc_size(p)
+#elif defined(__WIN32__)
+#include <malloc.h>
+#  define USABLE_SIZE(p) _msize(p)
+#elif defined(__FreeBSD__)
+#include <malloc_np.h>
+#  define USABLE_SIZE(p) malloc_usable_size(p)
+#else
+#include <malloc.h>
+#  define USABLE_SIZE(p) malloc_usable_size(p)
+#endif
+
 #if RUSTRT_TRACK_ALLOCATIONS >= 1
 // For some platforms, 16 byte alignment is required.
 #  define PTR_SIZE 16
@@ -37,12 +53,14 @@ void *memory_region::get_data(alloc_header *ptr) {
 
 memory_region::memory_region(rust_env *env, bool synchronized) :
     _env(env), _parent(NULL), _live_allocations(0),
+    _live_bytes(0), _peak_bytes(0), _track_bytes(false),
     _detailed_leaks(env->detailed_leaks),
     _synchronized(synchronized) {
 }
 
 memory_region::memory_region(memory_region *parent) :
     _env(parent->_env), _parent(parent), _live_allocations(0),
+    _live_bytes(0), _peak_bytes(0), _track_bytes(false),
     _detailed_leaks(parent->_detailed_leaks),
     _synchronized(parent->_synchronized) {
 }
@@ -57,6 +75,39 @@ void memory_region::dec_alloc() {
     sync::decrement(_live_allocations);
 }
 
+void memory_region::add_bytes(alloc_header *alloc) {
+    intptr_t live = sync::add(_live_bytes, (intptr_t)USABLE_SIZE(alloc));
+    intptr_t peak = sync::read(_peak_bytes);
+    while (live > peak &&
+           !sync::compare_and_swap(&_peak_bytes, peak, live)) {
+        peak = sync::read(_peak_bytes);
+    }
+}
+
+void memory_region::dec_bytes(alloc_header *alloc) {
+    sync::subtract(_live_bytes, (intptr_t)USABLE_SIZE(alloc));
+}
+
+void memory_region::start_tracking_bytes() {
+    _live_bytes = 0;
+    _peak_bytes = 0;
+    _track_bytes = true;
+}
+
+size_t memory_region::live_bytes() {
+    intptr_t live = sync::read(_live_bytes);
+    return live > 0 ? live : 0;
+}
+
+size_t memory_region::peak_bytes() {
+    intptr_t peak = sync::read(_peak_bytes);
+    return peak > 0 ? peak : 0;
+}
+
+void memory_region::reset_peak_bytes() {
+    _peak_bytes = sync::read(_live_bytes);
+}
+
 void memory_region::free(void *mem) {
     // printf("free: ptr 0x%" PRIxPTR" region=%p\n", (uintptr_t) mem, this);
     if (!mem) { return; }
@@ -71,6 +122,9 @@ void memory_region::free(void *mem) {
     }
     release_alloc(mem);
     maybe_poison(mem);
+    if (_track_bytes) {
+        dec_bytes(alloc);
+    }
     ::free(alloc);
 }
 
@@ -84,6 +138,9 @@ memory_region::realloc(void *mem, size_t orig_size) {
 #   if RUSTRT_TRACK_ALLOCATIONS >= 1
     assert(alloc->magic == MAGIC);
 #   endif
+    if (mem && _track_bytes) {
+        dec_bytes(alloc);
+    }
 
     size_t size = orig_size + HEADER_SIZE;
     alloc_header *newMem = (alloc_header *)::realloc(alloc, size);
@@ -94,6 +151,9 @@ memory_region::realloc(void *mem, size_t orig_size) {
                 (long int) size);
         abort();
     }
+    if (_track_bytes) {
+        add_bytes(newMem);
+    }
 
 #   if RUSTRT_TRACK_ALLOCATIONS >= 1
     assert(newMem->magic == MAGIC);
@@ -132,6 +192,9 @@ memory_region::malloc(size_t size, const char *tag, bool zero) {
                 (long int) size);
         abort();
     }
+    if (_track_bytes) {
+        add_bytes(mem);
+    }
 
 #   if RUSTRT_TRACK_ALLOCATIONS >= 1
     mem->magic = MAGIC;
@@ -57,6 +57,14 @@ private:
     rust_env *_env;
     memory_region *_parent;
     int _live_allocations;
+    // Bytes handed out by the system allocator since start_tracking_bytes(),
+    // net of those freed, and the most there have been at once since the
+    // last reset_peak_bytes(). Blocks allocated before tracking started may
+    // be freed after it, so these can go below zero. Measuring blocks isn't
+    // free, so it is only done once tracking has been asked for.
+    intptr_t _live_bytes;
+    intptr_t _peak_bytes;
+    bool _track_bytes;
     array_list<alloc_header *> _allocation_list;
     const bool _detailed_leaks;
     const bool _synchronized;
@@ -64,6 +72,8 @@ private:
 
     void add_alloc();
     void dec_alloc();
+    void add_bytes(alloc_header *alloc);
+    void dec_bytes(alloc_header *alloc);
     void maybe_poison(void *mem);
 
     void release_alloc(void *mem);
@@ -81,6 +91,10 @@ public:
     void *calloc(size_t size, const char *tag);
     void *realloc(void *mem, size_t size);
     void free(void *mem);
+    void start_tracking_bytes();
+    size_t live_bytes();
+    size_t peak_bytes();
+    void reset_peak_bytes();
     ~memory_region();
  };
 
@@ -219,6 +219,148 @@ debug_abi_2(floats f) {
     return ff;
 }
 
+/* One helper per shape of struct that the x86_64 calling convention
+ * classifies differently. Each returns its argument changed in a way the
+ * caller can check, so that both passing and returning are exercised. */
+
+struct two_u8s { uint8_t a; uint8_t b; };
+struct three_u16s { uint16_t a; uint16_t b; uint16_t c; };
+struct two_u32s { uint32_t a; uint32_t b; };
+struct three_u32s { uint32_t a; uint32_t b; uint32_t c; };
+struct two_u64s { uint64_t a; uint64_t b; };
+struct three_u64s { uint64_t a; uint64_t b; uint64_t c; };
+struct two_f32s { float a; float b; };
+struct three_f32s { float a; float b; float c; };
+struct two_f64s { double a; double b; };
+struct three_f64s { double a; double b; double c; };
+struct f32s_and_f64 { float a; float b; double c; };
+struct u32_and_f32 { uint32_t a; float b; };
+struct f64_and_u32 { double a; uint32_t b; };
+struct u16s_and_f32 { uint16_t a[3]; float b; };
+struct nested { two_u8s a; double b; };
+struct f32_array { float a[4]; };
+
+extern "C" two_u8s
+rust_dbg_abi_two_u8s(two_u8s s) {
+    two_u8s r = { (uint8_t)(s.b + 1), (uint8_t)(s.a + 1) };
+    return r;
+}
+
+extern "C" three_u16s
+rust_dbg_abi_three_u16s(three_u16s s) {
+    three_u16s r = { (uint16_t)(s.c + 1), s.b, (uint16_t)(s.a + 1) };
+    return r;
+}
+
+extern "C" two_u32s
+rust_dbg_abi_two_u32s(two_u32s s) {
+    two_u32s r = { s.b + 1, s.a + 1 };
+    return r;
+}
+
+extern "C" three_u32s
+rust_dbg_abi_three_u32s(three_u32s s) {
+    three_u32s r = { s.c + 1, s.b, s.a + 1 };
+    return r;
+}
+
+extern "C" two_u64s
+rust_dbg_abi_two_u64s(two_u64s s) {
+    two_u64s r = { s.b + 1, s.a + 1 };
+    return r;
+}
+
+extern "C" three_u64s
+rust_dbg_abi_three_u64s(three_u64s s) {
+    three_u64s r = { s.c + 1, s.b, s.a + 1 };
+    return r;
+}
+
+extern "C" two_f32s
+rust_dbg_abi_two_f32s(two_f32s s) {
+    two_f32s r = { s.b + 1.0f, s.a - 1.0f };
+    return r;
+}
+
+extern "C" three_f32s
+rust_dbg_abi_three_f32s(three_f32s s) {
+    three_f32s r = { s.c + 1.0f, s.b, s.a - 1.0f };
+    return r;
+}
+
+extern "C" two_f64s
+rust_dbg_abi_two_f64s(two_f64s s) {
+    two_f64s r = { s.b + 1.0, s.a - 1.0 };
+    return r;
+}
+
+extern "C" three_f64s
+rust_dbg_abi_three_f64s(three_f64s s) {
+    three_f64s r = { s.c + 1.0, s.b, s.a - 1.0 };
+    return r;
+}
+
+extern "C" f32s_and_f64
+rust_dbg_abi_f32s_and_f64(f32s_and_f64 s) {
+    f32s_and_f64 r = { s.b + 1.0f, s.a - 1.0f, s.c * 2.0 };
+    return r;
+}
+
+extern "C" u32_and_f32
+rust_dbg_abi_u32_and_f32(u32_and_f32 s) {
+    u32_and_f32 r = { s.a + 1, s.b * 2.0f };
+    return r;
+}
+
+extern "C" f64_and_u32
+rust_dbg_abi_f64_and_u32(f64_and_u32 s) {
+    f64_and_u32 r = { s.a * 2.0, s.b + 1 };
+    return r;
+}
+
+extern "C" u16s_and_f32
+rust_dbg_abi_u16s_and_f32(u16s_and_f32 s) {
+    u16s_and_f32 r = { { s.a[2], s.a[1], s.a[0] }, s.b * 2.0f };
+    return r;
+}
+
+extern "C" nested
+rust_dbg_abi_nested(nested s) {
+    nested r = { { s.a.b, s.a.a }, s.b * 2.0 };
+    return r;
+}
+
+extern "C" f32_array
+rust_dbg_abi_f32_array(f32_array s) {
+    f32_array r = { { s.a[3], s.a[2], s.a[1], s.a[0] } };
+    return r;
+}
+
+/* These take up all but one integer or SSE register before the struct,
+ * which then has to go on the stack. */
+
+extern "C" two_u64s
+rust_dbg_abi_u64s_then_two_u64s(uint64_t a, uint64_t b, uint64_t c,
+                                uint64_t d, uint64_t e, two_u64s s) {
+    two_u64s r = { a + b + c + d + e + s.a, s.b };
+    return r;
+}
+
+extern "C" two_f64s
+rust_dbg_abi_f64s_then_two_f64s(double a, double b, double c, double d,
+                                double e, double f, double g, two_f64s s) {
+    two_f64s r = { a + b + c + d + e + f + g + s.a, s.b };
+    return r;
+}
+
+/* The hidden pointer to the returned struct takes an integer register. */
+extern "C" three_u64s
+rust_dbg_abi_sret_then_two_u64s(uint64_t a, uint64_t b, uint64_t c,
+                                uint64_t d, two_u64s s) {
+    three_u64s r = { a + b + c + d, s.a, s.b };
+    return r;
+}
+
 /* Debug builtins for std::dbg. */
 
 static void
@@ -611,6 +753,74 @@ rust_get_task() {
     return rust_get_current_task();
 }
 
+extern "C" CDECL void
+rust_task_track_heap_bytes() {
+    rust_task *task = rust_get_current_task();
+    task->local_region.start_tracking_bytes();
+}
+
+extern "C" CDECL void
+rust_task_heap_bytes(size_t *live, size_t *peak) {
+    rust_task *task = rust_get_current_task();
+    *live = task->local_region.live_bytes();
+    *peak = task->local_region.peak_bytes();
+}
+
+extern "C" CDECL void
+rust_task_reset_heap_peak() {
+    rust_task *task = rust_get_current_task();
+    task->local_region.reset_peak_bytes();
+}
+
+// Dynamic library loading, used by rustc to load syntax extension plugins.
+// Each returns NULL on failure, after which rust_dlerror describes what
+// went wrong.
+
+#if defined(__WIN32__)
+extern "C" CDECL void *
+rust_dlopen(const char *path) {
+    return (void *)LoadLibraryA(path);
+}
+
+extern "C" CDECL void *
+rust_dlsym(void *lib, const char *name) {
+    return (void *)GetProcAddress((HMODULE)lib, name);
+}
+
+extern "C" CDECL void
+rust_dlclose(void *lib) {
+    FreeLibrary((HMODULE)lib);
+}
+
+extern "C" CDECL const char *
+rust_dlerror() {
+    static char buf[64];
+    snprintf(buf, sizeof(buf), "error %lu", (unsigned long)GetLastError());
+    return buf;
+}
+#else
+extern "C" CDECL void *
+rust_dlopen(const char *path) {
+    return dlopen(path, RTLD_NOW | RTLD_LOCAL);
+}
+
+extern "C" CDECL void *
+rust_dlsym(void *lib, const char *name) {
+    return dlsym(lib, name);
+}
+
+extern "C" CDECL void
+rust_dlclose(void *lib) {
+    dlclose(lib);
+}
+
+extern "C" CDECL const char *
+rust_dlerror() {
+    const char *err = dlerror();
+    return err ? err : "unknown error";
+}
+#endif
+
 extern "C" CDECL stk_seg *
 rust_get_stack_segment() {
     return rust_get_current_task()->stk;
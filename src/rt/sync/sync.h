@@ -39,6 +39,16 @@ public:
         return __sync_sub_and_fetch(&address, 1);
     }
 
+    template <class T>
+    static T add(T &address, T delta) {
+        return __sync_add_and_fetch(&address, delta);
+    }
+
+    template <class T>
+    static T subtract(T &address, T delta) {
+        return __sync_sub_and_fetch(&address, delta);
+    }
+
     template <class T>
     static T read(T *address) {
         return __sync_add_and_fetch(address, 0);
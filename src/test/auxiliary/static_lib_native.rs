@@ -0,0 +1,27 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:--staticlib
+
+#[link(name = "static_lib_native", vers = "0.1")];
+
+// When this crate is a static library these link arguments are only used
+// when linking the crates that use it
+#[nolink]
+#[link_args = "-lm"]
+extern mod libm {
+    fn cos(x: f64) -> f64;
+}
+
+pub fn cosine(x: f64) -> f64 {
+    unsafe { libm::cos(x) }
+}
+
+pub fn twice(x: uint) -> uint { x * 2u }
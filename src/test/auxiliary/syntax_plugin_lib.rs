@@ -0,0 +1,33 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern mod std;
+extern mod syntax;
+
+use std::oldmap::HashMap;
+use syntax::ast;
+use syntax::codemap::span;
+use syntax::ext::base::{ext_ctxt, MacResult, MRExpr, NormalTT};
+use syntax::ext::base::{SyntaxExpanderTT, SyntaxExtension};
+use syntax::ext::base::check_zero_tts;
+use syntax::ext::build::mk_uint;
+
+fn expand_forty_two(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
+                 -> MacResult {
+    check_zero_tts(cx, sp, tts, "forty_two!");
+    MRExpr(mk_uint(cx, sp, 42u))
+}
+
+#[no_mangle]
+pub fn rust_syntax_plugin_registrar(exts: HashMap<~str, SyntaxExtension>) {
+    exts.insert(~"forty_two",
+                NormalTT(SyntaxExpanderTT { expander: expand_forty_two,
+                                            span: None }));
+}
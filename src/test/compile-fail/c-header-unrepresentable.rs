@@ -0,0 +1,51 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:--c-header
+
+struct Point { x: i32, y: i32 }
+
+struct Named { name: ~str }
+
+#[no_mangle]
+pub extern fn move_point(_p: *mut Point, _dx: i32) -> i32 { 0 }
+
+#[no_mangle]
+pub extern fn boxed(_x: @int) -> int { 0 } //~ ERROR type `@int` has no C representation
+
+#[no_mangle]
+pub extern fn named(_n: &Named) -> u8 { 0 } //~ ERROR type `~str` has no C representation
+
+#[no_mangle]
+pub extern fn vec_len(_v: *u8, _n: uint) -> ~[u8] { ~[] } //~ ERROR type `~[u8]` has no C representation
+
+struct Guard { fd: i32 }
+
+impl Drop for Guard {
+    fn finalize(&self) {}
+}
+
+#[no_mangle]
+pub extern fn close(_g: *Guard) {} //~ ERROR struct `Guard` has a destructor
+
+mod geo {
+    pub struct Point { x: f64, y: f64 }
+}
+
+struct geo_Point { x: f32, y: f32 }
+
+#[no_mangle]
+pub extern fn nearest(_p: *geo::Point, _q: *geo_Point) {}
+//~^ ERROR would both be `struct geo_Point` in C
+
+// Not exported to C, so not checked
+pub extern fn mangled(_x: @int) -> int { 0 }
+
+fn main() {}
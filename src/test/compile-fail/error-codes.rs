@@ -0,0 +1,39 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Errors with a code print it after the message
+
+struct Point { x: int, y: int }
+
+fn add(a: int, b: int) -> int { a + b }
+
+fn id<T>(x: T) -> T { x }
+
+fn typeck(p: Point) {
+    let _: int = true; //~ ERROR (expected int but found bool) [E0016]
+    p.frobnicate();
+    //~^ ERROR any method in scope named `frobnicate` [E0023]
+    p.z; //~ ERROR but no field or method with that name was found [E0024]
+    add(1); //~ ERROR takes 2 parameters but 1 parameter was supplied [E0025]
+    id::<int, int>(5);
+    //~^ ERROR too many type parameters provided for this item [E0026]
+    Point { x: 1, y: 2, z: 3 }; //~ ERROR has no field named `z` [E0027]
+    Point { x: 1, x: 2, y: 3 }; //~ ERROR `x` specified more than once [E0028]
+    Point { x: 1 }; //~ ERROR missing field: `y` [E0029]
+}
+
+fn main() {
+    let x = Some(1);
+    match x {
+        Some(_) => (),
+        Some(1) => (), //~ ERROR unreachable pattern [E0001]
+        None => ()
+    }
+}
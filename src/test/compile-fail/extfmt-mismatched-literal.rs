@@ -0,0 +1,16 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn main() {
+    let _a = fmt!("%d", "ten"); //~ ERROR `%d` expects `int`, found a `str`
+    let _b = fmt!("%s", 10u); //~ ERROR `%s` expects `str`, found a `uint`
+    let _c = fmt!("%f", 1); //~ ERROR `%f` expects `float`, found an integer
+    let _d = fmt!("%*s", 'c', "x"); //~ ERROR width or precision expects `uint`
+}
@@ -0,0 +1,27 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -D while-true -D deprecated-mode -W unused-imports
+
+use core::either::Right; //~ WARNING unused import
+//~^ NOTE suggestion: remove this
+
+fn double(+x: int) -> int { //~ ERROR argument 1 uses by-copy mode
+//~^ NOTE suggestion: remove this
+    x * 2
+}
+
+fn main() {
+    while true { //~ ERROR denote infinite loops
+    //~^ NOTE suggestion: replace with `loop`
+        double(1);
+        break;
+    }
+}
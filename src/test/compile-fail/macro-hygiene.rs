@@ -0,0 +1,22 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// An expression passed to a macro can't see the macro's own bindings.
+
+macro_rules! with_tmp(
+    ($e:expr) => ({
+        let tmp = 5;
+        $e
+    })
+)
+
+fn main() {
+    let _y = with_tmp!(tmp + 1); //~ ERROR unresolved name
+}
@@ -0,0 +1,26 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:--test
+
+#[test]
+#[timeout(100)]
+//~^ ERROR timeout attribute must be of the form #[timeout = "ms"]
+fn list() { }
+
+#[test]
+#[timeout = "0"]
+//~^ ERROR timeout attribute must be of the form #[timeout = "ms"]
+fn zero() { }
+
+#[test]
+#[timeout = "fast"]
+//~^ ERROR timeout attribute must be of the form #[timeout = "ms"]
+fn word() { }
@@ -0,0 +1,34 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:-g
+// debugger:break 33
+// debugger:run
+// debugger:print a->boxed
+// check:$1 = 1
+// debugger:print b->boxed
+// check:$2 = 2.5
+// debugger:print c->boxed.x
+// check:$3 = 3
+// debugger:print *d
+// check:$4 = 4
+// debugger:print v->boxed.data[1]
+// check:$5 = 2
+
+struct Point { x: int, y: int }
+
+fn main() {
+    let a = @1;
+    let b = ~2.5;
+    let c = @Point { x: 3, y: 4 };
+    let d = &c.y;
+    let v = ~[1, 2, 3];
+    debug!("%? %? %? %? %?", a, b, c, d, v);
+}
@@ -0,0 +1,62 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:-g
+// debugger:break 61
+// debugger:break 50
+// debugger:break 58
+// debugger:break 51
+// debugger:run
+// debugger:print add.code != 0
+// check:$1 = 1
+// debugger:print add.env != 0
+// check:$2 = 1
+// debugger:print shape.vtable != 0
+// check:$3 = 1
+// debugger:print shape.box != 0
+// check:$4 = 1
+// debugger:continue
+// debugger:print f.code != 0
+// check:$5 = 1
+// debugger:print f.env != 0
+// check:$6 = 1
+// debugger:print shape.vtable != 0
+// check:$7 = 1
+// debugger:continue
+// debugger:print n
+// check:$8 = 10
+// debugger:print m
+// check:$9 = 11
+// debugger:continue
+// debugger:print r
+// check:$10 = 12
+
+trait Shape { fn area(&self) -> int; }
+
+struct Square { side: int }
+
+impl Square: Shape {
+    fn area(&self) -> int { self.side * self.side }
+}
+
+fn call(f: fn@(int) -> int, shape: @Shape) -> int {
+    let r = f(10);
+    r + shape.area() - 4
+}
+
+fn main() {
+    let k = 1;
+    let add = fn@(n: int) -> int {
+        let m = n + k;
+        m + k
+    };
+    let shape = @Square { side: 2 } as @Shape;
+    debug!("%d", call(add, shape));
+}
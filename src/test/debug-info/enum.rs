@@ -0,0 +1,42 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:-g
+// debugger:break 41
+// debugger:run
+// debugger:print some.discriminant
+// check:$1 = Some
+// debugger:print some.variants.Some.__0
+// check:$2 = 5
+// debugger:print none.discriminant
+// check:$3 = None
+// debugger:print color
+// check:$4 = Green
+// debugger:print pair.variants.Pair.__1
+// check:$5 = 7
+// debugger:print list.discriminant
+// check:$6 = Cons
+// debugger:print list.variants.Cons.__1->boxed.variants.Cons.__0
+// check:$7 = 2
+
+enum Color { Red, Green, Blue }
+
+enum Pair { Pair(int, int) }
+
+enum List { Cons(int, @List), Nil }
+
+fn main() {
+    let some = Some(5);
+    let none: Option<int> = None;
+    let color = Green;
+    let pair = Pair(6, 7);
+    let list = Cons(1, @Cons(2, @Nil));
+    debug!("%? %? %? %? %?", some, none, color, pair, list);
+}
@@ -0,0 +1,50 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:-g
+// debugger:break 36
+// debugger:break 41
+// debugger:break 49
+// debugger:run
+// debugger:print x
+// check:$1 = 1
+// debugger:print pair.__1
+// check:$2 = 1
+// debugger:continue
+// debugger:print x
+// check:$3 = 2.5
+// debugger:print pair.__0
+// check:$4 = 2.5
+// debugger:continue
+// debugger:print w
+// check:$5 = {value = 3}
+// debugger:continue
+// debugger:print w
+// check:$6 = {value = {__0 = 1, __1 = 1}}
+
+struct Wrapper<T> { value: T }
+
+fn dup<T: Copy>(x: T) -> (T, T) {
+    let pair = (x, x);
+    pair
+}
+
+fn wrap<T: Copy>(x: T) -> Wrapper<T> {
+    let w = Wrapper { value: x };
+    w
+}
+
+fn main() {
+    let a = dup(1);
+    let b = dup(2.5);
+    let c = wrap(3);
+    let w = Wrapper { value: a };
+    debug!("%? %? %? %?", a, b, c, w);
+}
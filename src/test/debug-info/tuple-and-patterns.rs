@@ -0,0 +1,36 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:-g
+// debugger:break 31
+// debugger:run
+// debugger:print t.__0
+// check:$1 = 1
+// debugger:print t.__1
+// check:$2 = 2.5
+// debugger:print a
+// check:$3 = 3
+// debugger:print b
+// check:$4 = 4
+// debugger:print x
+// check:$5 = 5
+// debugger:print y
+// check:$6 = 6
+
+struct Point { x: int, y: int }
+
+fn unpack((a, b): (int, int), Point { x, y }: Point) {
+    let t = (1, 2.5);
+    debug!("%? %? %? %? %?", t, a, b, x, y);
+}
+
+fn main() {
+    unpack((3, 4), Point { x: 5, y: 6 });
+}
@@ -0,0 +1,22 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// error-pattern:test spins timed out after 100 ms
+// compile-flags:--test
+
+extern mod std;
+
+// The test never yields, so its task can't be killed; the harness has
+// to report the timeout and exit without waiting for it
+#[test]
+#[timeout = "100"]
+fn spins() {
+    loop {}
+}
@@ -0,0 +1,64 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// xfail-fast
+// compile-flags:--c-header
+
+// Checks the header that --c-header writes next to the executable
+
+mod geo {
+    pub struct Point { x: i32, y: i32 }
+}
+
+pub struct Point { x: f64, y: f64 }
+
+pub struct Line { from: geo::Point, to: geo::Point }
+
+pub struct Grid { cells: [[u8 * 3] * 2], origin: *Point }
+
+#[no_mangle]
+pub extern fn grid_line(grid: *Grid, out: *mut Line) -> bool {
+    grid.is_null() || out.is_null()
+}
+
+#[no_mangle]
+pub extern fn fill(buf: *mut [u8 * 16], names: **mut u8) -> uint {
+    if buf.is_null() || names.is_null() { 0u } else { 16u }
+}
+
+pub fn main() {
+    let path = Path(os::args()[0]).with_filetype("h");
+    let header = result::unwrap(io::read_whole_file_str(&path));
+
+    // The include guard is named after the file
+    let lines = str::lines(header);
+    let guard = str::slice(lines[2], 8u, lines[2].len());
+    assert str::starts_with(guard, "C_HEADER_OUTPUT");
+    assert str::ends_with(guard, "_H");
+
+    let expected =
+        ~"/* Generated by rustc --c-header. Do not edit. */\n\n" +
+        fmt!("#ifndef %s\n#define %s\n\n", guard, guard) +
+        ~"#include <stdbool.h>\n#include <stdint.h>\n\n" +
+        ~"#ifdef __cplusplus\nextern \"C\" {\n#endif\n\n" +
+        ~"struct Point {\n    double x;\n    double y;\n};\n\n" +
+        ~"struct Grid {\n    uint8_t cells[2][3];\n" +
+        ~"    const struct Point *origin;\n};\n\n" +
+        ~"struct geo_Point {\n    int32_t x;\n    int32_t y;\n};\n\n" +
+        ~"struct Line {\n    struct geo_Point from;\n" +
+        ~"    struct geo_Point to;\n};\n\n" +
+        ~"bool grid_line(const struct Grid *grid, struct Line *out);\n" +
+        ~"uintptr_t fill(uint8_t (*buf)[16], uint8_t *const *names);\n\n" +
+        ~"#ifdef __cplusplus\n}\n#endif\n\n" +
+        fmt!("#endif /* %s */\n", guard);
+    assert header == expected;
+
+    os::remove_file(&path);
+}
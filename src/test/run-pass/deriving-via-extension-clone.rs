@@ -0,0 +1,43 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[deriving_clone]
+#[deriving_eq]
+struct Point<T> {
+    x: T,
+    y: T
+}
+
+#[deriving_clone]
+#[deriving_eq]
+struct Name(~str, uint);
+
+#[deriving_clone]
+#[deriving_eq]
+enum Shape {
+    Circle(Point<int>, uint),
+    Rect { corner: Point<int>, size: ~[uint] },
+    Empty
+}
+
+pub fn main() {
+    let p = Point { x: ~1, y: ~2 };
+    assert p.clone() == p;
+
+    let n = Name(~"bob", 3);
+    assert n.clone() == n;
+
+    let shapes = [Circle(Point { x: 1, y: 2 }, 3),
+                  Rect { corner: Point { x: 0, y: 0 }, size: ~[4, 5] },
+                  Empty];
+    for shapes.each |s| {
+        assert s.clone() == *s;
+    }
+}
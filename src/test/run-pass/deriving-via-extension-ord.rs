@@ -0,0 +1,51 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[deriving_eq]
+#[deriving_ord]
+struct Version {
+    major: uint,
+    minor: uint
+}
+
+#[deriving_eq]
+#[deriving_ord]
+struct Pair<T>(T, ~str);
+
+#[deriving_eq]
+#[deriving_ord]
+enum Token {
+    Number(int),
+    Word { text: ~str },
+    End
+}
+
+pub fn main() {
+    let a = Version { major: 1, minor: 9 };
+    let b = Version { major: 2, minor: 0 };
+    assert a < b && a <= b && b > a && b >= a;
+    assert a <= a && a >= a && !(a < a) && !(a > a);
+
+    assert Pair(1, ~"b") < Pair(2, ~"a");
+    assert Pair(1, ~"a") < Pair(1, ~"b");
+    assert Pair(1, ~"a") >= Pair(1, ~"a");
+
+    // Variants are ordered as they are declared
+    let tokens = ~[Number(-5), Number(3), Word { text: ~"a" },
+                   Word { text: ~"b" }, End];
+    for uint::range(0, tokens.len()) |i| {
+        for uint::range(0, tokens.len()) |j| {
+            assert (tokens[i] < tokens[j]) == (i < j);
+            assert (tokens[i] <= tokens[j]) == (i <= j);
+            assert (tokens[i] >= tokens[j]) == (i >= j);
+            assert (tokens[i] > tokens[j]) == (i > j);
+        }
+    }
+}
@@ -0,0 +1,42 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[deriving_to_str]
+struct Point {
+    x: int,
+    y: int
+}
+
+#[deriving_to_str]
+struct Wrapper<T>(T, ~str);
+
+#[deriving_to_str]
+enum Shape {
+    Circle(Point, uint),
+    Rect { corner: Point, size: ~[uint] },
+    Empty
+}
+
+pub fn main() {
+    let p = Point { x: 1, y: -2 };
+    assert p.to_str() == ~"Point { x: 1, y: -2 }";
+
+    let w = Wrapper(true, ~"hi");
+    assert w.to_str() == ~"Wrapper(true, hi)";
+
+    let c = Circle(Point { x: 0, y: 0 }, 5);
+    assert c.to_str() == ~"Circle(Point { x: 0, y: 0 }, 5)";
+
+    let r = Rect { corner: Point { x: 1, y: 1 }, size: ~[2, 3] };
+    assert r.to_str() ==
+        ~"Rect { corner: Point { x: 1, y: 1 }, size: [2, 3] }";
+
+    assert Empty.to_str() == ~"Empty";
+}
@@ -0,0 +1,61 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Structs of floats are passed in SSE registers, two f32s to a register
+
+struct TwoF32s { a: f32, b: f32 }
+struct ThreeF32s { a: f32, b: f32, c: f32 }
+struct TwoF64s { a: f64, b: f64 }
+struct F32sAndF64 { a: f32, b: f32, c: f64 }
+struct F32Array { a: [f32 * 4] }
+
+#[nolink]
+extern mod rustrt {
+    pub fn rust_dbg_abi_two_f32s(++s: TwoF32s) -> TwoF32s;
+    pub fn rust_dbg_abi_three_f32s(++s: ThreeF32s) -> ThreeF32s;
+    pub fn rust_dbg_abi_two_f64s(++s: TwoF64s) -> TwoF64s;
+    pub fn rust_dbg_abi_f32s_and_f64(++s: F32sAndF64) -> F32sAndF64;
+    pub fn rust_dbg_abi_f32_array(++s: F32Array) -> F32Array;
+}
+
+#[cfg(target_arch = "x86_64")]
+fn test() {
+    unsafe {
+        let r = rustrt::rust_dbg_abi_two_f32s(
+            TwoF32s { a: 1.5f32, b: 2.5f32 });
+        assert r.a == 3.5f32 && r.b == 0.5f32;
+
+        let r = rustrt::rust_dbg_abi_three_f32s(
+            ThreeF32s { a: 1.5f32, b: 2.5f32, c: 3.5f32 });
+        assert r.a == 4.5f32 && r.b == 2.5f32 && r.c == 0.5f32;
+
+        let r = rustrt::rust_dbg_abi_two_f64s(
+            TwoF64s { a: 1.25f64, b: 2.25f64 });
+        assert r.a == 3.25f64 && r.b == 0.25f64;
+
+        let r = rustrt::rust_dbg_abi_f32s_and_f64(
+            F32sAndF64 { a: 1.5f32, b: 2.5f32, c: 3.25f64 });
+        assert r.a == 3.5f32 && r.b == 0.5f32 && r.c == 6.5f64;
+
+        let r = rustrt::rust_dbg_abi_f32_array(
+            F32Array { a: [1f32, 2f32, 3f32, 4f32] });
+        assert r.a[0] == 4f32 && r.a[1] == 3f32;
+        assert r.a[2] == 2f32 && r.a[3] == 1f32;
+    }
+}
+
+#[cfg(target_arch = "x86")]
+#[cfg(target_arch = "arm")]
+fn test() {
+}
+
+pub fn main() {
+    test();
+}
@@ -0,0 +1,62 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Structs of integers are passed in integer registers, the last of which
+// may be only partly filled
+
+struct TwoU8s { a: u8, b: u8 }
+struct ThreeU16s { a: u16, b: u16, c: u16 }
+struct TwoU32s { a: u32, b: u32 }
+struct ThreeU32s { a: u32, b: u32, c: u32 }
+struct TwoU64s { a: u64, b: u64 }
+
+#[nolink]
+extern mod rustrt {
+    pub fn rust_dbg_abi_two_u8s(++s: TwoU8s) -> TwoU8s;
+    pub fn rust_dbg_abi_three_u16s(++s: ThreeU16s) -> ThreeU16s;
+    pub fn rust_dbg_abi_two_u32s(++s: TwoU32s) -> TwoU32s;
+    pub fn rust_dbg_abi_three_u32s(++s: ThreeU32s) -> ThreeU32s;
+    pub fn rust_dbg_abi_two_u64s(++s: TwoU64s) -> TwoU64s;
+}
+
+#[cfg(target_arch = "x86_64")]
+fn test() {
+    unsafe {
+        let r = rustrt::rust_dbg_abi_two_u8s(TwoU8s { a: 10u8, b: 20u8 });
+        assert r.a == 21u8 && r.b == 11u8;
+
+        let r = rustrt::rust_dbg_abi_three_u16s(
+            ThreeU16s { a: 0x1111u16, b: 0x2222u16, c: 0x3333u16 });
+        assert r.a == 0x3334u16 && r.b == 0x2222u16 && r.c == 0x1112u16;
+
+        let r = rustrt::rust_dbg_abi_two_u32s(
+            TwoU32s { a: 0xaaaa_aaaau32, b: 0xbbbb_bbbbu32 });
+        assert r.a == 0xbbbb_bbbcu32 && r.b == 0xaaaa_aaabu32;
+
+        let r = rustrt::rust_dbg_abi_three_u32s(
+            ThreeU32s { a: 1u32, b: 2u32, c: 0xcccc_ccccu32 });
+        assert r.a == 0xcccc_cccdu32 && r.b == 2u32 && r.c == 2u32;
+
+        let r = rustrt::rust_dbg_abi_two_u64s(
+            TwoU64s { a: 0xaaaa_aaaa_aaaa_aaaau64,
+                      b: 0xbbbb_bbbb_bbbb_bbbbu64 });
+        assert r.a == 0xbbbb_bbbb_bbbb_bbbcu64;
+        assert r.b == 0xaaaa_aaaa_aaaa_aaabu64;
+    }
+}
+
+#[cfg(target_arch = "x86")]
+#[cfg(target_arch = "arm")]
+fn test() {
+}
+
+pub fn main() {
+    test();
+}
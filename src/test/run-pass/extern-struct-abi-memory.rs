@@ -0,0 +1,68 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Structs larger than two eightbytes are passed on the stack and returned
+// through a hidden pointer, as are structs that don't fit in the registers
+// left after the arguments before them
+
+struct TwoU64s { a: u64, b: u64 }
+struct ThreeU64s { a: u64, b: u64, c: u64 }
+struct TwoF64s { a: f64, b: f64 }
+struct ThreeF64s { a: f64, b: f64, c: f64 }
+
+#[nolink]
+extern mod rustrt {
+    pub fn rust_dbg_abi_three_u64s(++s: ThreeU64s) -> ThreeU64s;
+    pub fn rust_dbg_abi_three_f64s(++s: ThreeF64s) -> ThreeF64s;
+    pub fn rust_dbg_abi_u64s_then_two_u64s(a: u64, b: u64, c: u64, d: u64,
+                                           e: u64, ++s: TwoU64s)
+                                        -> TwoU64s;
+    pub fn rust_dbg_abi_f64s_then_two_f64s(a: f64, b: f64, c: f64, d: f64,
+                                           e: f64, f: f64, g: f64,
+                                           ++s: TwoF64s) -> TwoF64s;
+    pub fn rust_dbg_abi_sret_then_two_u64s(a: u64, b: u64, c: u64, d: u64,
+                                           ++s: TwoU64s) -> ThreeU64s;
+}
+
+#[cfg(target_arch = "x86_64")]
+fn test() {
+    unsafe {
+        let r = rustrt::rust_dbg_abi_three_u64s(
+            ThreeU64s { a: 1u64, b: 2u64, c: 0xcccc_cccc_cccc_ccccu64 });
+        assert r.a == 0xcccc_cccc_cccc_cccdu64;
+        assert r.b == 2u64 && r.c == 2u64;
+
+        let r = rustrt::rust_dbg_abi_three_f64s(
+            ThreeF64s { a: 1.5f64, b: 2.5f64, c: 3.5f64 });
+        assert r.a == 4.5f64 && r.b == 2.5f64 && r.c == 0.5f64;
+
+        let r = rustrt::rust_dbg_abi_u64s_then_two_u64s(
+            1u64, 2u64, 3u64, 4u64, 5u64, TwoU64s { a: 6u64, b: 7u64 });
+        assert r.a == 21u64 && r.b == 7u64;
+
+        let r = rustrt::rust_dbg_abi_f64s_then_two_f64s(
+            1f64, 2f64, 3f64, 4f64, 5f64, 6f64, 7f64,
+            TwoF64s { a: 8f64, b: 9f64 });
+        assert r.a == 36f64 && r.b == 9f64;
+
+        let r = rustrt::rust_dbg_abi_sret_then_two_u64s(
+            1u64, 2u64, 3u64, 4u64, TwoU64s { a: 5u64, b: 6u64 });
+        assert r.a == 10u64 && r.b == 5u64 && r.c == 6u64;
+    }
+}
+
+#[cfg(target_arch = "x86")]
+#[cfg(target_arch = "arm")]
+fn test() {
+}
+
+pub fn main() {
+    test();
+}
@@ -0,0 +1,57 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Each eightbyte of a struct goes in an integer register if it holds any
+// integer, and in an SSE register otherwise
+
+struct U32AndF32 { a: u32, b: f32 }
+struct F64AndU32 { a: f64, b: u32 }
+struct U16sAndF32 { a: [u16 * 3], b: f32 }
+struct TwoU8s { a: u8, b: u8 }
+struct Nested { a: TwoU8s, b: f64 }
+
+#[nolink]
+extern mod rustrt {
+    pub fn rust_dbg_abi_u32_and_f32(++s: U32AndF32) -> U32AndF32;
+    pub fn rust_dbg_abi_f64_and_u32(++s: F64AndU32) -> F64AndU32;
+    pub fn rust_dbg_abi_u16s_and_f32(++s: U16sAndF32) -> U16sAndF32;
+    pub fn rust_dbg_abi_nested(++s: Nested) -> Nested;
+}
+
+#[cfg(target_arch = "x86_64")]
+fn test() {
+    unsafe {
+        let r = rustrt::rust_dbg_abi_u32_and_f32(
+            U32AndF32 { a: 0xaaaa_aaaau32, b: 1.5f32 });
+        assert r.a == 0xaaaa_aaabu32 && r.b == 3f32;
+
+        let r = rustrt::rust_dbg_abi_f64_and_u32(
+            F64AndU32 { a: 1.5f64, b: 0xbbbb_bbbbu32 });
+        assert r.a == 3f64 && r.b == 0xbbbb_bbbcu32;
+
+        let r = rustrt::rust_dbg_abi_u16s_and_f32(
+            U16sAndF32 { a: [1u16, 2u16, 3u16], b: 1.5f32 });
+        assert r.a[0] == 3u16 && r.a[1] == 2u16 && r.a[2] == 1u16;
+        assert r.b == 3f32;
+
+        let r = rustrt::rust_dbg_abi_nested(
+            Nested { a: TwoU8s { a: 1u8, b: 2u8 }, b: 1.5f64 });
+        assert r.a.a == 2u8 && r.a.b == 1u8 && r.b == 3f64;
+    }
+}
+
+#[cfg(target_arch = "x86")]
+#[cfg(target_arch = "arm")]
+fn test() {
+}
+
+pub fn main() {
+    test();
+}
@@ -0,0 +1,67 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// xfail-pretty - token trees can't pretty print
+
+// The temporaries a macro binds don't capture the caller's variables.
+
+macro_rules! add_twice(
+    ($e:expr) => ({
+        let x = $e;
+        x + x
+    })
+)
+
+macro_rules! add_one_via_closure(
+    ($e:expr) => ({
+        let f = |x: int| $e + x;
+        f(1)
+    })
+)
+
+macro_rules! unwrap_or_zero(
+    ($opt:expr, $e:expr) => (
+        match $opt {
+            Some(v) => v + $e,
+            None => $e
+        }
+    )
+)
+
+macro_rules! swap_via_tmp(
+    ($a:ident, $b:ident) => ({
+        let tmp = $a;
+        $a = $b;
+        $b = tmp;
+    })
+)
+
+pub fn main() {
+    let x = 10;
+    assert add_twice!(x + 1) == 22;
+
+    assert add_one_via_closure!(x) == 11;
+
+    let v = 100;
+    assert unwrap_or_zero!(Some(1), v) == 101;
+    assert unwrap_or_zero!(None, v) == 100;
+
+    let mut tmp = 1;
+    let mut other = 2;
+    swap_via_tmp!(tmp, other);
+    assert tmp == 2 && other == 1;
+
+    // A binding the caller passes in is still visible to the caller.
+    macro_rules! bind(
+        ($x:ident, $e:expr) => ( let $x = $e; )
+    );
+    bind!(y, 3);
+    assert y == 3;
+}
@@ -0,0 +1,22 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// xfail-fast
+// aux-build:static_lib_native.rs
+
+// The auxiliary crate is built as a static library, so its code and its
+// native dependency have to be linked into this one
+
+extern mod static_lib_native;
+
+pub fn main() {
+    assert static_lib_native::twice(21u) == 42u;
+    assert static_lib_native::cosine(0f64) == 1f64;
+}
@@ -0,0 +1,52 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Positional and named arguments, and widths and precisions taken from
+// arguments, in fmt!
+
+fn test(actual: ~str, expected: ~str) {
+    log(debug, copy actual);
+    log(debug, copy expected);
+    assert (actual == expected);
+}
+
+fn counted(count: &mut uint) -> uint {
+    *count += 1u;
+    *count
+}
+
+pub fn main() {
+    // Positional
+    test(fmt!("%2$s %1$s", "world", "hello"), ~"hello world");
+    test(fmt!("%1$d %1$d %1$d", 7), ~"7 7 7");
+    test(fmt!("%1$s-%s-%s", ~"a", ~"b"), ~"a-a-b");
+
+    // Named
+    test(fmt!("%(name)s is %(age)u", name = "Ada", age = 36u),
+         ~"Ada is 36");
+    test(fmt!("%(x)d,%(x)d", x = -1), ~"-1,-1");
+    test(fmt!("%s and %(b)s", "a", b = "b"), ~"a and b");
+
+    // Widths and precisions from arguments
+    test(fmt!("[%*d]", 5u, 42), ~"[   42]");
+    test(fmt!("[%-*d]", 5u, 42), ~"[42   ]");
+    test(fmt!("[%.*f]", 2u, 3.14159), ~"[3.14]");
+    test(fmt!("[%*.*f]", 7u, 1u, 3.14159), ~"[    3.1]");
+    test(fmt!("[%2$*s]", 4u, "hi"), ~"[  hi]");
+    test(fmt!("[%2$*1$s]", 3u, "x"), ~"[  x]");
+    test(fmt!("[%(v)*(w)s]", v = "ab", w = 4u), ~"[  ab]");
+
+    // An argument used more than once is only evaluated once
+    let mut count = 0u;
+    test(fmt!("%1$u %1$u", counted(&mut count)), ~"1 1");
+    assert count == 1u;
+    let s = ~"owned";
+    test(fmt!("%(s)s %(s)s", s = s), ~"owned owned");
+}